@@ -1,4 +1,7 @@
+pub use crate::ast::parse_ast::Pos;
 pub use crate::ast::BiOp;
+use crate::frontend::typing::source_name;
+use codespan_reporting::files::{Files, SimpleFiles};
 use global_counter::global_counter;
 use std::{cell::RefCell, hash::Hash, ops::Deref, rc::Rc};
 //this is just used to get a unique value.
@@ -41,11 +44,63 @@ impl<T: Hash> Hash for RCell<T> {
         (self.0).1.hash(state);
     }
 }
-//the main program. Only needs the name and value of the outputs, and the size of the inputs.
+//the main program. Only needs the name and value of the outputs, and the name and size of the inputs.
+//The probes are the named shared variables and states, used to trace the simulation
+//and to name the nodes in the outputs of the compiler.
+//The locations are the nodes of the variables named by the user, with where they are assigned or declared.
+//They are only known for the programs compiled from a .tj file, as the conflicts : the shared variables
+//assigned by two states which may be active together, with the nodes which tell whether each state is active,
+//and the files the locations point to.
 #[derive(Debug, Clone)]
 pub struct FlatProgramGraph {
     pub outputs: Vec<(String, RCell<Node>)>,
-    pub inputs: Vec<usize>,
+    pub inputs: Vec<(String, usize)>,
+    pub probes: Vec<(String, RCell<Node>)>,
+    pub locations: Vec<(String, Pos, RCell<Node>)>,
+    pub conflicts: Vec<(String, RCell<Node>, RCell<Node>)>,
+    pub files: Option<Rc<SimpleFiles<String, String>>>,
+}
+
+impl FlatProgramGraph {
+    pub fn input_sizes(&self) -> Vec<usize> {
+        self.inputs.iter().map(|(_, s)| *s).collect()
+    }
+
+    //the name given by the user to some of the nodes (the first probe using it)
+    pub fn node_names(&self) -> std::collections::HashMap<u32, &String> {
+        let mut names = std::collections::HashMap::new();
+        for (name, node) in &self.probes {
+            names.entry(node.id()).or_insert(name);
+        }
+        names
+    }
+
+    //where the variables named by the user are assigned, as "name file:line", for the comments of the backends
+    pub fn node_sources(&self) -> std::collections::HashMap<u32, String> {
+        let mut sources = std::collections::HashMap::new();
+        let files = match &self.files {
+            Some(files) => files,
+            None => return sources,
+        };
+        //the inlining renames the variables with counters, so only the name in the source is written.
+        //a node can be assigned to several variables : the first one in the source is kept
+        let mut locations: Vec<_> = self
+            .locations
+            .iter()
+            .map(|(name, loc, node)| (source_name(name), loc, node))
+            .collect();
+        locations.sort_by(|(name1, loc1, _), (name2, loc2, _)| (loc1, name1).cmp(&(loc2, name2)));
+        for (name, (file, start, _), node) in locations {
+            if let (Some(file_name), Some(line)) =
+                (files.name(*file), files.line_index(*file, *start))
+            {
+                sources
+                    .entry(node.id())
+                    .or_insert_with(|| format!("{} {}:{}", name, file_name, line + 1));
+            }
+        }
+        sources
+    }
 }
 
 //A "Node" of the dataflow graph is an operation
@@ -58,8 +113,10 @@ pub enum Node {
     BiOp(BiOp, RCell<Node>, RCell<Node>),
     Mux(RCell<Node>, RCell<Node>, RCell<Node>),
     Reg(usize, RCell<Node>), //The size is still specified
-    Ram(RCell<Node>, RCell<Node>, RCell<Node>, RCell<Node>),
-    Rom(usize, RCell<Node>), //Size specified here as well
+    //address size, word size, read address, write enable, write address, write data
+    Ram(usize, usize, RCell<Node>, RCell<Node>, RCell<Node>, RCell<Node>),
+    //name of the variable it is assigned to, address size, word size, read address
+    Rom(String, usize, usize, RCell<Node>),
     TmpValueHolder(usize), //This is used while building the graph. All instance of this are removed.
 }
 //As this struct can be recursive, I needed to make my own pretty-printer
@@ -110,7 +167,7 @@ fn print_node(node: &Node, in_reg: bool, indent: String) -> String {
                 )
             }
         }
-        Node::Ram(e1, e2, e3, e4) => format!(
+        Node::Ram(_, _, e1, e2, e3, e4) => format!(
             "Ram \n{}{} \n{}{} \n{}{} \n{}{}",
             indent,
             print_node(&*e1.borrow(), in_reg, indent.clone() + " "),
@@ -121,8 +178,9 @@ fn print_node(node: &Node, in_reg: bool, indent: String) -> String {
             indent,
             print_node(&*e4.borrow(), in_reg, indent.clone() + " ")
         ),
-        Node::Rom(_, e) => format!(
-            "Rom {}",
+        Node::Rom(name, _, _, e) => format!(
+            "Rom {} {}",
+            name,
             print_node(&*e.borrow(), in_reg, indent.clone() + " ")
         ),
         Node::TmpValueHolder(i) => format!("Temp value {}", i),
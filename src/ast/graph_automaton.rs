@@ -1,3 +1,4 @@
+pub use crate::ast::parse_ast::Pos;
 pub use crate::ast::BiOp;
 use std::{cell::Cell, hash::Hash};
 use std::{hash::Hasher, rc::Rc};
@@ -36,8 +37,15 @@ pub enum ExprOperation {
     BiOp(BiOp, Rc<ExprNode>, Rc<ExprNode>),
     Mux(Rc<ExprNode>, Rc<ExprNode>, Rc<ExprNode>),
     Reg(usize, Option<Rc<ExprNode>>), //size, state. None means a reference to itself, as we cannot have cycles here.
-    Ram(Rc<ExprNode>, Rc<ExprNode>, Rc<ExprNode>, Rc<ExprNode>),
-    Rom(usize, Rc<ExprNode>),
+    Ram(
+        usize,
+        usize,
+        Rc<ExprNode>,
+        Rc<ExprNode>,
+        Rc<ExprNode>,
+        Rc<ExprNode>,
+    ), //addr size, word size, ...
+    Rom(String, usize, usize, Rc<ExprNode>), //name, addr size, word size, read addr
     Last(usize),
 }
 //the default value
@@ -52,10 +60,12 @@ impl Default for ExprOperation {
 pub struct ProgramState {
     pub shared_outputs: Vec<(usize, Rc<ExprNode>)>, //All the shared variables assigned to by the state
     //The transitions
-    pub transition_outputs: Vec<(Option<usize>, Rc<ExprNode>, bool)>, //state_id, var, reset
+    pub transition_outputs: Vec<(Option<usize>, Rc<ExprNode>, bool, bool)>, //state_id, var, reset, instant
     pub inputs: Vec<usize>, //All the shared variables used in the state (not in transitions)
-    pub weak: bool,         //unused
+    pub weak: bool,         //strong transitions are taken at the beginning of the cycle
+    pub parent: Option<usize>, //the state which must be active for this one to be active
     pub n_vars: usize,      //maximum node id used
+    pub locations: Vec<(String, Rc<ExprNode>, Pos)>, //the variables named by the user, their node and where they are assigned
 }
 #[derive(Debug, Clone)]
 pub struct ProgramGraph {
@@ -64,5 +74,8 @@ pub struct ProgramGraph {
     pub shared: Vec<Vec<bool>>, //init value of each shared variable
     pub schedule: Vec<usize>, //At some point the nodes were scheduled. It is no longer the case, so unused
     pub outputs: Vec<(String, usize)>,
-    pub inputs: Vec<usize>,
+    pub inputs: Vec<(String, usize)>, //name and size of each input
+    pub probes: Vec<(String, usize)>, //shared variables and states named by the user, for debugging
+    pub locations: Vec<(String, usize, Pos)>, //where the variables named by the user are declared
+    pub conflicts: Vec<(String, usize, usize)>, //shared variables assigned by two states which can be active together
 }
@@ -14,3 +14,15 @@ pub enum BiOp {
     Nand, //-*
     Concat,
 }
+//operations on buses read as unsigned numbers. They are lowered to gates when the graph is built,
+//so they never reach the backends
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum ArithOp {
+    Add, //+, modulo 2^n
+    Sub, //-, modulo 2^n
+    Eq,  //==
+    Neq, // !=
+    Lt,  //<
+    Shl, //<<, by the value of a bus
+    Shr, //>>
+}
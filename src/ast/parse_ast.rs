@@ -14,7 +14,7 @@ use std::{
 use ahash::AHashMap;
 
 //Binary Operation are the same for every ast, so they are imported
-pub use crate::ast::BiOp;
+pub use crate::ast::{ArithOp, BiOp};
 
 //struct for storing the a position
 //the tuple is file_id, left index, right index
@@ -77,6 +77,7 @@ pub type Import = PathBuf; //an import is just a Path
 #[derive(Debug, Clone)]
 pub struct Automaton {
     pub name: String,
+    pub static_args: Vec<String>, //the sizes and constants given when the automaton is used
     pub inputs: Vec<Arg>,
     pub outputs: Vec<Arg>,
     pub shared: Vec<VarAssign>, //Variables shared across states and automata must be declared
@@ -107,6 +108,9 @@ pub struct ExtAutomaton {
     pub inputs: Loc<Vec<Loc<Expr>>>,
     pub outputs: Loc<Vec<Loc<Var>>>,
     pub name: Loc<Var>,
+    pub static_args: Loc<Vec<Const>>,
+    //the inlined calls this one comes from, with their static arguments
+    pub callers: Vec<Loc<String>>,
 }
 //a state
 #[derive(Debug, Clone)]
@@ -114,13 +118,16 @@ pub struct State {
     pub name: Loc<String>,
     pub statements: Vec<Statement>,
     pub transitions: Vec<Transition>,
-    pub weak: bool, //unused currently
+    pub weak: bool, //until (weak) or unless (strong) transitions
+    //the state calling the automaton this state was inlined from. It must be active for this one to be.
+    pub parent: Option<Var>,
 }
 #[derive(Debug, Clone)]
 pub struct Transition {
     pub condition: Loc<TrCond>,
     pub state: Loc<Option<Var>>,
     pub reset: bool,
+    pub instant: bool, //the state is entered in the same cycle (=>), instead of the next one (->)
 }
 //We have to track whether the transition was <Expr> -> <State> or <Default> -> <State>
 #[derive(Debug, Clone)]
@@ -171,7 +178,45 @@ pub struct FnAssign {
 #[derive(Debug, Clone)]
 pub enum ConstExpr {
     Known(Vec<bool>),
-    Unknown(bool, Loc<Const>), //Const bits initialized to 0
+    Unknown(bool, Loc<Const>),       //Const bits initialized to 0
+    Sized(Loc<Const>, Loc<Literal>), //a literal with an explicit width, like 8'hFF or n'd(k*2)
+}
+//the value of a literal with an explicit width
+#[derive(Debug, Clone)]
+pub enum Literal {
+    Bits(Vec<bool>), //written in hexadecimal, binary or decimal
+    Num(Const),      //a constant expression
+}
+//the bits of the digits of a binary (1 bit per digit) or hexadecimal (4 bits per digit) literal.
+//the underscores are separators
+pub fn digits_to_bits(digits: &str, bits_per_digit: u32) -> Vec<bool> {
+    digits
+        .chars()
+        .filter(|c| *c != '_')
+        .flat_map(|c| {
+            let d = c.to_digit(1 << bits_per_digit).unwrap();
+            (0..bits_per_digit).rev().map(move |i| (d >> i) & 1 == 1)
+        })
+        .collect()
+}
+//the bits of a decimal literal, as it can be wider than an integer
+pub fn decimal_to_bits(digits: &str) -> Vec<bool> {
+    //the bits from the least significant one, multiplied by 10 for each digit
+    let mut bits: Vec<bool> = Vec::new();
+    for d in digits.chars().filter(|c| *c != '_') {
+        let mut carry = d.to_digit(10).unwrap();
+        for b in &mut bits {
+            let v = *b as u32 * 10 + carry;
+            *b = v & 1 == 1;
+            carry = v >> 1;
+        }
+        while carry > 0 {
+            bits.push(carry & 1 == 1);
+            carry >>= 1;
+        }
+    }
+    bits.reverse();
+    bits
 }
 #[derive(Debug, Clone)]
 pub enum Expr {
@@ -179,6 +224,8 @@ pub enum Expr {
     Not(Box<Expr>), //Recursive struct need to be boxed. It just a kind of pointer.
     Slice(Box<Loc<Expr>>, Option<Const>, Option<Const>), //The two const are options because we can slice like this : a[1..]
     BiOp(BiOp, Box<Loc<Expr>>, Box<Loc<Expr>>),
+    Arith(ArithOp, Box<Loc<Expr>>, Box<Loc<Expr>>),
+    Builtin(Builtin, Box<Loc<Expr>>),
     Mux(Box<Loc<Expr>>, Box<Loc<Expr>>, Box<Loc<Expr>>),
     Var(Loc<Var>),
     Last(Loc<Var>),
@@ -187,11 +234,20 @@ pub enum Expr {
     Rom(RomStruct),
     FnCall(FnCall),
 }
+//the built-in functions on buses
+#[derive(Debug, Clone)]
+pub enum Builtin {
+    Repeat(Loc<Const>), //repeat<n>(x) is n copies of x
+    Reduce(BiOp),       //and_reduce(x), or_reduce(x), xor_reduce(x)
+    Reverse,            //reverse(x), the bits in the opposite order
+}
 #[derive(Debug, Clone)]
 pub struct FnCall {
     pub name: Loc<String>,
     pub args: Loc<Vec<Loc<Expr>>>,
     pub static_args: Loc<Vec<Const>>,
+    //the inlined calls this one comes from, with their static arguments
+    pub callers: Vec<Loc<String>>,
 }
 #[derive(Debug, Clone)]
 pub struct RamStruct {
@@ -208,10 +264,17 @@ pub struct RomStruct {
 
 #[derive(Debug, Clone)]
 pub struct IfStruct {
-    pub condition: Const,
+    pub condition: IfCondition,
     pub if_block: Vec<Statement>,
     pub else_block: Vec<Statement>,
 }
+//A constant condition selects one of the blocks at compile time.
+//A signal selects at each cycle the values of the variables assigned in the blocks.
+#[derive(Debug, Clone)]
+pub enum IfCondition {
+    Const(Const),
+    Signal(Loc<Var>),
+}
 #[derive(Debug, Clone)]
 pub enum Const {
     Value(i32),
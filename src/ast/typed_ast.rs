@@ -1,8 +1,10 @@
-pub use crate::ast::BiOp;
+pub use crate::ast::parse_ast::Pos;
+pub use crate::ast::{ArithOp, BiOp};
 use ahash::AHashMap;
 use std::ops::{Deref, DerefMut};
 /*
-A simpler, typed ast
+A simpler, typed ast.
+The only location information left is where the variables are declared and assigned, in side tables.
 */
 //a sized value, useful for typing expr.
 //It works just like "Loc" from the previous ast
@@ -32,6 +34,7 @@ pub struct Program {
     pub shared: AHashMap<SharedVar, Value>,
     pub states: AHashMap<Name, State>,
     pub init_states: Vec<Name>,
+    pub locations: AHashMap<SharedVar, Pos>, //where the shared variables, states and inputs are declared
 }
 
 //Just to be a bit more explicit in the ast, these are all strings
@@ -52,8 +55,10 @@ pub type Value = Vec<bool>;
 pub struct State {
     pub name: String,
     pub statements: AHashMap<Var, Expr>,
-    pub transitions: Vec<(Var, Option<Name>, bool)>,
+    pub transitions: Vec<(Var, Option<Name>, bool, bool)>, //condition, next state, reset, instant
     pub weak: bool,
+    pub parent: Option<Name>,
+    pub locations: AHashMap<Var, Pos>, //where each variable is assigned
 }
 
 pub type Expr = Sized<ExprType>;
@@ -73,11 +78,19 @@ pub enum ExprType {
     Not(ExprTerm),
     Slice(ExprTerm, usize, usize),
     BiOp(BiOp, ExprTerm, ExprTerm),
+    Arith(ArithOp, ExprTerm, ExprTerm),
+    Builtin(Builtin, ExprTerm),
     Mux(ExprTerm, ExprTerm, ExprTerm),
     Reg(ExprTerm),
     Last(SharedVar),
     Ram(RamStruct),
-    Rom(ExprTerm),
+    Rom(String, ExprTerm), //the name of the variable the rom is assigned to
+}
+#[derive(Debug, Clone)]
+pub enum Builtin {
+    Repeat(usize),
+    Reduce(BiOp),
+    Reverse,
 }
 #[derive(Debug, Clone)]
 pub struct RamStruct {
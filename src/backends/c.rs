@@ -0,0 +1,446 @@
+use std::{fmt::Display, io::Write};
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::ast::graph::*;
+use crate::util::{rom::RomImages, schedule::schedule};
+/*
+Generates a self-contained C program simulating the graph.
+Every node becomes a static array of 64 bits words (bit i of a bus is bit i%64 of word i/64),
+registers and rams become static arrays too, and roms constant arrays, filled with the rom images.
+tj_step computes one cycle with straight-line code, in the order given by util/schedule.rs.
+
+The generated program reads the inputs on stdin : at each cycle, each input (in order) is read
+as a sequence of 0s and 1s (its first bit first), whitespace being ignored.
+After each cycle, it prints one line : "name=bits" for each output, separated by spaces.
+It runs for the number of cycles given as its first argument, or until the end of the inputs.
+The number of cycles is required when all the inputs are empty, as there is nothing to read.
+
+To link it in another program, define TJ_NO_MAIN : the inputs are the arrays tj_in_<i>,
+the outputs the arrays tj_out_<name>, and tj_step computes a cycle.
+*/
+#[derive(Debug)]
+pub enum CError {
+    Io(std::io::Error),
+    CombinationalCycle,     //a cycle that does not go through a register
+    AddressTooLarge(usize), //rams are arrays, so their addresses are limited
+}
+
+impl From<std::io::Error> for CError {
+    fn from(err: std::io::Error) -> Self {
+        CError::Io(err)
+    }
+}
+
+impl Display for CError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CError::Io(err) => write!(f, "Error : cannot write the C code : {}", err),
+            CError::CombinationalCycle => write!(
+                f,
+                "Error : the program contains a combinational cycle, it cannot be compiled to C"
+            ),
+            CError::AddressTooLarge(n) => write!(
+                f,
+                "Error : the C backend only supports addresses up to {} bits, got {}",
+                MAX_ADDR_SIZE, n
+            ),
+        }
+    }
+}
+
+const MAX_ADDR_SIZE: usize = 24;
+
+const PRELUDE: &str = r#"#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define TJ_WORDS(n) ((n) == 0 ? 1 : ((n) + 63) / 64)
+
+static inline uint64_t tj_read_bits(const uint64_t *v, size_t start, size_t len) {
+    size_t w = start / 64, b = start % 64;
+    uint64_t res = v[w] >> b;
+    if (b != 0 && b + len > 64)
+        res |= v[w + 1] << (64 - b);
+    return len == 64 ? res : res & (((uint64_t)1 << len) - 1);
+}
+
+static inline void tj_write_bits(uint64_t *v, size_t start, size_t len, uint64_t bits) {
+    size_t w = start / 64, b = start % 64;
+    uint64_t mask = len == 64 ? ~(uint64_t)0 : (((uint64_t)1 << len) - 1);
+    v[w] = (v[w] & ~(mask << b)) | (bits << b);
+    if (b != 0 && b + len > 64)
+        v[w + 1] = (v[w + 1] & ~(mask >> (64 - b))) | (bits >> (64 - b));
+}
+
+static inline void tj_copy_bits(uint64_t *dst, size_t dst_start, const uint64_t *src, size_t src_start, size_t len) {
+    for (size_t i = 0; i < len; i += 64) {
+        size_t n = len - i < 64 ? len - i : 64;
+        tj_write_bits(dst, dst_start + i, n, tj_read_bits(src, src_start + i, n));
+    }
+}
+
+/* the first bit of an address is the most significant one */
+static inline uint64_t tj_address(const uint64_t *v, size_t len) {
+    uint64_t addr = 0;
+    for (size_t i = 0; i < len; i++)
+        addr = (addr << 1) | ((v[i / 64] >> (i % 64)) & 1);
+    return addr;
+}
+"#;
+
+const MAIN: &str = r#"
+#ifndef TJ_NO_MAIN
+static inline int tj_read_input(uint64_t *v, size_t len) {
+    memset(v, 0, TJ_WORDS(len) * sizeof(uint64_t));
+    for (size_t i = 0; i < len; i++) {
+        int c;
+        do {
+            c = getchar();
+        } while (c == ' ' || c == '\t' || c == '\n' || c == '\r');
+        if (c == EOF)
+            return 0;
+        if (c != '0' && c != '1') {
+            fprintf(stderr, "Error : unexpected character '%c' in the inputs\n", c);
+            exit(1);
+        }
+        if (c == '1')
+            v[i / 64] |= (uint64_t)1 << (i % 64);
+    }
+    return 1;
+}
+
+static inline void tj_print(const char *name, const uint64_t *v, size_t len) {
+    printf("%s=", name);
+    for (size_t i = 0; i < len; i++)
+        putchar((v[i / 64] >> (i % 64)) & 1 ? '1' : '0');
+}
+
+int main(int argc, char **argv) {
+    long long steps = argc > 1 ? atoll(argv[1]) : -1;
+"#;
+
+fn words(size: usize) -> usize {
+    size.div_ceil(64).max(1)
+}
+
+//the mask of the used bits of the last word of a bus
+fn last_mask(size: usize) -> u64 {
+    if size.is_multiple_of(64) {
+        u64::MAX
+    } else {
+        (1 << (size % 64)) - 1
+    }
+}
+
+//output names are used in C identifiers, with "_" appended when two of them give the same one
+fn c_names(prog: &FlatProgramGraph) -> Vec<String> {
+    let mut used = AHashSet::new();
+    prog.outputs
+        .iter()
+        .map(|(name, _)| {
+            let mut res: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            while !used.insert(res.clone()) {
+                res.push('_');
+            }
+            res
+        })
+        .collect()
+}
+
+fn write_words(dest: &mut impl Write, words: &[u64]) -> std::io::Result<()> {
+    for (i, w) in words.iter().enumerate() {
+        if i % 4 == 0 {
+            write!(dest, "\n   ")?;
+        }
+        write!(dest, " 0x{:016x},", w)?;
+    }
+    Ok(())
+}
+
+fn pack(bits: &[bool], size: usize) -> Vec<u64> {
+    let mut res = vec![0; words(size)];
+    for (i, b) in bits.iter().enumerate().take(size) {
+        res[i / 64] |= (*b as u64) << (i % 64);
+    }
+    res
+}
+
+pub fn to_c(prog: &FlatProgramGraph, roms: &RomImages, mut dest: impl Write) -> Result<(), CError> {
+    let order = schedule(prog).map_err(|_| CError::CombinationalCycle)?;
+    //name and size of every node
+    let mut vars: AHashMap<u32, (String, usize)> = AHashMap::new();
+    let var = |node: &RCell<Node>, vars: &AHashMap<u32, (String, usize)>| {
+        vars.get(&node.id()).unwrap().clone()
+    };
+    writeln!(dest, "/* Generated by tinyjazz */")?;
+    write!(dest, "{}", PRELUDE)?;
+    writeln!(dest)?;
+    for (i, (_, s)) in prog.inputs.iter().enumerate() {
+        writeln!(dest, "uint64_t tj_in_{}[{}];", i, words(*s))?;
+    }
+    //declarations
+    let (mut n_rams, mut n_roms) = (0, 0);
+    for node in &order {
+        let name = format!("v_{}", node.id());
+        let size = match &*node.borrow() {
+            Node::Input(i) => {
+                vars.insert(node.id(), (format!("tj_in_{}", i), prog.inputs[*i].1));
+                continue;
+            }
+            Node::Const(c) => {
+                write!(dest, "static const uint64_t {}[{}] = {{", name, words(c.len()))?;
+                write_words(&mut dest, &pack(c, c.len()))?;
+                writeln!(dest, "\n}};")?;
+                vars.insert(node.id(), (name, c.len()));
+                continue;
+            }
+            Node::Not(e) => var(e, &vars).1,
+            Node::Slice(_, c1, c2) => c2 - c1,
+            Node::BiOp(BiOp::Concat, e1, e2) => var(e1, &vars).1 + var(e2, &vars).1,
+            Node::BiOp(_, e, _) | Node::Mux(_, e, _) => var(e, &vars).1,
+            Node::Reg(s, _) => {
+                writeln!(dest, "static uint64_t n_{}[{}];", node.id(), words(*s))?;
+                *s
+            }
+            Node::Ram(addr_size, word_size, _, _, _, _) => {
+                if *addr_size > MAX_ADDR_SIZE {
+                    return Err(CError::AddressTooLarge(*addr_size));
+                }
+                writeln!(
+                    dest,
+                    "static uint64_t ram_{}[{}];",
+                    n_rams,
+                    words(*word_size) << addr_size
+                )?;
+                n_rams += 1;
+                *word_size
+            }
+            Node::Rom(rom_name, addr_size, word_size, _) => {
+                if *addr_size > 64 {
+                    return Err(CError::AddressTooLarge(*addr_size));
+                }
+                //only the part of the image which can be addressed is kept
+                let image = roms.image(rom_name).cloned().unwrap_or_default();
+                let max_len = 1usize.checked_shl(*addr_size as u32).unwrap_or(usize::MAX);
+                let mut content = Vec::new();
+                if *word_size > 0 {
+                    for word in image.chunks(*word_size).take(max_len) {
+                        content.extend(pack(word, *word_size));
+                    }
+                }
+                let len = content.len() / words(*word_size);
+                if content.is_empty() {
+                    content.push(0);
+                }
+                write!(dest, "static const uint64_t rom_{}[{}] = {{", n_roms, content.len())?;
+                write_words(&mut dest, &content)?;
+                writeln!(dest, "\n}};")?;
+                writeln!(dest, "#define ROM_{}_LEN {}", n_roms, len)?;
+                n_roms += 1;
+                *word_size
+            }
+            Node::TmpValueHolder(_) => panic!("Should not happen: temp value in codegen"),
+        };
+        writeln!(dest, "static uint64_t {}[{}];", name, words(size))?;
+        vars.insert(node.id(), (name, size));
+    }
+    let out_names = c_names(prog);
+    for ((_, node), out_name) in prog.outputs.iter().zip(&out_names) {
+        writeln!(
+            dest,
+            "const uint64_t *const tj_out_{} = {};",
+            out_name,
+            var(node, &vars).0
+        )?;
+    }
+    //the step function
+    writeln!(dest, "\nvoid tj_step(void) {{")?;
+    for node in &order {
+        if let Node::Reg(s, _) = &*node.borrow() {
+            writeln!(
+                dest,
+                "    memcpy(v_{0}, n_{0}, {1} * sizeof(uint64_t));",
+                node.id(),
+                words(*s)
+            )?;
+        }
+    }
+    let (mut ram_id, mut rom_id) = (0, 0);
+    //(ram id, address size, ram node) for the writes at the end of the cycle
+    let mut ram_writes = Vec::new();
+    for node in &order {
+        let (dst, size) = var(node, &vars);
+        let n = words(size);
+        match node.borrow().clone() {
+            Node::Input(_) | Node::Const(_) | Node::Reg(_, _) => (),
+            Node::Ram(addr_size, _, e, _, _, _) => {
+                let (addr, len) = var(&e, &vars);
+                writeln!(
+                    dest,
+                    "    memcpy({}, &ram_{}[(tj_address({}, {}) & 0x{:x}) * {}], {} * sizeof(uint64_t));",
+                    dst,
+                    ram_id,
+                    addr,
+                    len,
+                    (1u64 << addr_size) - 1,
+                    n,
+                    n
+                )?;
+                ram_writes.push((ram_id, addr_size, node.clone()));
+                ram_id += 1;
+            }
+            Node::Rom(_, _, _, e) => {
+                let (addr, len) = var(&e, &vars);
+                writeln!(dest, "    {{")?;
+                writeln!(dest, "        uint64_t addr = tj_address({}, {});", addr, len)?;
+                writeln!(dest, "        if (addr < ROM_{}_LEN)", rom_id)?;
+                writeln!(
+                    dest,
+                    "            memcpy({}, &rom_{}[addr * {}], {} * sizeof(uint64_t));",
+                    dst, rom_id, n, n
+                )?;
+                writeln!(dest, "        else")?;
+                writeln!(dest, "            memset({}, 0, {} * sizeof(uint64_t));", dst, n)?;
+                writeln!(dest, "    }}")?;
+                rom_id += 1;
+            }
+            Node::Not(e) => {
+                let src = var(&e, &vars).0;
+                for w in 0..n {
+                    writeln!(dest, "    {}[{}] = ~{}[{}];", dst, w, src, w)?;
+                }
+                writeln!(dest, "    {}[{}] &= 0x{:x};", dst, n - 1, last_mask(size))?;
+            }
+            Node::BiOp(BiOp::Concat, e1, e2) => {
+                let ((a, a_size), (b, b_size)) = (var(&e1, &vars), var(&e2, &vars));
+                if size > 64 {
+                    writeln!(dest, "    tj_copy_bits({}, 0, {}, 0, {});", dst, a, a_size)?;
+                    writeln!(
+                        dest,
+                        "    tj_copy_bits({}, {}, {}, 0, {});",
+                        dst, a_size, b, b_size
+                    )?;
+                } else if a_size == 0 {
+                    writeln!(dest, "    {}[0] = {}[0];", dst, b)?;
+                } else if b_size == 0 {
+                    writeln!(dest, "    {}[0] = {}[0];", dst, a)?;
+                } else {
+                    writeln!(
+                        dest,
+                        "    {}[0] = {}[0] | ({}[0] << {});",
+                        dst, a, b, a_size
+                    )?;
+                }
+            }
+            Node::BiOp(op, e1, e2) => {
+                let (a, b) = (var(&e1, &vars).0, var(&e2, &vars).0);
+                for w in 0..n {
+                    match op {
+                        BiOp::And => writeln!(dest, "    {0}[{1}] = {2}[{1}] & {3}[{1}];", dst, w, a, b)?,
+                        BiOp::Or => writeln!(dest, "    {0}[{1}] = {2}[{1}] | {3}[{1}];", dst, w, a, b)?,
+                        BiOp::Xor => writeln!(dest, "    {0}[{1}] = {2}[{1}] ^ {3}[{1}];", dst, w, a, b)?,
+                        BiOp::Nand => writeln!(dest, "    {0}[{1}] = ~({2}[{1}] & {3}[{1}]);", dst, w, a, b)?,
+                        BiOp::Concat => unreachable!(),
+                    }
+                }
+                if op == BiOp::Nand {
+                    writeln!(dest, "    {}[{}] &= 0x{:x};", dst, n - 1, last_mask(size))?;
+                }
+            }
+            Node::Slice(e, c1, _) => {
+                let src = var(&e, &vars).0;
+                if size > 64 {
+                    writeln!(dest, "    tj_copy_bits({}, 0, {}, {}, {});", dst, src, c1, size)?;
+                } else {
+                    writeln!(dest, "    {}[0] = tj_read_bits({}, {}, {});", dst, src, c1, size)?;
+                }
+            }
+            Node::Mux(e1, e2, e3) => {
+                let (c, a, b) = (var(&e1, &vars).0, var(&e2, &vars).0, var(&e3, &vars).0);
+                if n == 1 {
+                    writeln!(dest, "    {}[0] = ({}[0] & 1) ? {}[0] : {}[0];", dst, c, a, b)?;
+                } else {
+                    writeln!(
+                        dest,
+                        "    memcpy({}, ({}[0] & 1) ? {} : {}, {} * sizeof(uint64_t));",
+                        dst, c, a, b, n
+                    )?;
+                }
+            }
+            _ if size == 0 => (),
+            Node::TmpValueHolder(_) => panic!("Should not happen: temp value in codegen"),
+        }
+    }
+    //the rams are written at the end of the cycle
+    for (ram_id, addr_size, node) in ram_writes {
+        if let Node::Ram(_, word_size, _, we, wa, wd) = node.borrow().clone() {
+            let (wa, len) = var(&wa, &vars);
+            writeln!(dest, "    if ({}[0] & 1)", var(&we, &vars).0)?;
+            writeln!(
+                dest,
+                "        memcpy(&ram_{}[(tj_address({}, {}) & 0x{:x}) * {}], {}, {} * sizeof(uint64_t));",
+                ram_id,
+                wa,
+                len,
+                (1u64 << addr_size) - 1,
+                words(word_size),
+                var(&wd, &vars).0,
+                words(word_size)
+            )?;
+        }
+    }
+    //the registers are updated at the beginning of the next cycle
+    for node in &order {
+        if let Node::Reg(s, e) = &*node.borrow() {
+            writeln!(
+                dest,
+                "    memcpy(n_{}, {}, {} * sizeof(uint64_t));",
+                node.id(),
+                var(e, &vars).0,
+                words(*s)
+            )?;
+        }
+    }
+    writeln!(dest, "}}")?;
+    //the main function
+    write!(dest, "{}", MAIN)?;
+    //without inputs to read, the end of the inputs is never reached
+    if prog.inputs.iter().all(|(_, s)| *s == 0) {
+        writeln!(dest, "    if (steps < 0) {{")?;
+        writeln!(
+            dest,
+            "        fprintf(stderr, \"Error : the program has no inputs, the number of cycles must be given\\n\");"
+        )?;
+        writeln!(dest, "        return 1;")?;
+        writeln!(dest, "    }}")?;
+    }
+    writeln!(dest, "    for (long long t = 0; steps < 0 || t < steps; t++) {{")?;
+    for (i, (_, s)) in prog.inputs.iter().enumerate() {
+        writeln!(dest, "        if (!tj_read_input(tj_in_{}, {}))", i, s)?;
+        writeln!(dest, "            break;")?;
+    }
+    writeln!(dest, "        tj_step();")?;
+    for (i, ((name, node), out_name)) in prog.outputs.iter().zip(&out_names).enumerate() {
+        if i != 0 {
+            writeln!(dest, "        putchar(' ');")?;
+        }
+        writeln!(
+            dest,
+            "        tj_print(\"{}\", tj_out_{}, {});",
+            name,
+            out_name,
+            var(node, &vars).1
+        )?;
+    }
+    writeln!(dest, "        putchar('\\n');")?;
+    writeln!(dest, "    }}")?;
+    writeln!(dest, "    return 0;")?;
+    writeln!(dest, "}}")?;
+    writeln!(dest, "#endif")?;
+    Ok(())
+}
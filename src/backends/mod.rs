@@ -1 +1,3 @@
+pub(crate) mod c;
 pub(crate) mod netlist;
+pub(crate) mod verilog;
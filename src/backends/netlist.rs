@@ -7,11 +7,8 @@ use std::{
 //not much to say : it writes a netlist to anything implementing write
 //(it can be a file, or a string, or a port...)
 pub fn to_netlist(source: &FlatProgramGraph, mut dest: impl Write) -> Result<(), std::io::Error> {
-    writeln!(
-        dest,
-        "INPUT {}",
-        (0..source.inputs.len()).format_with(", ", |elt, f| f(&format_args!("i_{}", elt)))
-    )?;
+    let names = Names::new(source);
+    writeln!(dest, "INPUT {}", names.inputs.iter().format(", "))?;
     writeln!(
         dest,
         "OUTPUT {}",
@@ -21,156 +18,262 @@ pub fn to_netlist(source: &FlatProgramGraph, mut dest: impl Write) -> Result<(),
             .format_with(", ", |elt, f| f(&format_args!("o_{}", elt.0)))
     )?;
     write!(dest, "VAR ")?;
-    for (i, s) in source.inputs.iter().enumerate() {
-        write!(dest, "i_{} : {},", i, s)?;
+    for (name, (_, s)) in names.inputs.iter().zip(source.inputs.iter()) {
+        write!(dest, "{} : {},", name, s)?;
     }
+    //the memory is shared between outputs, so a node is only declared and computed once
+    let mut vars_mem = HashSet::new();
+    let mut sizes = HashMap::new();
+    let input_sizes = source.input_sizes();
     for (i, (name, n)) in source.outputs.iter().enumerate() {
-        let s = write_vars(n, &mut dest, &mut HashMap::new(), &source.inputs)?;
+        let s = write_vars(
+            n,
+            &mut dest,
+            &mut vars_mem,
+            &mut sizes,
+            &input_sizes,
+            &names,
+        )?;
         write!(dest, "o_{} : {}", name, s)?;
         if i != source.outputs.len() - 1 {
             write!(dest, ",")?;
         }
     }
     write!(dest, "\nIN\n")?;
+    let mut instr_mem = HashSet::new();
     for (s, n) in &source.outputs {
-        write_instr(n, &mut dest, &mut HashSet::new())?;
+        write_instr(n, &mut dest, &mut instr_mem, &names)?;
         write!(dest, "o_{} = ", s)?;
-        write_var_name(n, &mut dest)?;
+        write_var_name(n, &mut dest, &names)?;
         write!(dest, "\n")?;
     }
     Ok(())
 }
 
+const KEYWORDS: &[&str] = &[
+    "INPUT", "OUTPUT", "VAR", "IN", "NOT", "AND", "OR", "XOR", "NAND", "MUX", "REG", "RAM", "ROM",
+    "CONCAT", "SLICE", "SELECT",
+];
+
+//The names of the variables of the netlist.
+//Inputs and probes keep the name given by the user, when it is a valid and unused netlist name,
+//other nodes are called v_<id>, and outputs o_<name>.
+struct Names {
+    inputs: Vec<String>,
+    nodes: HashMap<u32, String>,
+    sources: HashMap<u32, String>, //where the variables named by the user are assigned
+}
+
+impl Names {
+    fn new(source: &FlatProgramGraph) -> Self {
+        let mut used: HashSet<String> = source
+            .outputs
+            .iter()
+            .map(|(name, _)| format!("o_{}", name))
+            .collect();
+        let mut available = |name: &str| {
+            let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && !KEYWORDS.contains(&name)
+                && !(name.starts_with("v_") && name[2..].chars().all(|c| c.is_ascii_digit()))
+                && !(name.starts_with("i_") && name[2..].chars().all(|c| c.is_ascii_digit()));
+            valid && used.insert(name.to_string())
+        };
+        let inputs = source
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                if available(name) {
+                    name.clone()
+                } else {
+                    format!("i_{}", i)
+                }
+            })
+            .collect();
+        let mut nodes = HashMap::new();
+        for (id, name) in source.node_names() {
+            if available(name) {
+                nodes.insert(id, name.clone());
+            }
+        }
+        Names {
+            inputs,
+            nodes,
+            sources: source.node_sources(),
+        }
+    }
+
+    fn get(&self, node: &RCell<Node>) -> String {
+        match &*node.borrow() {
+            Node::Input(i) => self.inputs[*i].clone(),
+            _ => match self.nodes.get(&node.id()) {
+                Some(name) => name.clone(),
+                None => format!("v_{}", node.id()),
+            },
+        }
+    }
+}
+
 fn write_vars(
     node: &RCell<Node>,
     dest: &mut impl Write,
-    mem: &mut HashMap<u32, usize>,
+    mem: &mut HashSet<u32>,
+    sizes: &mut HashMap<u32, usize>,
     input_sizes: &Vec<usize>,
+    names: &Names,
 ) -> Result<usize, std::io::Error> {
-    if let Some(i) = mem.get(&node.id()) {
-        return Ok(*i);
+    let size = node_size(node, sizes, input_sizes);
+    if mem.contains(&node.id()) {
+        return Ok(size);
     }
-    mem.insert(node.id(), 0);
-    let size = match node.borrow().clone() {
-        Node::Input(i) => input_sizes[i],
-        Node::Const(c) => c.len(),
-        Node::Not(e) => write_vars(&e, dest, mem, input_sizes)?,
-        Node::Slice(e, c1, c2) => {
-            write_vars(&e, dest, mem, input_sizes)?;
-            c2 - c1
+    mem.insert(node.id());
+    match node.borrow().clone() {
+        Node::Input(_) | Node::Const(_) | Node::TmpValueHolder(_) => (),
+        Node::Not(e) | Node::Slice(e, _, _) | Node::Reg(_, e) | Node::Rom(_, _, _, e) => {
+            write_vars(&e, dest, mem, sizes, input_sizes, names)?;
         }
         Node::BiOp(_, e1, e2) => {
-            write_vars(&e1, dest, mem, input_sizes)?;
-            write_vars(&e2, dest, mem, input_sizes)?
+            write_vars(&e1, dest, mem, sizes, input_sizes, names)?;
+            write_vars(&e2, dest, mem, sizes, input_sizes, names)?;
         }
         Node::Mux(e1, e2, e3) => {
-            write_vars(&e1, dest, mem, input_sizes)?;
-            write_vars(&e2, dest, mem, input_sizes)?;
-            write_vars(&e3, dest, mem, input_sizes)?
-        }
-        Node::Reg(s, e) => {
-            mem.insert(node.id(), s);
-            write_vars(&e, dest, mem, input_sizes)?;
-            s
+            write_vars(&e1, dest, mem, sizes, input_sizes, names)?;
+            write_vars(&e2, dest, mem, sizes, input_sizes, names)?;
+            write_vars(&e3, dest, mem, sizes, input_sizes, names)?;
         }
-        Node::Ram(e1, e2, e3, e4) => {
-            write_vars(&e1, dest, mem, input_sizes)?;
-            write_vars(&e2, dest, mem, input_sizes)?;
-            write_vars(&e3, dest, mem, input_sizes)?;
-            write_vars(&e4, dest, mem, input_sizes)?
+        Node::Ram(_, _, e1, e2, e3, e4) => {
+            write_vars(&e1, dest, mem, sizes, input_sizes, names)?;
+            write_vars(&e2, dest, mem, sizes, input_sizes, names)?;
+            write_vars(&e3, dest, mem, sizes, input_sizes, names)?;
+            write_vars(&e4, dest, mem, sizes, input_sizes, names)?;
         }
-        Node::Rom(s, e) => {
-            write_vars(&e, dest, mem, input_sizes)?;
-            s
-        }
-        Node::TmpValueHolder(_) => 0,
     };
-    write!(dest, "v_{}", node.id())?;
+    //inputs are already declared
+    if let Node::Input(_) = &*node.borrow() {
+        return Ok(size);
+    }
+    write!(dest, "{}", names.get(node))?;
     if size != 1 {
         write!(dest, " : {}", size)?;
     }
     write!(dest, ", ")?;
-    mem.insert(node.id(), size);
     Ok(size)
 }
 
+//computes the size of a node.
+//Cycles always go through a register, whose size is known, so this terminates.
+fn node_size(node: &RCell<Node>, mem: &mut HashMap<u32, usize>, input_sizes: &Vec<usize>) -> usize {
+    if let Some(i) = mem.get(&node.id()) {
+        return *i;
+    }
+    let size = match node.borrow().clone() {
+        Node::Input(i) => input_sizes[i],
+        Node::Const(c) => c.len(),
+        Node::Not(e) => node_size(&e, mem, input_sizes),
+        Node::Slice(_, c1, c2) => c2 - c1,
+        Node::BiOp(BiOp::Concat, e1, e2) => {
+            node_size(&e1, mem, input_sizes) + node_size(&e2, mem, input_sizes)
+        }
+        Node::BiOp(_, e, _) | Node::Mux(_, e, _) => node_size(&e, mem, input_sizes),
+        Node::Reg(s, _) | Node::Ram(_, s, _, _, _, _) | Node::Rom(_, _, s, _) => s,
+        Node::TmpValueHolder(_) => 0,
+    };
+    mem.insert(node.id(), size);
+    size
+}
+
+//the start of the instruction of a node, after where it comes from if it is a variable named by the user
+fn write_assigned(
+    node: &RCell<Node>,
+    dest: &mut impl Write,
+    names: &Names,
+) -> Result<(), std::io::Error> {
+    if let Some(source) = names.sources.get(&node.id()) {
+        writeln!(dest, "# {}", source)?;
+    }
+    write!(dest, "{} = ", names.get(node))
+}
+
 fn write_instr(
     node: &RCell<Node>,
     dest: &mut impl Write,
     mem: &mut HashSet<u32>,
+    names: &Names,
 ) -> Result<(), std::io::Error> {
     if mem.contains(&node.id()) {
         return Ok(());
     }
     mem.insert(node.id());
     match node.borrow().clone() {
-        Node::Input(_) => {}
+        Node::Input(_) => return Ok(()),
         Node::Const(v) => {
-            write!(dest, "v_{} = ", node.id())?;
+            write_assigned(node, dest, names)?;
             write!(
                 dest,
                 "{}",
                 v.iter()
-                    .format_with(" ", |elt, f| f(if *elt { &"1" } else { &"0" }))
+                    .format_with("", |elt, f| f(if *elt { &"1" } else { &"0" }))
             )?
         }
         Node::Not(e) => {
-            write_instr(&e, dest, mem)?;
-            write!(dest, "v_{} = ", node.id())?;
+            write_instr(&e, dest, mem, names)?;
+            write_assigned(node, dest, names)?;
             write!(dest, "NOT ")?;
-            write_var_name(&e, dest)?;
+            write_var_name(&e, dest, names)?;
         }
         Node::Slice(e, c1, c2) => {
-            write_instr(&e, dest, mem)?;
-            write!(dest, "v_{} = ", node.id())?;
+            write_instr(&e, dest, mem, names)?;
+            write_assigned(node, dest, names)?;
+            //the bounds of a slice are inclusive in the netlist format
             if c2 - c1 != 1 {
-                write!(dest, "SLICE {} {} ", c1, c2)?;
+                write!(dest, "SLICE {} {} ", c1, c2.saturating_sub(1))?;
             } else {
                 write!(dest, "SELECT {} ", c1)?;
             }
-            write_var_name(&e, dest)?;
+            write_var_name(&e, dest, names)?;
         }
         Node::BiOp(op, e1, e2) => {
-            write_instr(&e1, dest, mem)?;
-            write_instr(&e2, dest, mem)?;
-            write!(dest, "v_{} = ", node.id())?;
+            write_instr(&e1, dest, mem, names)?;
+            write_instr(&e2, dest, mem, names)?;
+            write_assigned(node, dest, names)?;
             write_op(op, dest)?;
-            write_var_name(&e1, dest)?;
-            write_var_name(&e2, dest)?;
+            write_var_name(&e1, dest, names)?;
+            write_var_name(&e2, dest, names)?;
         }
         Node::Mux(e1, e2, e3) => {
-            write_instr(&e1, dest, mem)?;
-            write_instr(&e2, dest, mem)?;
-            write_instr(&e3, dest, mem)?;
-            write!(dest, "v_{} = ", node.id())?;
+            write_instr(&e1, dest, mem, names)?;
+            write_instr(&e2, dest, mem, names)?;
+            write_instr(&e3, dest, mem, names)?;
+            write_assigned(node, dest, names)?;
             write!(dest, "MUX ")?;
-            write_var_name(&e1, dest)?;
-            write_var_name(&e2, dest)?;
-            write_var_name(&e3, dest)?;
+            write_var_name(&e1, dest, names)?;
+            write_var_name(&e2, dest, names)?;
+            write_var_name(&e3, dest, names)?;
         }
         Node::Reg(_, e) => {
-            write_instr(&e, dest, mem)?;
-            write!(dest, "v_{} = ", node.id())?;
+            write_instr(&e, dest, mem, names)?;
+            write_assigned(node, dest, names)?;
             write!(dest, "REG ")?;
-            write_var_name(&e, dest)?;
-        }
-        Node::Ram(e1, e2, e3, e4) => {
-            write_instr(&e1, dest, mem)?;
-            write_instr(&e2, dest, mem)?;
-            write_instr(&e3, dest, mem)?;
-            write_instr(&e4, dest, mem)?;
-            write!(dest, "v_{} = ", node.id())?;
-            write!(dest, "RAM ")?;
-            write_var_name(&e1, dest)?;
-            write_var_name(&e2, dest)?;
-            write_var_name(&e3, dest)?;
-            write_var_name(&e4, dest)?;
-        }
-        Node::Rom(_, e) => {
-            write_instr(&e, dest, mem)?;
-            write!(dest, "v_{} = ", node.id())?;
-            write!(dest, "ROM ")?;
-            write_var_name(&e, dest)?;
+            write_var_name(&e, dest, names)?;
+        }
+        Node::Ram(addr_size, word_size, e1, e2, e3, e4) => {
+            write_instr(&e1, dest, mem, names)?;
+            write_instr(&e2, dest, mem, names)?;
+            write_instr(&e3, dest, mem, names)?;
+            write_instr(&e4, dest, mem, names)?;
+            write_assigned(node, dest, names)?;
+            write!(dest, "RAM {} {} ", addr_size, word_size)?;
+            write_var_name(&e1, dest, names)?;
+            write_var_name(&e2, dest, names)?;
+            write_var_name(&e3, dest, names)?;
+            write_var_name(&e4, dest, names)?;
+        }
+        Node::Rom(_, addr_size, word_size, e) => {
+            write_instr(&e, dest, mem, names)?;
+            write_assigned(node, dest, names)?;
+            write!(dest, "ROM {} {} ", addr_size, word_size)?;
+            write_var_name(&e, dest, names)?;
         }
         Node::TmpValueHolder(_) => {
             panic!("Should not happen: temp value in codegen")
@@ -180,11 +283,12 @@ fn write_instr(
     Ok(())
 }
 
-fn write_var_name(node: &RCell<Node>, dest: &mut impl Write) -> Result<(), std::io::Error> {
-    match node.borrow().clone() {
-        Node::Input(i) => write!(dest, "i_{} ", i),
-        _ => write!(dest, "v_{} ", node.id()),
-    }
+fn write_var_name(
+    node: &RCell<Node>,
+    dest: &mut impl Write,
+    names: &Names,
+) -> Result<(), std::io::Error> {
+    write!(dest, "{} ", names.get(node))
 }
 
 fn write_op(op: BiOp, dest: &mut impl Write) -> Result<(), std::io::Error> {
@@ -0,0 +1,418 @@
+use std::{fmt::Display, io::Write};
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::ast::graph::*;
+use crate::util::{rom::RomImages, schedule::schedule};
+/*
+Generates a synthesizable Verilog module simulating the graph.
+The module has a clk input, and one port for each input and output, named after it
+(with "_" appended if this name is already used, the outputs keeping their names first). Every cycle of the program is one clock period : the inputs are read and
+the outputs computed combinationally, and registers and rams are updated on the rising edge.
+
+Buses are declared as [0:size-1], so that bit 0 is the leftmost one, as in the rest of tinyjazz :
+concatenations and addresses (first bit most significant) keep their meaning.
+Verilog has no zero-sized signals, so they are left out (and a zero-sized output has no port).
+Registers and rams start at 0, and roms are initialized with the rom images.
+*/
+#[derive(Debug)]
+pub enum VerilogError {
+    Io(std::io::Error),
+    CombinationalCycle,     //a cycle that does not go through a register
+    AddressTooLarge(usize), //rams and roms are arrays, so their addresses are limited
+}
+
+impl From<std::io::Error> for VerilogError {
+    fn from(err: std::io::Error) -> Self {
+        VerilogError::Io(err)
+    }
+}
+
+impl Display for VerilogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerilogError::Io(err) => write!(f, "Error : cannot write the Verilog code : {}", err),
+            VerilogError::CombinationalCycle => write!(
+                f,
+                "Error : the program contains a combinational cycle, it cannot be compiled to Verilog"
+            ),
+            VerilogError::AddressTooLarge(n) => write!(
+                f,
+                "Error : the Verilog backend only supports addresses up to {} bits, got {}",
+                MAX_ADDR_SIZE, n
+            ),
+        }
+    }
+}
+
+const MAX_ADDR_SIZE: usize = 24;
+
+//the reserved words of Verilog (IEEE 1364-2005), and the clock of the module
+const KEYWORDS: &[&str] = &[
+    "always",
+    "and",
+    "assign",
+    "automatic",
+    "begin",
+    "buf",
+    "bufif0",
+    "bufif1",
+    "case",
+    "casex",
+    "casez",
+    "cell",
+    "clk",
+    "cmos",
+    "config",
+    "deassign",
+    "default",
+    "defparam",
+    "design",
+    "disable",
+    "edge",
+    "else",
+    "end",
+    "endcase",
+    "endconfig",
+    "endfunction",
+    "endgenerate",
+    "endmodule",
+    "endprimitive",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "event",
+    "for",
+    "force",
+    "forever",
+    "fork",
+    "function",
+    "generate",
+    "genvar",
+    "highz0",
+    "highz1",
+    "if",
+    "ifnone",
+    "incdir",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "instance",
+    "integer",
+    "join",
+    "large",
+    "liblist",
+    "library",
+    "localparam",
+    "macromodule",
+    "medium",
+    "module",
+    "nand",
+    "negedge",
+    "nmos",
+    "nor",
+    "noshowcancelled",
+    "not",
+    "notif0",
+    "notif1",
+    "or",
+    "output",
+    "parameter",
+    "pmos",
+    "posedge",
+    "primitive",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pulsestyle_ondetect",
+    "pulsestyle_onevent",
+    "rcmos",
+    "real",
+    "realtime",
+    "reg",
+    "release",
+    "repeat",
+    "rnmos",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "scalared",
+    "showcancelled",
+    "signed",
+    "small",
+    "specify",
+    "specparam",
+    "strong0",
+    "strong1",
+    "supply0",
+    "supply1",
+    "table",
+    "task",
+    "time",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "unsigned",
+    "use",
+    "uwire",
+    "vectored",
+    "wait",
+    "wand",
+    "weak0",
+    "weak1",
+    "while",
+    "wire",
+    "wor",
+    "xnor",
+    "xor",
+];
+
+//the names of the nodes : v_<id>, ram_<n>, ram_<n>_i and rom_<n>
+fn is_internal(name: &str) -> bool {
+    match name.trim_end_matches("_i").split_once('_') {
+        Some((prefix, n)) => {
+            ["v", "ram", "rom"].contains(&prefix)
+                && !n.is_empty()
+                && n.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+//input and output names are used as port names, which must be different from each other
+//and from the names of the nodes
+fn port_name(name: &str, used: &mut AHashSet<String>) -> String {
+    let mut res: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if res.starts_with(|c: char| c.is_ascii_digit()) || KEYWORDS.contains(&res.as_str()) {
+        res.insert(0, 'o');
+        res.insert(1, '_');
+    }
+    while is_internal(&res) || !used.insert(res.clone()) {
+        res.push('_');
+    }
+    res
+}
+
+fn range(size: usize) -> String {
+    format!("[0:{}]", size - 1)
+}
+
+fn literal(bits: &[bool]) -> String {
+    let digits: String = bits.iter().map(|b| if *b { '1' } else { '0' }).collect();
+    format!("{}'b{}", bits.len(), digits)
+}
+
+pub fn to_verilog(
+    prog: &FlatProgramGraph,
+    roms: &RomImages,
+    mut dest: impl Write,
+) -> Result<(), VerilogError> {
+    let order = schedule(prog).map_err(|_| VerilogError::CombinationalCycle)?;
+    //name and size of every node
+    let mut vars: AHashMap<u32, (String, usize)> = AHashMap::new();
+    let var = |node: &RCell<Node>, vars: &AHashMap<u32, (String, usize)>| {
+        vars.get(&node.id()).unwrap().clone()
+    };
+    //the header
+    let mut used: AHashSet<String> = ["clk".to_string()].iter().cloned().collect();
+    let out_ports: Vec<String> = prog
+        .outputs
+        .iter()
+        .map(|(name, _)| port_name(name, &mut used))
+        .collect();
+    let mut ports = vec!["clk".to_string()];
+    let mut inputs = Vec::new();
+    for (name, s) in &prog.inputs {
+        let port = port_name(name, &mut used);
+        if *s > 0 {
+            ports.push(port.clone());
+        }
+        inputs.push((port, *s));
+    }
+    //declarations, with the size of every node
+    let (mut n_rams, mut n_roms) = (0, 0);
+    let mut body = Vec::new();
+    for node in &order {
+        let name = format!("v_{}", node.id());
+        let size = match &*node.borrow() {
+            Node::Input(i) => {
+                vars.insert(node.id(), inputs[*i].clone());
+                continue;
+            }
+            Node::Const(c) => c.len(),
+            Node::Not(e) => var(e, &vars).1,
+            Node::Slice(_, c1, c2) => c2 - c1,
+            Node::BiOp(BiOp::Concat, e1, e2) => var(e1, &vars).1 + var(e2, &vars).1,
+            Node::BiOp(_, e, _) | Node::Mux(_, e, _) => var(e, &vars).1,
+            Node::Reg(s, _) => *s,
+            Node::Ram(addr_size, _, _, _, _, _) | Node::Rom(_, addr_size, _, _) => {
+                if *addr_size > MAX_ADDR_SIZE {
+                    return Err(VerilogError::AddressTooLarge(*addr_size));
+                }
+                match &*node.borrow() {
+                    Node::Ram(_, word_size, _, _, _, _) => *word_size,
+                    Node::Rom(_, _, word_size, _) => *word_size,
+                    _ => unreachable!(),
+                }
+            }
+            Node::TmpValueHolder(_) => panic!("Should not happen: temp value in codegen"),
+        };
+        vars.insert(node.id(), (name, size));
+        if size > 0 {
+            body.push(node.clone());
+        }
+    }
+    let mut outputs = Vec::new();
+    for ((_, node), port) in prog.outputs.iter().zip(out_ports) {
+        let (var_name, size) = var(node, &vars);
+        if size > 0 {
+            ports.push(port.clone());
+            outputs.push((port, var_name, size));
+        }
+    }
+    writeln!(dest, "// Generated by tinyjazz")?;
+    writeln!(dest, "module tinyjazz({});", ports.join(", "))?;
+    writeln!(dest, "    input clk;")?;
+    for (port, s) in &inputs {
+        if *s > 0 {
+            writeln!(dest, "    input {} {};", range(*s), port)?;
+        }
+    }
+    for (port, _, size) in &outputs {
+        writeln!(dest, "    output {} {};", range(*size), port)?;
+    }
+    writeln!(dest)?;
+    for node in &body {
+        let (name, size) = var(node, &vars);
+        match &*node.borrow() {
+            Node::Reg(_, _) => writeln!(dest, "    reg {} {} = 0;", range(size), name)?,
+            _ => writeln!(dest, "    wire {} {};", range(size), name)?,
+        }
+    }
+    //the nodes, with where the variables named by the user are assigned
+    let sources = prog.node_sources();
+    for node in &body {
+        let (dst, size) = var(node, &vars);
+        if let Some(source) = sources.get(&node.id()) {
+            writeln!(dest, "    // {}", source)?;
+        }
+        match node.borrow().clone() {
+            Node::Input(_) | Node::TmpValueHolder(_) => (),
+            Node::Const(c) => writeln!(dest, "    assign {} = {};", dst, literal(&c))?,
+            Node::Not(e) => writeln!(dest, "    assign {} = ~{};", dst, var(&e, &vars).0)?,
+            Node::Slice(e, c1, c2) => {
+                writeln!(dest, "    assign {} = {}[{}:{}];", dst, var(&e, &vars).0, c1, c2 - 1)?
+            }
+            Node::BiOp(BiOp::Concat, e1, e2) => {
+                //zero-sized operands do not exist in the Verilog code
+                let operands: Vec<String> = [e1, e2]
+                    .iter()
+                    .map(|e| var(e, &vars))
+                    .filter(|(_, s)| *s > 0)
+                    .map(|(n, _)| n)
+                    .collect();
+                writeln!(dest, "    assign {} = {{{}}};", dst, operands.join(", "))?
+            }
+            Node::BiOp(op, e1, e2) => {
+                let (a, b) = (var(&e1, &vars).0, var(&e2, &vars).0);
+                match op {
+                    BiOp::And => writeln!(dest, "    assign {} = {} & {};", dst, a, b)?,
+                    BiOp::Or => writeln!(dest, "    assign {} = {} | {};", dst, a, b)?,
+                    BiOp::Xor => writeln!(dest, "    assign {} = {} ^ {};", dst, a, b)?,
+                    BiOp::Nand => writeln!(dest, "    assign {} = ~({} & {});", dst, a, b)?,
+                    BiOp::Concat => unreachable!(),
+                }
+            }
+            Node::Mux(e1, e2, e3) => writeln!(
+                dest,
+                "    assign {} = {}[0] ? {} : {};",
+                dst,
+                var(&e1, &vars).0,
+                var(&e2, &vars).0,
+                var(&e3, &vars).0
+            )?,
+            Node::Reg(_, e) => {
+                writeln!(dest, "    always @(posedge clk)")?;
+                writeln!(dest, "        {} <= {};", dst, var(&e, &vars).0)?;
+            }
+            Node::Ram(addr_size, _, ra, we, wa, wd) => {
+                let mem = format!("ram_{}", n_rams);
+                n_rams += 1;
+                //an address of size 0 always designates the only word
+                let address = |e: &RCell<Node>| {
+                    if addr_size == 0 {
+                        "0".to_string()
+                    } else {
+                        var(e, &vars).0
+                    }
+                };
+                writeln!(dest, "    reg {} {} [0:{}];", range(size), mem, (1usize << addr_size) - 1)?;
+                writeln!(dest, "    integer {}_i;", mem)?;
+                writeln!(dest, "    initial")?;
+                writeln!(
+                    dest,
+                    "        for ({0}_i = 0; {0}_i < {1}; {0}_i = {0}_i + 1) {0}[{0}_i] = 0;",
+                    mem,
+                    1usize << addr_size
+                )?;
+                writeln!(dest, "    assign {} = {}[{}];", dst, mem, address(&ra))?;
+                //the ram is written at the end of the cycle
+                writeln!(dest, "    always @(posedge clk)")?;
+                writeln!(dest, "        if ({}[0])", var(&we, &vars).0)?;
+                writeln!(dest, "            {}[{}] <= {};", mem, address(&wa), var(&wd, &vars).0)?;
+            }
+            Node::Rom(rom_name, addr_size, _, ra) => {
+                //only the part of the image which can be addressed is kept
+                let image = roms.image(&rom_name).cloned().unwrap_or_default();
+                let content: Vec<&[bool]> = image.chunks(size).take(1 << addr_size).collect();
+                if content.is_empty() {
+                    writeln!(dest, "    assign {} = 0;", dst)?;
+                    continue;
+                }
+                let mem = format!("rom_{}", n_roms);
+                n_roms += 1;
+                writeln!(dest, "    reg {} {} [0:{}];", range(size), mem, content.len() - 1)?;
+                writeln!(dest, "    initial begin")?;
+                for (i, word) in content.iter().enumerate() {
+                    //the last word is completed with 0s
+                    let mut word = word.to_vec();
+                    word.resize(size, false);
+                    writeln!(dest, "        {}[{}] = {};", mem, i, literal(&word))?;
+                }
+                writeln!(dest, "    end")?;
+                if addr_size == 0 {
+                    writeln!(dest, "    assign {} = {}[0];", dst, mem)?;
+                } else {
+                    //reading outside of the image gives 0s
+                    writeln!(
+                        dest,
+                        "    assign {0} = {1} < {2} ? {3}[{1}] : {4}'b0;",
+                        dst,
+                        var(&ra, &vars).0,
+                        content.len(),
+                        mem,
+                        size
+                    )?;
+                }
+            }
+        }
+    }
+    //the outputs
+    for (port, name, _) in &outputs {
+        writeln!(dest, "    assign {} = {};", port, name)?;
+    }
+    writeln!(dest, "endmodule")?;
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use crate::ast::graph_automaton::*;
+use crate::ast::typed_ast::Builtin;
+use crate::ast::ArithOp;
+use itertools::Itertools;
+use std::rc::Rc;
+/*
+This file lowers the arithmetic operations on buses to gates, while the graph of a state is built.
+The buses are read as unsigned numbers, with the most significant bit first (a[0]).
+
+a + b is a ripple carry adder, and a - b is a + not(b) + 1.
+a < b when a - b borrows, which means a + not(b) + 1 has no carry out.
+a << b and a >> b are barrel shifters : a stage of muxes for each bit of b.
+
+The built-ins are lowered here as well, with slices and concatenations of the bits of their argument.
+*/
+
+pub fn lower(
+    op: &ArithOp,
+    a: Rc<ExprNode>,
+    b: Rc<ExprNode>,
+    size_a: usize,
+    size_b: usize,
+) -> ExprOperation {
+    let res = match op {
+        ArithOp::Add => add(&a, &b, size_a, constant(false, 1)).0,
+        ArithOp::Sub => add(&a, &node(ExprOperation::Not(b)), size_a, constant(true, 1)).0,
+        ArithOp::Lt => {
+            let (_, carry) = add(&a, &node(ExprOperation::Not(b)), size_a, constant(true, 1));
+            node(ExprOperation::Not(carry))
+        }
+        ArithOp::Eq => node(ExprOperation::Not(any(&biop(BiOp::Xor, a, b), size_a))),
+        ArithOp::Neq => any(&biop(BiOp::Xor, a, b), size_a),
+        ArithOp::Shl => shift(true, a, size_a, &b, size_b),
+        ArithOp::Shr => shift(false, a, size_a, &b, size_b),
+    };
+    res.op.clone()
+}
+
+pub fn lower_builtin(b: &Builtin, a: Rc<ExprNode>, size: usize) -> ExprOperation {
+    let res = match b {
+        Builtin::Repeat(n) => concat(vec![a; *n]),
+        Builtin::Reduce(op) => (0..size)
+            .map(|i| slice(&a, i, i + 1))
+            .fold1(|acc, b| biop(op.clone(), acc, b))
+            .unwrap_or_else(|| constant(matches!(op, BiOp::And), 1)),
+        Builtin::Reverse => concat((0..size).rev().map(|i| slice(&a, i, i + 1)).collect()),
+    };
+    res.op.clone()
+}
+
+//adds two buses of the same size and a carry, and returns the sum and the carry out
+fn add(
+    a: &Rc<ExprNode>,
+    b: &Rc<ExprNode>,
+    size: usize,
+    carry: Rc<ExprNode>,
+) -> (Rc<ExprNode>, Rc<ExprNode>) {
+    let mut carry = carry;
+    let mut sum = Vec::with_capacity(size);
+    for i in (0..size).rev() {
+        let (x, y) = (slice(a, i, i + 1), slice(b, i, i + 1));
+        let x_xor_y = biop(BiOp::Xor, x.clone(), y.clone());
+        sum.push(biop(BiOp::Xor, x_xor_y.clone(), carry.clone()));
+        carry = biop(
+            BiOp::Or,
+            biop(BiOp::And, x, y),
+            biop(BiOp::And, carry, x_xor_y),
+        );
+    }
+    sum.reverse();
+    (concat(sum), carry)
+}
+
+//true if any bit of the bus is set
+fn any(a: &Rc<ExprNode>, size: usize) -> Rc<ExprNode> {
+    (0..size)
+        .map(|i| slice(a, i, i + 1))
+        .fold1(|acc, b| biop(BiOp::Or, acc, b))
+        .unwrap_or_else(|| constant(false, 1))
+}
+
+//the bits of the amount are read from the least significant one, which shifts by one
+fn shift(
+    left: bool,
+    a: Rc<ExprNode>,
+    size: usize,
+    amount: &Rc<ExprNode>,
+    amount_size: usize,
+) -> Rc<ExprNode> {
+    let mut res = a;
+    for i in 0..amount_size {
+        let shifted = match 1usize.checked_shl(i as u32).filter(|by| *by < size) {
+            Some(by) if left => concat(vec![slice(&res, by, size), constant(false, by)]),
+            Some(by) => concat(vec![constant(false, by), slice(&res, 0, size - by)]),
+            //everything is shifted out
+            None => constant(false, size),
+        };
+        let j = amount_size - 1 - i;
+        res = node(ExprOperation::Mux(slice(amount, j, j + 1), shifted, res));
+    }
+    res
+}
+
+fn node(op: ExprOperation) -> Rc<ExprNode> {
+    Rc::new(ExprNode {
+        op,
+        ..Default::default()
+    })
+}
+
+fn constant(b: bool, size: usize) -> Rc<ExprNode> {
+    node(ExprOperation::Const(vec![b; size]))
+}
+
+fn slice(a: &Rc<ExprNode>, i1: usize, i2: usize) -> Rc<ExprNode> {
+    node(ExprOperation::Slice(a.clone(), i1, i2))
+}
+
+fn biop(op: BiOp, a: Rc<ExprNode>, b: Rc<ExprNode>) -> Rc<ExprNode> {
+    node(ExprOperation::BiOp(op, a, b))
+}
+
+fn concat(bits: Vec<Rc<ExprNode>>) -> Rc<ExprNode> {
+    bits.into_iter()
+        .fold1(|acc, b| biop(BiOp::Concat, acc, b))
+        .unwrap_or_else(|| constant(false, 0))
+}
@@ -5,7 +5,6 @@ use ahash::{AHashMap, AHashSet};
 use crate::ast::{graph::*, graph_automaton::*};
 /*
 Transforms the automata in a single dataflow graph, using the following process :
--compute all reset condition so regs can be reset appropriatly.
 -Replace every node state var "n" with "last n" (as it is the expected behaviour) (this can be done lazily)
 -make a table of which node produces which output usign which ExprNode.
 for each node:
@@ -14,26 +13,29 @@ for each node:
 -Replace them all, in scheduling order.
 -Last has dissapeared as it is redundant.
 -Everything is wrapped in RCell to stay mutable, for optimisations later on
-Once this is done, computes the state variables. They should all be simple shared vars,
-and so it should be simple using the previous map.
+Once this is done, computes the state variables (see compute_transitions), and whether each state
+was entered through a reset, so that "last" gives the init values.
 Then replace all temp values with actual links in the graph, and everything is good
 */
 
 pub fn flatten_automata(prog: &ProgramGraph) -> FlatProgramGraph {
     let n_input = prog.inputs.len();
-    let n_node = n_input + prog.states.len();
     let mut shared_map = AHashMap::new();
     let mut nodes_mem = vec![AHashMap::new(); prog.states.len()];
-    let reset_conditions = compute_reset_conditions(
-        &prog,
-        &mut shared_map,
-        &prog.shared,
-        &mut nodes_mem,
-        n_input,
-    );
+    //the reset conditions are only known once all the transitions are computed, so they are filled in later
+    let (can_reset_start, can_reset) = compute_resettable(prog, n_input);
+    let placeholders = |v: Vec<bool>| {
+        v.into_iter()
+            .map(|b| if b { Some(placeholder()) } else { None })
+            .collect::<Vec<_>>()
+    };
+    let reset_start = placeholders(can_reset_start);
+    let reset_conditions = placeholders(can_reset);
     let init_node = &RCell::new(Node::Reg(1, RCell::new(Node::Const(vec![true]))));
     //Link all the inputs and outputs of shared vars.
-    for state_id in 0..prog.states.len() {
+    //The last state computed sets the value of a shared var, so the states entered through
+    //instantaneous transitions are computed after the states they come from.
+    for state_id in instant_order(prog) {
         compute_state(
             &prog.states[state_id],
             &mut shared_map,
@@ -49,15 +51,74 @@ pub fn flatten_automata(prog: &ProgramGraph) -> FlatProgramGraph {
     compute_transitions(
         &prog,
         &mut shared_map,
-        &prog.shared,
         &mut nodes_mem,
+        &reset_start,
         &reset_conditions,
-        n_input,
+        init_node,
     );
-    //when init value are needed, adds them.
-    //this is done using "reg 1" to know whether this is the first cycle or not.
-    add_init_values(&mut shared_map, &prog.shared, n_node, n_input, init_node);
-    remove_tmp_value(&mut shared_map, &prog.shared);
+    let copies = remove_tmp_value(&mut shared_map, &prog.shared);
+    //the probes use the nodes which are read by the rest of the graph, so they can name them
+    let probes = prog
+        .probes
+        .iter()
+        .map(|(s, i)| {
+            (
+                s.to_string(),
+                copies
+                    .get(i)
+                    .and_then(|c| c.first())
+                    .or(shared_map.get(i))
+                    .cloned()
+                    .unwrap_or(RCell::new(Node::Const(prog.shared[*i].clone()))),
+            )
+        })
+        .collect();
+    //the variables are located where they are assigned in each state, and the other shared variables
+    //(the states and the inputs) where they are declared.
+    //a shared variable is both the node which computes it, and the copies read by the rest of the graph
+    let assignments = prog
+        .states
+        .iter()
+        .zip(nodes_mem.iter())
+        .flat_map(|(state, mem)| {
+            state.locations.iter().filter_map(move |(s, node, loc)| {
+                mem.get(node).map(|n| (s.clone(), *loc, n.clone()))
+            })
+        });
+    let assigned = prog
+        .states
+        .iter()
+        .flat_map(|state| state.locations.iter().map(|(s, _, _)| s))
+        .collect::<AHashSet<_>>();
+    let declarations = prog
+        .locations
+        .iter()
+        .filter(|(s, _, _)| !assigned.contains(s));
+    let locations = assignments
+        .chain(declarations.flat_map(|(s, i, loc)| {
+            copies
+                .get(i)
+                .into_iter()
+                .flatten()
+                .chain(shared_map.get(i))
+                .map(move |node| (s.to_string(), *loc, node.clone()))
+        }))
+        .collect();
+    //the states are shared variables, which are true when they are active
+    let active = |state_id: usize| {
+        let i = n_input + state_id;
+        copies
+            .get(&i)
+            .and_then(|c| c.first())
+            .or(shared_map.get(&i))
+            .cloned()
+            .unwrap_or(RCell::new(Node::Const(prog.shared[i].clone())))
+    };
+    let conflicts = prog
+        .conflicts
+        .iter()
+        .map(|(s, s1, s2)| (s.to_string(), active(*s1), active(*s2)))
+        .collect();
     FlatProgramGraph {
         outputs: prog
             .outputs
@@ -72,6 +133,10 @@ pub fn flatten_automata(prog: &ProgramGraph) -> FlatProgramGraph {
             })
             .collect(),
         inputs: prog.inputs.clone(),
+        probes,
+        locations,
+        conflicts,
+        files: None,
     }
 }
 
@@ -80,7 +145,7 @@ fn compute_state(
     shared_map: &mut AHashMap<usize, RCell<Node>>,
     shared_sizes: &Vec<Vec<bool>>,
     state_mem: &mut AHashMap<Rc<ExprNode>, RCell<Node>>,
-    reset_conditions: &Vec<Option<Node>>,
+    reset_conditions: &Vec<Option<RCell<Node>>>,
     n_input: usize,
     state_id: usize,
     init_node: &RCell<Node>,
@@ -128,60 +193,146 @@ fn compute_state(
         shared_map.insert(*id, RCell::new(new_node));
     }
 }
-fn compute_reset_conditions(
-    prog: &ProgramGraph,
-    shared_map: &mut AHashMap<usize, RCell<Node>>,
-    shared_sizes: &Vec<Vec<bool>>,
-    state_mem: &mut Vec<AHashMap<Rc<ExprNode>, RCell<Node>>>,
-    n_input: usize,
-) -> Vec<Option<Node>> {
-    let mut reset_conditions = vec![None; prog.states.len()];
-    for (pred_id, node) in prog.states.iter().enumerate() {
-        for (next_id, expr_node, b) in &node.transition_outputs {
-            if next_id.is_some() && *b {
-                let next_id = next_id.unwrap();
-                let new_node = compute_node(
-                    expr_node.clone(),
-                    shared_map,
-                    shared_sizes,
-                    &mut state_mem[pred_id],
-                    &vec![None; prog.states.len()],
-                    n_input,
-                    pred_id,
-                );
-                let condition = Node::BiOp(
-                    BiOp::And,
-                    RCell::new(Node::TmpValueHolder(pred_id + prog.inputs.len())),
-                    new_node,
-                );
-                let prev_condition = std::mem::take(&mut reset_conditions[next_id]);
-                reset_conditions[next_id] = if prev_condition.is_none() {
-                    Some(condition)
-                } else {
-                    Some(Node::BiOp(
-                        BiOp::Or,
-                        RCell::new(condition),
-                        RCell::new(prev_condition.unwrap()),
-                    ))
-                };
+//The states in topological order of the instantaneous transitions (the loops are rejected earlier),
+//and in the order of their ids otherwise.
+fn instant_order(prog: &ProgramGraph) -> Vec<usize> {
+    fn visit(
+        state_id: usize,
+        prog: &ProgramGraph,
+        visited: &mut Vec<bool>,
+        order: &mut Vec<usize>,
+    ) {
+        if visited[state_id] {
+            return;
+        }
+        visited[state_id] = true;
+        let state = &prog.states[state_id];
+        for (next_id, _, _, instant) in &state.transition_outputs {
+            if let (Some(next_id), true) = (next_id, state.weak && *instant) {
+                visit(*next_id, prog, visited, order);
+            }
+        }
+        order.push(state_id);
+    }
+    let mut visited = vec![false; prog.states.len()];
+    let mut order = Vec::new();
+    for state_id in (0..prog.states.len()).rev() {
+        visit(state_id, prog, &mut visited, &mut order);
+    }
+    order.reverse();
+    order
+}
+//Whether each state can be entered through a reset : at the beginning of the cycle (after a
+//weak reset transition, or when it is an init state and its parent is reset), and at all.
+fn compute_resettable(prog: &ProgramGraph, n_input: usize) -> (Vec<bool>, Vec<bool>) {
+    let mut can_reset_start = vec![false; prog.states.len()];
+    let mut can_reset = vec![false; prog.states.len()];
+    for state in &prog.states {
+        for (next_id, _, reset, instant) in &state.transition_outputs {
+            if let (Some(next_id), true) = (next_id, reset) {
+                can_reset_start[*next_id] |= state.weak && !instant;
+                can_reset[*next_id] = true;
             }
         }
     }
-    reset_conditions
+    //the parents can be children themselves, so this is repeated until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (state_id, state) in prog.states.iter().enumerate() {
+            if let Some(parent) = state.parent {
+                if can_reset[parent] && prog.shared[n_input + state_id][0] && !can_reset[state_id] {
+                    can_reset_start[state_id] = true;
+                    can_reset[state_id] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+    (can_reset_start, can_reset)
+}
+
+//A node whose value is given once it is computed
+fn placeholder() -> RCell<Node> {
+    RCell::new(Node::Const(Vec::new()))
+}
+fn and(n1: RCell<Node>, n2: RCell<Node>) -> RCell<Node> {
+    RCell::new(Node::BiOp(BiOp::And, n1, n2))
+}
+fn or_all(nodes: Vec<RCell<Node>>) -> RCell<Node> {
+    nodes
+        .into_iter()
+        .fold(None, |prev, n| match prev {
+            None => Some(n),
+            Some(prev) => Some(RCell::new(Node::BiOp(BiOp::Or, n, prev))),
+        })
+        .unwrap_or(RCell::new(Node::Const(vec![false])))
 }
 
+/*
+Computes the state variables. A state is selected at the beginning of a cycle, by the transitions of
+the previous cycle (or by the init values). During the cycle, it is active if :
+-its parent (the state calling the automaton it comes from) is active. Otherwise, it stays selected, and
+the automaton resumes when the parent is active again. If the parent is entered through a reset, the init
+states of the automaton are selected instead.
+-it is not a strong state, whose transitions are taken at the beginning of the cycle : the state it goes to is
+active instead, and stays selected.
+-or it is entered through an instantaneous transition of an active state.
+The weak transitions of the active states are taken at the end of the cycle, and select the states of the next one.
+The instantaneous ones are taken at the same time, but the state they go to is active during this cycle :
+its body is computed as well, and its transitions are taken instead.
+The state variables read by the rest of the graph tell whether each state is active.
+*/
 fn compute_transitions(
     prog: &ProgramGraph,
     shared_map: &mut AHashMap<usize, RCell<Node>>,
-    shared_sizes: &Vec<Vec<bool>>,
     state_mem: &mut Vec<AHashMap<Rc<ExprNode>, RCell<Node>>>,
-    reset_conditions: &Vec<Option<Node>>,
-    n_input: usize,
+    reset_start: &Vec<Option<RCell<Node>>>,
+    reset_conditions: &Vec<Option<RCell<Node>>>,
+    init_node: &RCell<Node>,
 ) {
-    for (pred_id, node) in prog.states.iter().enumerate() {
-        for (next_id, expr_node, _) in &node.transition_outputs {
-            if let Some(next_id) = next_id {
-                let new_node = compute_node(
+    let shared_sizes = &prog.shared;
+    let n_input = prog.inputs.len();
+    let n_states = prog.states.len();
+    let active = |state_id: usize| RCell::new(Node::TmpValueHolder(n_input + state_id));
+    let is_init = |state_id: usize| shared_sizes[n_input + state_id][0];
+    let parent_reset = |state_id: usize| {
+        prog.states[state_id]
+            .parent
+            .and_then(|p| reset_conditions[p].clone())
+    };
+    //the conditions of strong transitions are computed before the state, so "last" only
+    //takes into account the resets which happened before
+    let mut strong_mem = vec![AHashMap::new(); n_states];
+    let selected: Vec<RCell<Node>> = (0..n_states).map(|_| placeholder()).collect();
+    let start: Vec<RCell<Node>> = (0..n_states)
+        .map(|state_id| match parent_reset(state_id) {
+            Some(reset) if is_init(state_id) => or_all(vec![selected[state_id].clone(), reset]),
+            Some(reset) => and(selected[state_id].clone(), RCell::new(Node::Not(reset))),
+            None => selected[state_id].clone(),
+        })
+        .collect();
+    let with_parent = |state_id: usize, node: RCell<Node>| match prog.states[state_id].parent {
+        Some(parent) => and(active(parent), node),
+        None => node,
+    };
+    let mut active_terms: Vec<Vec<RCell<Node>>> = (0..n_states)
+        .map(|state_id| {
+            if prog.states[state_id].weak {
+                vec![with_parent(state_id, start[state_id].clone())]
+            } else {
+                Vec::new()
+            }
+        })
+        .collect();
+    let mut selected_terms = vec![Vec::new(); n_states];
+    let mut weak_reset_terms = vec![Vec::new(); n_states];
+    //the resets of the states entered during the cycle (through strong or instantaneous transitions)
+    let mut instant_reset_terms = vec![Vec::new(); n_states];
+    for (pred_id, state) in prog.states.iter().enumerate() {
+        for (next_id, expr_node, reset, instant) in &state.transition_outputs {
+            let condition = if state.weak {
+                compute_node(
                     expr_node.clone(),
                     shared_map,
                     shared_sizes,
@@ -189,22 +340,82 @@ fn compute_transitions(
                     reset_conditions,
                     n_input,
                     pred_id,
-                );
-                let condition = RCell::new(Node::BiOp(
-                    BiOp::And,
-                    RCell::new(Node::TmpValueHolder(pred_id + n_input)),
-                    new_node,
-                ));
-                let prev_condition = shared_map.remove(&(*next_id + n_input));
-                shared_map.insert(
-                    *next_id + n_input,
-                    if prev_condition.is_none() {
-                        condition
-                    } else {
-                        RCell::new(Node::BiOp(BiOp::Or, condition, prev_condition.unwrap()))
-                    },
-                );
+                )
+            } else {
+                compute_node(
+                    expr_node.clone(),
+                    shared_map,
+                    shared_sizes,
+                    &mut strong_mem[pred_id],
+                    reset_start,
+                    n_input,
+                    pred_id,
+                )
+            };
+            //terminate : no state is selected
+            let next_id = if let Some(next_id) = next_id {
+                *next_id
+            } else {
+                continue;
+            };
+            let taken = if state.weak {
+                and(active(pred_id), condition)
+            } else {
+                with_parent(pred_id, and(start[pred_id].clone(), condition))
+            };
+            if state.weak && !instant {
+                selected_terms[next_id].push(taken.clone());
+                if *reset {
+                    weak_reset_terms[next_id].push(taken);
+                }
+            } else {
+                active_terms[next_id].push(taken.clone());
+                if *reset {
+                    instant_reset_terms[next_id].push(taken);
+                }
+            }
+        }
+    }
+    for (state_id, state) in prog.states.iter().enumerate() {
+        //strong states stay selected until one of their transitions is taken
+        if !state.weak {
+            selected_terms[state_id].push(active(state_id));
+        }
+        if let Some(parent) = state.parent {
+            selected_terms[state_id].push(and(
+                RCell::new(Node::Not(active(parent))),
+                start[state_id].clone(),
+            ));
+        }
+        let next = RCell::new(Node::Reg(
+            1,
+            or_all(std::mem::take(&mut selected_terms[state_id])),
+        ));
+        *selected[state_id].borrow_mut() = if is_init(state_id) {
+            Node::Mux(init_node.clone(), next, RCell::new(Node::Const(vec![true])))
+        } else {
+            next.borrow().clone()
+        };
+        shared_map.insert(
+            n_input + state_id,
+            or_all(std::mem::take(&mut active_terms[state_id])),
+        );
+        //a weak reset transition resets the state at the next cycle
+        if let Some(reset) = &reset_start[state_id] {
+            let mut terms = Vec::new();
+            if !weak_reset_terms[state_id].is_empty() {
+                let taken = or_all(std::mem::take(&mut weak_reset_terms[state_id]));
+                terms.push(RCell::new(Node::Reg(1, taken)));
+            }
+            if let Some(parent_reset) = parent_reset(state_id).filter(|_| is_init(state_id)) {
+                terms.push(parent_reset);
             }
+            *reset.borrow_mut() = or_all(terms).borrow().clone();
+        }
+        if let Some(reset) = &reset_conditions[state_id] {
+            let mut terms = std::mem::take(&mut instant_reset_terms[state_id]);
+            terms.extend(reset_start[state_id].clone());
+            *reset.borrow_mut() = or_all(terms).borrow().clone();
         }
     }
 }
@@ -214,7 +425,7 @@ fn compute_node(
     shared_map: &mut AHashMap<usize, RCell<Node>>,
     shared_size: &Vec<Vec<bool>>,
     state_mem: &mut AHashMap<Rc<ExprNode>, RCell<Node>>,
-    reset_conditions: &Vec<Option<Node>>,
+    reset_conditions: &Vec<Option<RCell<Node>>>,
     n_input: usize,
     state_id: usize,
 ) -> RCell<Node> {
@@ -330,7 +541,9 @@ fn compute_node(
             *tmp_value.borrow_mut() = node.borrow().clone();
             node
         }
-        ExprOperation::Ram(e1, e2, e3, e4) => RCell::new(Node::Ram(
+        ExprOperation::Ram(addr_size, word_size, e1, e2, e3, e4) => RCell::new(Node::Ram(
+            addr_size,
+            word_size,
             compute_node(
                 e1,
                 shared_map,
@@ -368,8 +581,10 @@ fn compute_node(
                 state_id,
             ),
         )),
-        ExprOperation::Rom(s, e) => RCell::new(Node::Rom(
-            s,
+        ExprOperation::Rom(name, addr_size, word_size, e) => RCell::new(Node::Rom(
+            name,
+            addr_size,
+            word_size,
             compute_node(
                 e,
                 shared_map,
@@ -381,52 +596,43 @@ fn compute_node(
             ),
         )),
         ExprOperation::Last(i) => {
-            let inside_node = if let Some(n) = &reset_conditions[state_id] {
-                Node::Mux(
-                    RCell::new(n.clone()),
+            let last = RCell::new(Node::Reg(
+                shared_size[i].len(),
+                RCell::new(Node::TmpValueHolder(i)),
+            ));
+            //the state was entered through a reset
+            if let Some(reset) = &reset_conditions[state_id] {
+                RCell::new(Node::Mux(
+                    reset.clone(),
                     RCell::new(Node::Const(shared_size[i].clone())),
-                    RCell::new(Node::TmpValueHolder(i)),
-                )
+                    last,
+                ))
             } else {
-                Node::TmpValueHolder(i)
-            };
-            RCell::new(Node::Reg(shared_size[i].len(), RCell::new(inside_node)))
+                last
+            }
         }
     };
     state_mem.insert(expr_node, ret.clone());
     ret
 }
 
-fn add_init_values(
+//Returns one of the nodes that replaced the temp values of each shared variable.
+//Nodes reading a shared variable use these copies, and not the node in shared_map.
+fn remove_tmp_value(
     shared_map: &mut AHashMap<usize, RCell<Node>>,
     shared_size: &Vec<Vec<bool>>,
-    n_states: usize,
-    n_input: usize,
-    init_node: &RCell<Node>,
-) {
-    for (i, n) in shared_map.iter_mut() {
-        if *i >= n_states || *i < n_input {
-            continue;
-        }
-        if shared_size[*i]
-            .iter()
-            .fold(false, |prev, next| prev || *next)
-        {
-            *n = RCell::new(Node::Mux(
-                init_node.clone(),
-                RCell::new(Node::Reg(1, n.clone())),
-                RCell::new(Node::Const(shared_size[*i].clone())),
-            ));
-        } else {
-            *n = RCell::new(Node::Reg(1, n.clone()));
-        }
-    }
-}
-
-fn remove_tmp_value(shared_map: &mut AHashMap<usize, RCell<Node>>, shared_size: &Vec<Vec<bool>>) {
+) -> AHashMap<usize, Vec<RCell<Node>>> {
+    let mut copies = AHashMap::new();
     let mut tmp_values = Vec::new();
-    for (_, node) in shared_map.iter() {
-        fetch_tmp_values(node.clone(), &mut tmp_values, &mut AHashSet::new())
+    //sorted, so that the copies are always the same
+    let mut ids = shared_map.keys().cloned().collect::<Vec<_>>();
+    ids.sort();
+    for id in ids {
+        fetch_tmp_values(
+            shared_map[&id].clone(),
+            &mut tmp_values,
+            &mut AHashSet::new(),
+        )
     }
     for val in tmp_values.drain(..) {
         let i = if let Node::TmpValueHolder(i) = &*val.borrow() {
@@ -440,7 +646,9 @@ fn remove_tmp_value(shared_map: &mut AHashMap<usize, RCell<Node>>, shared_size:
             .clone()
             .borrow()
             .clone();
+        copies.entry(i).or_insert_with(Vec::new).push(val);
     }
+    copies
 }
 //get the temp values everywhere in a vec so they can be replaced
 fn fetch_tmp_values(
@@ -466,13 +674,13 @@ fn fetch_tmp_values(
             fetch_tmp_values(e3.clone(), tmp_values, mem)
         }
         Node::Reg(_, e) => fetch_tmp_values(e.clone(), tmp_values, mem),
-        Node::Ram(e1, e2, e3, e4) => {
+        Node::Ram(_, _, e1, e2, e3, e4) => {
             fetch_tmp_values(e1.clone(), tmp_values, mem);
             fetch_tmp_values(e2.clone(), tmp_values, mem);
             fetch_tmp_values(e3.clone(), tmp_values, mem);
             fetch_tmp_values(e4.clone(), tmp_values, mem)
         }
-        Node::Rom(_, e) => fetch_tmp_values(e.clone(), tmp_values, mem),
+        Node::Rom(_, _, _, e) => fetch_tmp_values(e.clone(), tmp_values, mem),
         Node::TmpValueHolder(_) => tmp_values.push(node.clone()),
     }
 }
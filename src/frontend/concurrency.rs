@@ -0,0 +1,121 @@
+use crate::ast::graph_automaton::*;
+use ahash::{AHashMap, AHashSet};
+use std::rc::Rc;
+/*
+This file finds the shared variables assigned by two states which can be active during the same cycle.
+The value of such a variable is the one of the state computed last, which is hardly what was meant.
+
+Two states cannot be active at the same time when they are in the same automaton, and only one state of this
+automaton can be active : the states of an automaton are the ones linked by its transitions. Several of them are
+active when the automaton has several init states, or when a state has several default transitions, as they are
+taken together. Those share the node of their condition.
+The states entered through an instantaneous transition are active during the same cycle as the state they
+come from, but they are computed after it, so their assignments replace the previous ones on purpose.
+A state is active at the same time as its parent, and the states of parallel automata can always be.
+The states of the automata called by two states which cannot be active at the same time cannot be either.
+
+This is only a warning, as the states may never be active at the same time in practice.
+The pairs of states are kept in the graph, so the simulator and the interpreter can tell when it really happens.
+*/
+#[derive(Debug)]
+pub enum ConcurrencyWarning {
+    MultipleAssign(String, Pos, Pos), //the shared variable, and where it is assigned by each state
+}
+
+pub fn find_conflicts(prog: &mut ProgramGraph) -> Vec<ConcurrencyWarning> {
+    let automata = automata(prog);
+    let mut init_counts = AHashMap::new();
+    for state in &prog.init_states {
+        *init_counts.entry(automata[*state]).or_insert(0) += 1;
+    }
+    //the automata with a state which has several default transitions
+    let mut several_defaults = AHashSet::new();
+    for (state_id, state) in prog.states.iter().enumerate() {
+        let outputs = &state.transition_outputs;
+        if outputs.iter().enumerate().any(|(i, (next1, cond1, _, _))| {
+            outputs[i + 1..]
+                .iter()
+                .any(|(next2, cond2, _, _)| Rc::ptr_eq(cond1, cond2) && next1 != next2)
+        }) {
+            several_defaults.insert(automata[state_id]);
+        }
+    }
+    let shared = prog
+        .locations
+        .iter()
+        .map(|(name, _, _)| name)
+        .collect::<AHashSet<_>>();
+    //the states assigning each shared variable named by the user, and where they assign it
+    let mut writes: Vec<(&String, Vec<(usize, Pos)>)> = Vec::new();
+    for (state_id, state) in prog.states.iter().enumerate() {
+        for (name, _, loc) in state
+            .locations
+            .iter()
+            .filter(|(n, _, _)| shared.contains(n))
+        {
+            match writes.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, w)) => w.push((state_id, *loc)),
+                None => writes.push((name, vec![(state_id, *loc)])),
+            }
+        }
+    }
+    let mut conflicts = Vec::new();
+    let mut warnings = Vec::new();
+    for (name, w) in writes {
+        for (i, (s1, loc1)) in w.iter().enumerate() {
+            for (s2, loc2) in &w[i + 1..] {
+                let exclusive = |a: usize, b: usize| {
+                    a != b
+                        && automata[a] == automata[b]
+                        && init_counts.get(&automata[a]) == Some(&1)
+                        && !several_defaults.contains(&automata[a])
+                };
+                let (a1, a2) = (ancestors(prog, *s1), ancestors(prog, *s2));
+                if a1.contains(s2)
+                    || a2.contains(s1)
+                    || !a1.iter().any(|a| a2.iter().any(|b| exclusive(*a, *b)))
+                {
+                    conflicts.push((name.clone(), *s1, *s2));
+                    warnings.push(ConcurrencyWarning::MultipleAssign(
+                        name.clone(),
+                        *loc1,
+                        *loc2,
+                    ));
+                }
+            }
+        }
+    }
+    prog.conflicts = conflicts;
+    warnings
+}
+
+//the automaton of each state, which is the group of states linked by transitions
+fn automata(prog: &ProgramGraph) -> Vec<usize> {
+    let mut automata = (0..prog.states.len()).collect::<Vec<usize>>();
+    fn find(automata: &mut Vec<usize>, s: usize) -> usize {
+        if automata[s] != s {
+            automata[s] = find(automata, automata[s]);
+        }
+        automata[s]
+    }
+    for (state_id, state) in prog.states.iter().enumerate() {
+        for (next_id, _, _, _) in &state.transition_outputs {
+            if let Some(next_id) = next_id {
+                let (a, b) = (find(&mut automata, state_id), find(&mut automata, *next_id));
+                automata[a] = b;
+            }
+        }
+    }
+    (0..prog.states.len())
+        .map(|s| find(&mut automata, s))
+        .collect()
+}
+
+//the state, its parent, the parent of its parent...
+fn ancestors(prog: &ProgramGraph, state_id: usize) -> Vec<usize> {
+    let mut res = vec![state_id];
+    while let Some(parent) = prog.states[*res.last().unwrap()].parent {
+        res.push(parent);
+    }
+    res
+}
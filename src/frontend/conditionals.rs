@@ -0,0 +1,275 @@
+use crate::ast::parse_ast::*;
+use crate::frontend::hierarchical_automata::expr_vars;
+use ahash::{AHashMap, AHashSet};
+use global_counter::global_counter;
+/*
+This file turns the dynamic ifs, whose condition is a signal, into muxes.
+It runs after the functions are inlined : the blocks only contain flat assignments and other ifs.
+
+The variables assigned in a block are renamed inside of it, and their value is chosen after the if :
+if c then x = a else x = b end if
+becomes :
+x$then = a
+x$else = b
+x = mux(c, x$then, x$else)
+
+A variable assigned in only one of the blocks keeps its previous value in the other one (last(x)),
+so it must be a shared variable or an output. The other ones are intermediate values of their block :
+they are not merged, and it is an error to read them outside of it.
+The automata cannot be used in a dynamic if, they would need to be paused.
+*/
+global_counter!(IF_COUNTER, u32, 0);
+
+#[derive(Debug)]
+pub enum DynamicIfError {
+    AssignedInOneBlock(Pos, String), //a local variable read outside of the only block which assigns it
+    UseInIf(Pos, String),
+}
+type Result<T> = std::result::Result<T, DynamicIfError>;
+
+pub fn lower_dynamic_ifs(prog: &mut Program) -> Result<()> {
+    //the automata with static arguments are only used through their instances
+    for (_, automaton) in prog
+        .automata
+        .iter_mut()
+        .filter(|(_, a)| a.static_args.is_empty())
+    {
+        let shared: AHashSet<String> = automaton
+            .shared
+            .iter()
+            .map(|v| v.var.value.clone())
+            .chain(automaton.outputs.iter().map(|a| a.name.clone()))
+            .collect();
+        for (_, state) in automaton.states.iter_mut() {
+            //the number of times each variable is read in the state, to know if it is read outside of a block
+            let mut reads = AHashMap::new();
+            count_reads(&state.statements, &mut reads);
+            for transition in &state.transitions {
+                if let TrCond::Expr(e) = &transition.condition.value {
+                    let mut vars = Vec::new();
+                    expr_vars(e, &mut vars);
+                    for v in vars {
+                        *reads.entry(v.value.clone()).or_insert(0) += 1;
+                    }
+                }
+            }
+            let statements = std::mem::take(&mut state.statements);
+            state.statements = lower_statements(statements, &shared, &reads)?;
+        }
+    }
+    Ok(())
+}
+
+fn lower_statements(
+    statements: Vec<Statement>,
+    shared: &AHashSet<String>,
+    reads: &AHashMap<String, usize>,
+) -> Result<Vec<Statement>> {
+    let mut res = Vec::new();
+    for statement in statements {
+        match statement {
+            Statement::If(IfStruct {
+                condition: IfCondition::Signal(condition),
+                if_block,
+                else_block,
+            }) => res.append(&mut lower_if(
+                condition, if_block, else_block, shared, reads,
+            )?),
+            statement => res.push(statement),
+        }
+    }
+    Ok(res)
+}
+
+//the nested ifs are lowered first, and their merged variables are renamed with the others
+fn lower_if(
+    condition: Loc<Var>,
+    if_block: Vec<Statement>,
+    else_block: Vec<Statement>,
+    shared: &AHashSet<String>,
+    reads: &AHashMap<String, usize>,
+) -> Result<Vec<Statement>> {
+    let counter = IF_COUNTER.get_cloned();
+    IF_COUNTER.inc();
+    let mut if_vars = Vec::new();
+    assigned_vars(&if_block, &mut if_vars)?;
+    let mut else_vars = Vec::new();
+    assigned_vars(&else_block, &mut else_vars)?;
+    let mut if_reads = AHashMap::new();
+    count_reads(&if_block, &mut if_reads);
+    let mut else_reads = AHashMap::new();
+    count_reads(&else_block, &mut else_reads);
+
+    let mut if_map = AHashMap::new();
+    let mut else_map = AHashMap::new();
+    let mut merges = Vec::new();
+    let mut seen = AHashSet::new();
+    for var in if_vars.iter().chain(else_vars.iter()) {
+        if !seen.insert(&var.value) {
+            continue;
+        }
+        let in_if = if_vars.iter().any(|v| v.value == var.value);
+        let in_else = else_vars.iter().any(|v| v.value == var.value);
+        //the variable is assigned in only one of the blocks
+        if in_if != in_else && !shared.contains(&var.value) {
+            let block_reads = if in_if { &if_reads } else { &else_reads };
+            if reads.get(&var.value) > block_reads.get(&var.value) {
+                return Err(DynamicIfError::AssignedInOneBlock(
+                    var.loc,
+                    var.value.clone(),
+                ));
+            }
+            continue;
+        }
+        //the expressions must stay flat, so the previous value is read in its own variable
+        let mut value = |assigned: bool, block: &str, map: &mut AHashMap<String, String>| {
+            let name = format!("$if${}${}${}", var.value, block, counter);
+            if assigned {
+                map.insert(var.value.clone(), name.clone());
+            } else {
+                merges.push(Statement::Assign(vec![VarAssign {
+                    var: Loc::new(var.loc, name.clone()),
+                    expr: Loc::new(var.loc, Expr::Last(var.clone())),
+                }]));
+            }
+            Expr::Var(Loc::new(var.loc, name))
+        };
+        let if_value = value(in_if, "then", &mut if_map);
+        let else_value = value(in_else, "else", &mut else_map);
+        merges.push(Statement::Assign(vec![VarAssign {
+            var: var.clone(),
+            expr: Loc::new(
+                var.loc,
+                Expr::Mux(
+                    Box::new(Loc::new(condition.loc, Expr::Var(condition.clone()))),
+                    Box::new(Loc::new(var.loc, if_value)),
+                    Box::new(Loc::new(var.loc, else_value)),
+                ),
+            ),
+        }]));
+    }
+
+    let mut res = lower_statements(if_block, shared, reads)?;
+    rename_vars(&mut res, &if_map);
+    let mut else_block = lower_statements(else_block, shared, reads)?;
+    rename_vars(&mut else_block, &else_map);
+    res.append(&mut else_block);
+    res.append(&mut merges);
+    Ok(res)
+}
+
+//the variables assigned in a block, with the position of their assignment
+fn assigned_vars(statements: &[Statement], vars: &mut Vec<Loc<Var>>) -> Result<()> {
+    for statement in statements {
+        match statement {
+            Statement::Assign(assigns) => vars.extend(assigns.iter().map(|a| a.var.clone())),
+            Statement::If(IfStruct {
+                if_block,
+                else_block,
+                ..
+            }) => {
+                assigned_vars(if_block, vars)?;
+                assigned_vars(else_block, vars)?;
+            }
+            Statement::FnAssign(f) => vars.extend(f.vars.iter().cloned()),
+            Statement::ExtAutomaton(e) => {
+                return Err(DynamicIfError::UseInIf(e.name.loc, e.name.value.clone()))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn count_reads(statements: &[Statement], reads: &mut AHashMap<String, usize>) {
+    let mut vars = Vec::new();
+    for statement in statements {
+        match statement {
+            Statement::Assign(assigns) => {
+                for assign in assigns {
+                    expr_vars(&assign.expr, &mut vars);
+                }
+            }
+            Statement::If(IfStruct {
+                condition,
+                if_block,
+                else_block,
+            }) => {
+                if let IfCondition::Signal(v) = condition {
+                    vars.push(v);
+                }
+                count_reads(if_block, reads);
+                count_reads(else_block, reads);
+            }
+            Statement::FnAssign(f) => {
+                for arg in f.f.args.iter() {
+                    expr_vars(arg, &mut vars);
+                }
+            }
+            Statement::ExtAutomaton(e) => {
+                for input in e.inputs.iter() {
+                    expr_vars(input, &mut vars);
+                }
+            }
+        }
+    }
+    for v in vars {
+        *reads.entry(v.value.clone()).or_insert(0) += 1;
+    }
+}
+
+//renames the variables assigned and read in a lowered block
+fn rename_vars(statements: &mut Vec<Statement>, map: &AHashMap<String, String>) {
+    for statement in statements {
+        match statement {
+            Statement::Assign(assigns) => {
+                for assign in assigns {
+                    if let Some(name) = map.get(&assign.var.value) {
+                        assign.var.value = name.clone();
+                    }
+                    rename_vars_in_expr(&mut assign.expr, map);
+                }
+            }
+            _ => panic!("Statement other than an assignment in a lowered if. Should not happen."),
+        }
+    }
+}
+
+fn rename_vars_in_expr(expr: &mut Expr, map: &AHashMap<String, String>) {
+    match expr {
+        Expr::Var(v) => {
+            if let Some(name) = map.get(&v.value) {
+                v.value = name.clone();
+            }
+        }
+        //last is the value of the shared variable at the previous cycle
+        Expr::Const(_) | Expr::Last(_) => (),
+        Expr::Not(e) => rename_vars_in_expr(e, map),
+        Expr::Slice(e, _, _) | Expr::Reg(_, e) | Expr::Builtin(_, e) => rename_vars_in_expr(e, map),
+        Expr::BiOp(_, e1, e2) | Expr::Arith(_, e1, e2) => {
+            rename_vars_in_expr(e1, map);
+            rename_vars_in_expr(e2, map)
+        }
+        Expr::Mux(e1, e2, e3) => {
+            rename_vars_in_expr(e1, map);
+            rename_vars_in_expr(e2, map);
+            rename_vars_in_expr(e3, map)
+        }
+        Expr::Ram(RamStruct {
+            read_addr,
+            write_enable,
+            write_addr,
+            write_data,
+        }) => {
+            rename_vars_in_expr(read_addr, map);
+            rename_vars_in_expr(write_enable, map);
+            rename_vars_in_expr(write_addr, map);
+            rename_vars_in_expr(write_data, map)
+        }
+        Expr::Rom(RomStruct { read_addr, .. }) => rename_vars_in_expr(read_addr, map),
+        Expr::FnCall(FnCall { args, .. }) => {
+            for e in args.iter_mut() {
+                rename_vars_in_expr(e, map)
+            }
+        }
+    }
+}
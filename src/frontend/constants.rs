@@ -5,6 +5,10 @@ use solvent::DepGraph;
 In this file, we try to simplify all the constants as much as possible.
 In particular, the global constants are replaced,
 and all the constants outiside of functions are replaced by simple numbers.
+Automata with static arguments are treated like functions : their constants are computed
+when they are instantiated with the values of the static arguments.
+The condition of an if which is a name, but neither a constant nor a static argument, is a signal :
+the if is dynamic, and kept until it is turned into muxes.
 */
 #[derive(Clone, Debug)]
 pub enum ComputeConstError {
@@ -32,29 +36,18 @@ pub fn compute_consts(prog: &mut Program) -> Result<(), ComputeConstError> {
 
     //iterate through statements to call appropriate functions
     for (_, m) in &mut prog.automata {
-        for arg in &mut m.inputs {
-            let res = compute_const(&arg.size, &prog.global_consts)?;
-            arg.size.value = Const::Value(res);
-        }
-        for arg in &mut m.outputs {
-            let res = compute_const(&arg.size, &prog.global_consts)?;
-            arg.size.value = Const::Value(res);
-        }
-        for shared in &mut m.shared {
-            compute_consts_in_expr(&mut shared.expr, &prog.global_consts)?;
-        }
         for (_, state) in &mut m.states {
-            for statement in &mut state.statements {
-                compute_consts_in_statement(statement, &prog.global_consts)?;
-            }
-            for transition in &mut state.transitions {
-                if let TrCond::Expr(expr) = &mut transition.condition.value {
-                    compute_consts_in_expr(
-                        &mut Loc::new(transition.condition.loc, expr),
-                        &prog.global_consts,
-                    )?;
-                }
-            }
+            find_signal_conditions(&mut state.statements, &prog.global_consts, &m.static_args);
+        }
+        if m.static_args.is_empty() {
+            compute_consts_in_automaton(m, &prog.global_consts)?;
+        } else {
+            //the rest is computed when the automaton is instantiated with values
+            let static_args = m.static_args.clone();
+            let consts = &prog.global_consts;
+            map_consts_in_automaton(m, &mut |c: &mut Const| {
+                simplify_const(c, consts, &static_args)
+            })?;
         }
     }
     for (_, function) in &mut prog.functions {
@@ -64,6 +57,11 @@ pub fn compute_consts(prog: &mut Program) -> Result<(), ComputeConstError> {
         for arg in &mut function.return_vars {
             simplify_const(&mut arg.size, &prog.global_consts, &function.static_args)?;
         }
+        find_signal_conditions(
+            &mut function.statements,
+            &prog.global_consts,
+            &function.static_args,
+        );
         for statement in &mut function.statements {
             simplify_consts_in_statement(statement, &prog.global_consts, &function.static_args)?;
         }
@@ -71,6 +69,60 @@ pub fn compute_consts(prog: &mut Program) -> Result<(), ComputeConstError> {
     Ok(())
 }
 
+//marks the conditions of the ifs which are signals instead of constants
+fn find_signal_conditions(
+    statements: &mut Vec<Statement>,
+    consts: &AHashMap<String, Const>,
+    static_args: &Vec<String>,
+) {
+    for statement in statements {
+        if let Statement::If(ifstruct) = statement {
+            if let IfCondition::Const(Const::Var(v)) = &ifstruct.condition {
+                if !consts.contains_key(&v.value) && !static_args.contains(&v.value) {
+                    ifstruct.condition = IfCondition::Signal(v.clone());
+                }
+            }
+            find_signal_conditions(&mut ifstruct.if_block, consts, static_args);
+            find_signal_conditions(&mut ifstruct.else_block, consts, static_args);
+        }
+    }
+}
+
+//replace all the constants of an automaton with values
+pub fn compute_consts_in_automaton(
+    m: &mut Automaton,
+    consts: &AHashMap<String, Const>,
+) -> Result<(), ComputeConstError> {
+    let mut closure = |c: &mut Const| {
+        *c = Const::Value(compute_const(c, consts)?);
+        Ok(())
+    };
+    map_consts_in_automaton(m, &mut closure)
+}
+//maps a closure on all consts in an automaton
+fn map_consts_in_automaton<F>(m: &mut Automaton, f: &mut F) -> Result<(), ComputeConstError>
+where
+    F: FnMut(&mut Const) -> Result<(), ComputeConstError>,
+{
+    for arg in m.inputs.iter_mut().chain(m.outputs.iter_mut()) {
+        f(&mut arg.size.value)?;
+    }
+    for shared in &mut m.shared {
+        map_consts_in_expr(&mut shared.expr, f)?;
+    }
+    for (_, state) in &mut m.states {
+        for statement in &mut state.statements {
+            map_consts_in_statement(statement, f)?;
+        }
+        for transition in &mut state.transitions {
+            if let TrCond::Expr(expr) = &mut transition.condition.value {
+                map_consts_in_expr(expr, f)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 //simplify a constant as much as possible, without assuming it can be reduced to a constant
 //for use in functions only.
 fn simplify_const(
@@ -153,7 +205,9 @@ where
             Ok(())
         }
         Statement::If(ifstruct) => {
-            f(&mut ifstruct.condition)?;
+            if let IfCondition::Const(c) = &mut ifstruct.condition {
+                f(c)?;
+            }
             for stat in &mut ifstruct.if_block {
                 map_consts_in_statement(stat, f)?;
             }
@@ -169,6 +223,7 @@ where
                     name: _,
                     args,
                     static_args,
+                    callers: _,
                 },
         }) => {
             for arg in &mut **args {
@@ -183,6 +238,9 @@ where
             for input_e in e.inputs.iter_mut() {
                 map_consts_in_expr(input_e, f)?;
             }
+            for arg in &mut *e.static_args {
+                f(arg)?;
+            }
             Ok(())
         }
     }
@@ -211,23 +269,18 @@ fn compute_op(op: &ConstBiOp, v1: i32, v2: i32, loc: Pos) -> Result<i32, Compute
     }
 }
 //same as with statements, but for expressions
-fn compute_consts_in_expr(
-    expr: &mut Expr,
-    consts: &AHashMap<String, Const>,
-) -> Result<(), ComputeConstError> {
-    let mut closure = |c: &mut Const| {
-        *c = Const::Value(compute_const(c, consts)?);
-        Ok(())
-    };
-    map_consts_in_expr(expr, &mut closure)
-}
-//same
 fn map_consts_in_expr<F>(expr: &mut Expr, f: &mut F) -> Result<(), ComputeConstError>
 where
     F: FnMut(&mut Const) -> Result<(), ComputeConstError>,
 {
     match expr {
         Expr::Const(ConstExpr::Unknown(_, c)) => f(c),
+        Expr::Const(ConstExpr::Sized(width, literal)) => {
+            if let Literal::Num(c) = &mut literal.value {
+                f(c)?;
+            }
+            f(width)
+        }
         Expr::Not(e) => map_consts_in_expr(e, f),
         Expr::Slice(e, c1, c2) => {
             if let Some(c1) = c1 {
@@ -238,7 +291,7 @@ where
             }
             map_consts_in_expr(e, f)
         }
-        Expr::BiOp(_, e1, e2) => {
+        Expr::BiOp(_, e1, e2) | Expr::Arith(_, e1, e2) => {
             map_consts_in_expr(e1, f)?;
             map_consts_in_expr(e2, f)
         }
@@ -251,6 +304,12 @@ where
             f(c)?;
             map_consts_in_expr(e, f)
         }
+        Expr::Builtin(b, e) => {
+            if let Builtin::Repeat(c) = b {
+                f(c)?;
+            }
+            map_consts_in_expr(e, f)
+        }
         Expr::Ram(RamStruct {
             read_addr,
             write_enable,
@@ -273,6 +332,7 @@ where
             name: _,
             args,
             static_args,
+            callers: _,
         }) => {
             for arg in &mut **args {
                 map_consts_in_expr(arg, f)?;
@@ -0,0 +1,215 @@
+use crate::ast::graph::{FlatProgramGraph, Node};
+use crate::ast::parse_ast::*;
+use crate::util::schedule::{dependencies, late_dependencies};
+use ahash::AHashSet;
+/*
+This file finds the parts of a program which are useless : they can be removed without changing what it computes.
+It is not an error, as it often happens while a design is being written, but large designs accumulate a lot of it.
+
+Most of it is found in the syntax tree, before the transitions are made explicit :
+-the shared variables which are never read (their value is lost)
+-the states which cannot be reached from an init state
+-the functions which are never called (only in the main file, as the imported ones are libraries)
+It also finds the states with several default transitions. They are not dead : when none of the conditions
+are true, all the default transitions are taken together, and all their states become active, which is
+rarely what was meant.
+The inputs of main which don't influence any output are found at the end, in the flat graph.
+*/
+#[derive(Debug)]
+pub enum DeadCodeWarning {
+    UnusedShared(String, Pos),
+    UnreachableState(String, Pos),
+    UnusedFunction(String, Pos),
+    DuplicateDefault(Pos, Pos), //a default transition, and the first default one of the state
+    UnusedInput(String, Pos),
+}
+
+pub fn find_dead_code(prog: &Program) -> Vec<DeadCodeWarning> {
+    let mut warnings = Vec::new();
+    let mut calls = AHashSet::new();
+    let mut names: Vec<&String> = prog.automata.keys().collect();
+    names.sort();
+    for name in names {
+        let automaton = &prog.automata[name];
+        let mut reads = AHashSet::new();
+        let mut states: Vec<&State> = automaton.states.values().collect();
+        states.sort_by_key(|s| s.name.loc);
+        for state in &states {
+            statements_reads(&state.statements, &mut reads, &mut calls);
+            for transition in &state.transitions {
+                if let TrCond::Expr(e) = &transition.condition.value {
+                    expr_reads(e, &mut reads, &mut calls)
+                }
+            }
+            if let Some(default) = state.transitions.iter().find(|t| t.condition.is_default()) {
+                warnings.extend(
+                    state
+                        .transitions
+                        .iter()
+                        .filter(|t| t.condition.is_default())
+                        .skip(1)
+                        .map(|t| {
+                            DeadCodeWarning::DuplicateDefault(
+                                t.condition.loc,
+                                default.condition.loc,
+                            )
+                        }),
+                );
+            }
+        }
+        reads.extend(automaton.outputs.iter().map(|o| &*o.name));
+        warnings.extend(
+            automaton
+                .shared
+                .iter()
+                .filter(|v| !reads.contains(&*v.var.value))
+                .map(|v| DeadCodeWarning::UnusedShared(v.var.value.clone(), v.var.loc)),
+        );
+        //the states reached from the init states by the transitions
+        let mut reached: AHashSet<&str> = automaton.init_states.iter().map(|s| &*s.value).collect();
+        let mut queue: Vec<&str> = reached.iter().copied().collect();
+        while let Some(state) = queue.pop() {
+            for transition in automaton
+                .states
+                .get(state)
+                .into_iter()
+                .flat_map(|s| &s.transitions)
+            {
+                if let Some(next) = transition.state.value.as_deref() {
+                    if reached.insert(next) {
+                        queue.push(next)
+                    }
+                }
+            }
+        }
+        warnings.extend(
+            states
+                .iter()
+                .filter(|s| !reached.contains(&*s.name.value))
+                .map(|s| DeadCodeWarning::UnreachableState(s.name.value.clone(), s.name.loc)),
+        );
+    }
+    let mut functions: Vec<&Function> = prog.functions.values().collect();
+    functions.sort_by_key(|f| f.name.loc);
+    for function in &functions {
+        //a recursive call does not count
+        let mut function_calls = AHashSet::new();
+        statements_reads(
+            &function.statements,
+            &mut AHashSet::new(),
+            &mut function_calls,
+        );
+        function_calls.remove(&*function.name.value);
+        calls.extend(function_calls);
+    }
+    warnings.extend(
+        functions
+            .iter()
+            .filter(|f| f.name.loc.0 == 0 && !calls.contains(&*f.name.value))
+            .map(|f| DeadCodeWarning::UnusedFunction(f.name.value.clone(), f.name.loc)),
+    );
+    warnings
+}
+
+//the inputs of main which are not needed to compute the outputs.
+//they are located with the shared variables of the graph before it was flattened (the inputs are the first ones),
+//as the unused ones are not in the flat graph anymore
+pub fn find_unused_inputs(
+    prog: &FlatProgramGraph,
+    locations: &[(String, usize, Pos)],
+) -> Vec<DeadCodeWarning> {
+    let mut used = AHashSet::new();
+    let mut done = AHashSet::new();
+    let mut stack: Vec<_> = prog.outputs.iter().map(|(_, node)| node.clone()).collect();
+    while let Some(node) = stack.pop() {
+        if !done.insert(node.id()) {
+            continue;
+        }
+        let node = node.borrow();
+        if let Node::Input(i) = &*node {
+            used.insert(*i);
+        }
+        stack.extend(dependencies(&node));
+        stack.extend(late_dependencies(&node));
+    }
+    locations
+        .iter()
+        .filter(|(_, i, _)| *i < prog.inputs.len() && !used.contains(i))
+        .map(|(name, _, loc)| DeadCodeWarning::UnusedInput(name.clone(), *loc))
+        .collect()
+}
+
+//the variables read and the functions called by some statements
+fn statements_reads<'a>(
+    statements: &'a [Statement],
+    reads: &mut AHashSet<&'a str>,
+    calls: &mut AHashSet<&'a str>,
+) {
+    for statement in statements {
+        match statement {
+            Statement::Assign(assigns) => {
+                for assign in assigns {
+                    expr_reads(&assign.expr, reads, calls)
+                }
+            }
+            Statement::If(IfStruct {
+                condition,
+                if_block,
+                else_block,
+            }) => {
+                if let IfCondition::Signal(v) = condition {
+                    reads.insert(&v.value);
+                }
+                statements_reads(if_block, reads, calls);
+                statements_reads(else_block, reads, calls)
+            }
+            Statement::FnAssign(FnAssign { f, .. }) => fn_call_reads(f, reads, calls),
+            Statement::ExtAutomaton(e) => {
+                for input in e.inputs.iter() {
+                    expr_reads(input, reads, calls)
+                }
+            }
+        }
+    }
+}
+
+//the last value of a variable is a read too
+fn expr_reads<'a>(expr: &'a Expr, reads: &mut AHashSet<&'a str>, calls: &mut AHashSet<&'a str>) {
+    match expr {
+        Expr::Var(v) | Expr::Last(v) => {
+            reads.insert(&v.value);
+        }
+        Expr::Const(_) => (),
+        Expr::Not(e) => expr_reads(e, reads, calls),
+        Expr::Slice(e, _, _) | Expr::Reg(_, e) | Expr::Builtin(_, e) => expr_reads(e, reads, calls),
+        Expr::BiOp(_, e1, e2) | Expr::Arith(_, e1, e2) => {
+            expr_reads(e1, reads, calls);
+            expr_reads(e2, reads, calls)
+        }
+        Expr::Mux(e1, e2, e3) => {
+            expr_reads(e1, reads, calls);
+            expr_reads(e2, reads, calls);
+            expr_reads(e3, reads, calls)
+        }
+        Expr::Ram(RamStruct {
+            read_addr,
+            write_enable,
+            write_addr,
+            write_data,
+        }) => {
+            expr_reads(read_addr, reads, calls);
+            expr_reads(write_enable, reads, calls);
+            expr_reads(write_addr, reads, calls);
+            expr_reads(write_data, reads, calls)
+        }
+        Expr::Rom(RomStruct { read_addr, .. }) => expr_reads(read_addr, reads, calls),
+        Expr::FnCall(f) => fn_call_reads(f, reads, calls),
+    }
+}
+
+fn fn_call_reads<'a>(f: &'a FnCall, reads: &mut AHashSet<&'a str>, calls: &mut AHashSet<&'a str>) {
+    calls.insert(&f.name.value);
+    for arg in f.args.iter() {
+        expr_reads(arg, reads, calls)
+    }
+}
@@ -1,121 +1,373 @@
 mod parser;
 mod parse_ast {
-    use std::collections::{HashMap, HashSet};
-
     pub use crate::ast::BiOp;
+    use crate::ast::parse_ast::Loc;
+    //a variable, with its position in the file
+    pub type Var<'a> = Loc<&'a str>;
+    //The lists keep the order of the file (it matters for the inputs),
+    //and duplicates are checked afterwards.
     pub struct Netlist<'a> {
-        pub inputs: HashSet<&'a str>,
-        pub outputs: HashSet<&'a str>,
-        pub vars: HashMap<&'a str, usize>,
-        pub instr: HashMap<&'a str, Expr<'a>>,
+        pub inputs: Vec<Var<'a>>,
+        pub outputs: Vec<Var<'a>>,
+        pub vars: Vec<(Var<'a>, usize)>,
+        pub instr: Vec<(Var<'a>, Expr<'a>)>,
     }
     pub enum Expr<'a> {
         Const(Vec<bool>),
-        Not(&'a str),
-        Reg(&'a str),
-        Var(&'a str),
-        BiOp(BiOp, &'a str, &'a str),
-        Mux(&'a str, &'a str, &'a str),
-        Ram(&'a str, &'a str, &'a str, &'a str),
-        Rom(&'a str),
-        Slice(&'a str, usize, usize),
+        Not(Var<'a>),
+        Reg(Var<'a>),
+        Var(Var<'a>),
+        BiOp(BiOp, Var<'a>, Var<'a>),
+        Mux(Var<'a>, Var<'a>, Var<'a>),
+        //the sizes (address size, word size) are optional, as older netlists don't have them
+        Ram(Option<(usize, usize)>, Var<'a>, Var<'a>, Var<'a>, Var<'a>),
+        Rom(Option<(usize, usize)>, Var<'a>),
+        Slice(Var<'a>, usize, usize),
     }
 }
 
-use std::{collections::HashMap, fs::read_to_string};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::read_to_string,
+    path::PathBuf,
+    rc::Rc,
+};
 
-use crate::ast::graph::*;
+use crate::ast::{graph::*, parse_ast::Pos};
+use crate::frontend::parser_wrapper::{FileError, ParseErrorType, ParserError};
+use crate::util::{errors::TinyjazzError, schedule::CombinationalCycle};
+use codespan_reporting::files::SimpleFiles;
 use parse_ast::*;
 
+use lalrpop_util::ParseError;
 use parser::ProgramParser;
+/*
+Netlists are checked before being converted, so that a malformed netlist
+gives an error message instead of a panic.
+*/
+#[derive(Debug)]
+pub enum NetlistError {
+    UndeclaredVar(Pos, String),              //not in the VAR section
+    UndefinedVar(Pos, String),               //never assigned, and not an input
+    MultipleDeclaration(Pos, String, Pos),   //declared twice in the VAR section
+    MultipleAssign(Pos, String, Pos),        //assigned twice
+    WrongSize(Pos, String, usize, usize, Pos), //var, expected size, declared size, declaration
+    IndexOutOfRange(Pos, usize, usize),      //index, size of the bus
+    ReversedSlice(Pos, usize, usize),        //first index, last index
+}
+type Result<T> = std::result::Result<T, NetlistError>;
+//the size and the definition of each variable
+type Sizes<'a> = HashMap<&'a str, usize>;
+type Definitions<'a, 'b> = HashMap<&'a str, &'b Expr<'a>>;
+
 //parse the file and convert it into a flatprogramgraph
-pub fn from_netlist(path: &str) -> FlatProgramGraph {
-    let file = read_to_string(path.clone()).unwrap();
-    let netlist = ProgramParser::new().parse(&file).unwrap();
-    let mut mem = HashMap::<&str, RCell<Node>>::new();
+pub fn from_netlist(path: &str) -> std::result::Result<FlatProgramGraph, TinyjazzError> {
+    let mut files = SimpleFiles::new();
+    let file = read_to_string(path).map_err(|e| {
+        let error: ParserError = FileError::from((PathBuf::from(path), e)).into();
+        (error, Rc::new(files.clone()))
+    })?;
+    let file_id = files.add(path.to_string(), file.clone());
+    let netlist = ProgramParser::new()
+        .parse(file_id, &file)
+        .map_err(|e| {
+            let error = match e {
+                ParseError::User { error: (l, s, r) } => ParseErrorType::Invalid(l, r, s.into()),
+                e => e.map_error(|(_, s, _)| s).into(),
+            };
+            let error: ParserError = (file_id, error).into();
+            (error, Rc::new(files.clone()))
+        })?;
+    let files = Rc::new(files);
+    let (sizes, instr) = check_netlist(&netlist).map_err(|e| (e, files.clone()))?;
+    let order = netlist_order(&netlist, &instr).map_err(|e| (e, files.clone()))?;
     let in_map: HashMap<&str, usize> = netlist
         .inputs
         .iter()
         .enumerate()
-        .map(|(i, v)| (*v, i))
+        .map(|(i, v)| (v.value, i))
         .collect();
     let inputs = netlist
         .inputs
         .iter()
-        .map(|v| *netlist.vars.get(v).unwrap())
+        .map(|v| (v.value.to_string(), *sizes.get(v.value).unwrap()))
         .collect();
-    let mut defer = Vec::new();
+    let mut mem = HashMap::<&str, RCell<Node>>::new();
+    let mut late = Vec::new();
+    for var in order {
+        let node = node_from_var(var, &instr, &sizes, &mem, &in_map, &mut late);
+        mem.insert(var, node);
+    }
+    for (var, node) in late {
+        let n = late_node(var, &instr, &sizes, &mem);
+        *node.borrow_mut() = n;
+    }
     let outputs = netlist
         .outputs
         .iter()
-        .map(|v| {
-            (
-                v.to_string(),
-                node_from_var(v, &netlist, &mut mem, &in_map, &mut defer),
-            )
+        .map(|v| (v.to_string(), mem.get(v.value).unwrap().clone()))
+        .collect();
+    //the variables named by the user are kept as probes (the ones written by tinyjazz are v_<id>)
+    let mut probes: Vec<(String, RCell<Node>)> = mem
+        .into_iter()
+        .filter(|(v, _)| {
+            !(in_map.contains_key(v)
+                || netlist.outputs.iter().any(|o| o.value == *v)
+                || v.starts_with("v_") && v[2..].chars().all(|c| c.is_ascii_digit()))
         })
+        .map(|(v, node)| (v.to_string(), node))
         .collect();
-    while !defer.is_empty() {
-        let cur_defer = defer;
-        defer = Vec::new();
-        for (v, node) in cur_defer {
-            let n = node_from_var(v, &netlist, &mut mem, &in_map, &mut defer);
-            *node.borrow_mut() = Node::Reg(*netlist.vars.get(v).unwrap(), n);
+    probes.sort_by(|(v1, _), (v2, _)| v1.cmp(v2));
+    Ok(FlatProgramGraph {
+        inputs,
+        outputs,
+        probes,
+        locations: Vec::new(),
+        conflicts: Vec::new(),
+        files: None,
+    })
+}
+
+//Checks that every variable is declared and assigned exactly once, and that the sizes are consistent.
+//Returns the size and the definition of every variable.
+fn check_netlist<'a, 'b>(netlist: &'b Netlist<'a>) -> Result<(Sizes<'a>, Definitions<'a, 'b>)> {
+    let mut decls: HashMap<&str, (usize, Pos)> = HashMap::new();
+    for (var, size) in &netlist.vars {
+        if let Some((_, loc)) = decls.insert(var.value, (*size, var.loc)) {
+            return Err(NetlistError::MultipleDeclaration(
+                var.loc,
+                var.to_string(),
+                loc,
+            ));
+        }
+    }
+    let mut defs: HashMap<&str, (Pos, &Expr)> = HashMap::new();
+    let mut input_locs: HashMap<&str, Pos> = HashMap::new();
+    for var in &netlist.inputs {
+        declared_size(var, &decls)?;
+        if let Some(loc) = input_locs.insert(var.value, var.loc) {
+            return Err(NetlistError::MultipleAssign(var.loc, var.to_string(), loc));
+        }
+    }
+    for (var, expr) in &netlist.instr {
+        declared_size(var, &decls)?;
+        if let Some(loc) = input_locs.get(var.value) {
+            return Err(NetlistError::MultipleAssign(var.loc, var.to_string(), *loc));
+        }
+        if let Some((loc, _)) = defs.insert(var.value, (var.loc, expr)) {
+            return Err(NetlistError::MultipleAssign(var.loc, var.to_string(), loc));
+        }
+    }
+    //a variable used as an operand must be declared and assigned somewhere
+    let use_var = |var: &Var| {
+        if !defs.contains_key(var.value) && !input_locs.contains_key(var.value) {
+            declared_size(var, &decls)?;
+            return Err(NetlistError::UndefinedVar(var.loc, var.to_string()));
+        }
+        declared_size(var, &decls)
+    };
+    for var in &netlist.outputs {
+        use_var(var)?;
+    }
+    for (var, expr) in &netlist.instr {
+        let size = declared_size(var, &decls)?;
+        match expr {
+            Expr::Const(c) => expect_size(var, c.len(), &decls)?,
+            Expr::Var(v) | Expr::Not(v) | Expr::Reg(v) => {
+                let n = use_var(v)?;
+                expect_size(var, n, &decls)?
+            }
+            Expr::BiOp(BiOp::Concat, v1, v2) => {
+                let n = use_var(v1)? + use_var(v2)?;
+                expect_size(var, n, &decls)?
+            }
+            Expr::BiOp(_, v1, v2) => {
+                let n = use_var(v1)?;
+                use_var(v2)?;
+                expect_size(v2, n, &decls)?;
+                expect_size(var, n, &decls)?
+            }
+            Expr::Mux(c, v1, v2) => {
+                use_var(c)?;
+                expect_size(c, 1, &decls)?;
+                let n = use_var(v1)?;
+                use_var(v2)?;
+                expect_size(v2, n, &decls)?;
+                expect_size(var, n, &decls)?
+            }
+            Expr::Ram(sizes, read_addr, write_enable, write_addr, write_data) => {
+                let (addr_size, word_size) = sizes.unwrap_or((use_var(read_addr)?, size));
+                for v in [read_addr, write_addr].iter() {
+                    use_var(v)?;
+                    expect_size(v, addr_size, &decls)?;
+                }
+                use_var(write_enable)?;
+                expect_size(write_enable, 1, &decls)?;
+                use_var(write_data)?;
+                expect_size(write_data, word_size, &decls)?;
+                expect_size(var, word_size, &decls)?
+            }
+            Expr::Rom(sizes, read_addr) => {
+                let (addr_size, word_size) = sizes.unwrap_or((use_var(read_addr)?, size));
+                use_var(read_addr)?;
+                expect_size(read_addr, addr_size, &decls)?;
+                expect_size(var, word_size, &decls)?
+            }
+            Expr::Slice(v, c1, c2) => {
+                let n = use_var(v)?;
+                if c1 >= c2 {
+                    return Err(NetlistError::ReversedSlice(v.loc, *c1, *c2 - 1));
+                }
+                if *c2 > n {
+                    return Err(NetlistError::IndexOutOfRange(v.loc, *c2 - 1, n));
+                }
+                expect_size(var, c2 - c1, &decls)?
+            }
+        }
+    }
+    Ok((
+        decls.into_iter().map(|(v, (size, _))| (v, size)).collect(),
+        defs.into_iter().map(|(v, (_, expr))| (v, expr)).collect(),
+    ))
+}
+
+fn declared_size(var: &Var, decls: &HashMap<&str, (usize, Pos)>) -> Result<usize> {
+    decls
+        .get(var.value)
+        .map(|(size, _)| *size)
+        .ok_or_else(|| NetlistError::UndeclaredVar(var.loc, var.to_string()))
+}
+
+fn expect_size(var: &Var, expected: usize, decls: &HashMap<&str, (usize, Pos)>) -> Result<()> {
+    let (size, loc) = decls.get(var.value).unwrap();
+    if *size != expected {
+        Err(NetlistError::WrongSize(
+            var.loc,
+            var.to_string(),
+            expected,
+            *size,
+            *loc,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+//the variables needed to compute the outputs, each one after the ones it needs during the same cycle.
+//The search is iterative, as a netlist can be a long chain, and it stops on a combinational cycle.
+//The arguments of the registers and the written values of the rams are only needed at the end
+//of the cycle, so they are searched from afterwards.
+fn netlist_order<'a>(
+    netlist: &Netlist<'a>,
+    instr: &HashMap<&'a str, &Expr<'a>>,
+) -> std::result::Result<Vec<&'a str>, CombinationalCycle> {
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    let mut roots: Vec<&str> = netlist.outputs.iter().rev().map(|v| v.value).collect();
+    while let Some(root) = roots.pop() {
+        let mut in_progress = HashSet::new();
+        let mut stack = vec![(root, 0)];
+        while let Some((var, i)) = stack.pop() {
+            if done.contains(var) {
+                continue;
+            }
+            if i == 0 && !in_progress.insert(var) {
+                //the variables in progress are on the stack, each one computed from the next one
+                let locs: HashMap<&str, Pos> = netlist
+                    .instr
+                    .iter()
+                    .map(|(v, _)| (v.value, v.loc))
+                    .collect();
+                let start = stack.iter().position(|(v, _)| *v == var).unwrap();
+                return Err(CombinationalCycle(
+                    stack[start..]
+                        .iter()
+                        .map(|(v, _)| (v.to_string(), locs.get(v).copied()))
+                        .collect(),
+                ));
+            }
+            let (deps, late_deps) = instr
+                .get(var)
+                .map_or((Vec::new(), Vec::new()), |e| dependencies(e));
+            if i < deps.len() {
+                stack.push((var, i + 1));
+                if !done.contains(deps[i]) {
+                    stack.push((deps[i], 0));
+                }
+                continue;
+            }
+            roots.extend(late_deps);
+            in_progress.remove(var);
+            done.insert(var);
+            order.push(var);
+        }
+    }
+    Ok(order)
+}
+
+//the variables needed by an expression during the cycle, and the ones needed at the end of the cycle
+fn dependencies<'a>(expr: &Expr<'a>) -> (Vec<&'a str>, Vec<&'a str>) {
+    match expr {
+        Expr::Const(_) => (Vec::new(), Vec::new()),
+        Expr::Var(v) | Expr::Not(v) | Expr::Rom(_, v) | Expr::Slice(v, _, _) => {
+            (vec![v.value], Vec::new())
         }
+        Expr::Reg(v) => (Vec::new(), vec![v.value]),
+        Expr::BiOp(_, v1, v2) => (vec![v1.value, v2.value], Vec::new()),
+        Expr::Mux(v1, v2, v3) => (vec![v1.value, v2.value, v3.value], Vec::new()),
+        Expr::Ram(_, v1, v2, v3, v4) => (vec![v1.value], vec![v2.value, v3.value, v4.value]),
     }
-    FlatProgramGraph { inputs, outputs }
 }
 
+//the node of a variable, once the nodes it needs during the cycle are built.
+//The registers and the rams can need variables which come after them, they are built at the end
 fn node_from_var<'a>(
     var: &'a str,
-    netlist: &Netlist<'a>,
-    mem: &mut HashMap<&'a str, RCell<Node>>,
+    instr: &HashMap<&'a str, &Expr<'a>>,
+    sizes: &Sizes<'a>,
+    mem: &HashMap<&'a str, RCell<Node>>,
     in_map: &HashMap<&str, usize>,
-    defer: &mut Vec<(&'a str, RCell<Node>)>,
+    late: &mut Vec<(&'a str, RCell<Node>)>,
 ) -> RCell<Node> {
-    if let Some(n) = mem.get(var) {
-        return n.clone();
-    }
     if let Some(i) = in_map.get(var) {
-        let node = RCell::new(Node::Input(*i));
-        mem.insert(var, node.clone());
-        return node;
+        return RCell::new(Node::Input(*i));
     }
-    let node = match netlist.instr.get(var).unwrap() {
-        Expr::Var(v) => node_from_var(*v, netlist, mem, in_map, defer),
+    let node = |v: &Var| mem.get(v.value).unwrap().clone();
+    match instr.get(var).unwrap() {
+        Expr::Var(v) => node(v),
         Expr::Const(c) => RCell::new(Node::Const(c.clone())),
-        Expr::Not(v) => RCell::new(Node::Not(node_from_var(*v, netlist, mem, in_map, defer))),
-        Expr::Reg(v) => {
-            let res = RCell::new(Node::TmpValueHolder(defer.len()));
-            defer.push((v, res.clone()));
+        Expr::Not(v) => RCell::new(Node::Not(node(v))),
+        Expr::Reg(_) | Expr::Ram(..) => {
+            let res = RCell::new(Node::TmpValueHolder(late.len()));
+            late.push((var, res.clone()));
             res
         }
-        Expr::Rom(v) => RCell::new(Node::Rom(
-            *netlist.vars.get(v).unwrap(),
-            node_from_var(*v, netlist, mem, in_map, defer),
-        )),
-        Expr::BiOp(op, v1, v2) => RCell::new(Node::BiOp(
-            op.clone(),
-            node_from_var(*v1, netlist, mem, in_map, defer),
-            node_from_var(*v2, netlist, mem, in_map, defer),
-        )),
-        Expr::Mux(v1, v2, v3) => RCell::new(Node::Mux(
-            node_from_var(*v1, netlist, mem, in_map, defer),
-            node_from_var(*v2, netlist, mem, in_map, defer),
-            node_from_var(*v3, netlist, mem, in_map, defer),
-        )),
-        Expr::Ram(v1, v2, v3, v4) => RCell::new(Node::Ram(
-            node_from_var(*v1, netlist, mem, in_map, defer),
-            node_from_var(*v2, netlist, mem, in_map, defer),
-            node_from_var(*v3, netlist, mem, in_map, defer),
-            node_from_var(*v4, netlist, mem, in_map, defer),
-        )),
-        Expr::Slice(v, c1, c2) => RCell::new(Node::Slice(
-            node_from_var(*v, netlist, mem, in_map, defer),
-            *c1,
-            *c2,
-        )),
-    };
-    mem.insert(var, node.clone());
-    node
+        Expr::Rom(rom_sizes, v) => {
+            let (addr_size, word_size) =
+                rom_sizes.unwrap_or((*sizes.get(v.value).unwrap(), *sizes.get(var).unwrap()));
+            RCell::new(Node::Rom(var.to_string(), addr_size, word_size, node(v)))
+        }
+        Expr::BiOp(op, v1, v2) => RCell::new(Node::BiOp(op.clone(), node(v1), node(v2))),
+        Expr::Mux(v1, v2, v3) => RCell::new(Node::Mux(node(v1), node(v2), node(v3))),
+        Expr::Slice(v, c1, c2) => RCell::new(Node::Slice(node(v), *c1, *c2)),
+    }
+}
+
+//the register or the ram computing a variable, once all the nodes are built
+fn late_node<'a>(
+    var: &'a str,
+    instr: &HashMap<&'a str, &Expr<'a>>,
+    sizes: &Sizes<'a>,
+    mem: &HashMap<&'a str, RCell<Node>>,
+) -> Node {
+    let node = |v: &Var| mem.get(v.value).unwrap().clone();
+    match instr.get(var).unwrap() {
+        Expr::Reg(v) => Node::Reg(*sizes.get(v.value).unwrap(), node(v)),
+        Expr::Ram(ram_sizes, v1, v2, v3, v4) => {
+            let (addr_size, word_size) =
+                ram_sizes.unwrap_or((*sizes.get(v1.value).unwrap(), *sizes.get(var).unwrap()));
+            Node::Ram(addr_size, word_size, node(v1), node(v2), node(v3), node(v4))
+        }
+        _ => panic!("Should not happen : only the registers and the rams are built late"),
+    }
 }
@@ -1,7 +1,9 @@
 // auto-generated: "lalrpop 0.19.1"
-// sha256: d6abb1ac2eacdab87f777af07914d9e238c97219662ff3772c20b72e6843a6
+// sha256: 91adc76911e7f8684fb4ca361b0c14ffc6031e05e39587be44559b0911a1fc3
 use std::str::FromStr;
+use lalrpop_util::ParseError;
 use crate::frontend::from_netlist::parse_ast::*;
+use crate::ast::parse_ast::Loc;
 #[allow(unused_extern_crates)]
 extern crate lalrpop_util as __lalrpop_util;
 #[allow(unused_imports)]
@@ -12,7 +14,9 @@ mod __parse__Program {
     #![allow(non_snake_case, non_camel_case_types, unused_mut, unused_variables, unused_imports, unused_parens)]
 
     use std::str::FromStr;
+    use lalrpop_util::ParseError;
     use crate::frontend::from_netlist::parse_ast::*;
+    use crate::ast::parse_ast::Loc;
     #[allow(unused_extern_crates)]
     extern crate lalrpop_util as __lalrpop_util;
     #[allow(unused_imports)]
@@ -22,158 +26,179 @@ mod __parse__Program {
     pub enum __Symbol<'input>
      {
         Variant0(&'input str),
-        Variant1((&'input str, usize)),
-        Variant2(::std::vec::Vec<(&'input str, usize)>),
-        Variant3(::std::vec::Vec<&'input str>),
-        Variant4(BiOp),
-        Variant5(Vec<(&'input str, usize)>),
-        Variant6(Vec<&'input str>),
-        Variant7(Vec<bool>),
-        Variant8(Expr<'input>),
-        Variant9((&'input str, Expr<'input>)),
-        Variant10(::std::vec::Vec<(&'input str, Expr<'input>)>),
-        Variant11(usize),
-        Variant12(Netlist<'input>),
-        Variant13(::std::option::Option<(&'input str, usize)>),
-        Variant14(::std::option::Option<&'input str>),
+        Variant1((Var<'input>, usize)),
+        Variant2(::std::vec::Vec<(Var<'input>, usize)>),
+        Variant3(Var<'input>),
+        Variant4(::std::vec::Vec<Var<'input>>),
+        Variant5(usize),
+        Variant6(BiOp),
+        Variant7(Vec<(Var<'input>, usize)>),
+        Variant8(Vec<Var<'input>>),
+        Variant9(Vec<bool>),
+        Variant10(Expr<'input>),
+        Variant11((Var<'input>, Expr<'input>)),
+        Variant12(::std::vec::Vec<(Var<'input>, Expr<'input>)>),
+        Variant13(Netlist<'input>),
+        Variant14(::std::option::Option<(Var<'input>, usize)>),
+        Variant15(::std::option::Option<Var<'input>>),
     }
     const __ACTION: &[i8] = &[
         // State 0
         0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 1
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -21, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -23, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 2
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -23, 0, 0, 0, 0, 0, -23, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -25, 0, 0, 0, 0, 0, -25, 0, 0, 38,
         // State 3
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -21, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -23, 0, 0, 38,
         // State 4
-        0, 0, 0, 0, 0, 0, 0, -17, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, -19, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 5
-        0, 0, 0, 0, 0, 0, 0, -19, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, -21, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 6
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 7
-        0, 45, 46, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 47, 0,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 0,
         // State 8
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 9
-        0, 52, 53, 0, 0, 54, 55, 0, 0, 12, 56, 13, 57, 0, 14, 15, 16, 17, 18, 0, 58, 0, 31,
+        0, 59, 60, 0, 0, 61, 62, 0, 0, 12, 63, 13, 64, 0, 14, 15, 16, 17, 18, 0, 65, 66, 38,
         // State 10
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 11
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 12
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 13
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 38,
         // State 14
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 15
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 38,
         // State 16
-        0, 45, 46, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 47, 0,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 0,
         // State 17
-        0, 45, 46, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 47, 0,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 0,
         // State 18
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 19
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 20
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 0,
         // State 21
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 22
-        0, 45, 46, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 47, 0,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 0,
         // State 23
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 24
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 52, 53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 54, 0,
         // State 25
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 26
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 27
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 28
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 29
-        33, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -20, 0, 0, 0, 0, 0, -20, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 30
-        -52, 0, 0, -52, -52, 0, 0, -52, 0, 0, 0, 0, 0, -52, 0, 0, 0, 0, 0, -52, 0, 0, -52,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 31
-        34, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -22, 0, 0, 0, 0, 0, -22, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 32
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -9, 0, 0, 0, 0, 0, -9, 0, 0, -9,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 33
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -10, 0, 0, 0, 0, 0, -10, 0, 0, -10,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 38,
         // State 34
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 35
-        0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 36
-        40, 0, 0, 0, 0, 0, 0, -16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        40, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -22, 0, 0, 0, 0, 0, -22, 0, 0, 0,
         // State 37
-        -48, 0, 0, 8, 0, 0, 0, -48, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        -57, 0, 0, -57, -57, 0, 0, -57, 0, 0, 0, 0, 0, -57, 0, 0, 0, 0, 0, -57, 0, 0, -57,
         // State 38
-        41, 0, 0, 0, 0, 0, 0, -18, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        41, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -24, 0, 0, 0, 0, 0, -24, 0, 0, 0,
         // State 39
-        0, 0, 0, 0, 0, 0, 0, -4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -4,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -9, 0, 0, 0, 0, 0, -9, 0, 0, -9,
         // State 40
-        0, 0, 0, 0, 0, 0, 0, -5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -5,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -10, 0, 0, 0, 0, 0, -10, 0, 0, -10,
         // State 41
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -41,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0,
         // State 42
-        0, 0, 0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 43
-        -49, 0, 0, 0, 0, 0, 0, -49, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        47, 0, 0, 0, 0, 0, 0, -18, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 44
-        -44, -44, -44, 0, 0, 0, 0, -44, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -44, -44,
+        -53, 0, 0, 8, 0, 0, 0, -53, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 45
-        -45, -45, -45, 0, 0, 0, 0, -45, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -45, -45,
+        48, 0, 0, 0, 0, 0, 0, -20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 46
-        -43, -43, -43, 0, 0, 0, 0, -43, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -43, -43,
+        0, 0, 0, 0, 0, 0, 0, -4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -4,
         // State 47
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -42,
+        0, 0, 0, 0, 0, 0, 0, -5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -5,
         // State 48
-        0, 59, 60, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -36,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -46,
         // State 49
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -38,
+        0, 0, 0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 50
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -37,
+        -54, 0, 0, 0, 0, 0, 0, -54, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
         // State 51
-        0, -26, -26, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -26,
+        -49, -49, -49, 0, 0, 0, 0, -49, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -49, -49,
         // State 52
-        0, -27, -27, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -27,
+        -50, -50, -50, 0, 0, 0, 0, -50, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -50, -50,
         // State 53
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -11,
+        -48, -48, -48, 0, 0, 0, 0, -48, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -48, -48,
         // State 54
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -15,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -47,
         // State 55
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -14,
+        0, 67, 68, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -41,
         // State 56
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -12,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -43,
         // State 57
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -13,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -42,
         // State 58
-        0, -24, -24, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -24,
+        0, -28, -28, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -28,
         // State 59
-        0, -25, -25, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -25,
+        0, -29, -29, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -29,
         // State 60
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -28,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -13,
         // State 61
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -30,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -17,
         // State 62
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -29,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -16,
         // State 63
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -31,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -14,
         // State 64
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -33,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -15,
         // State 65
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -34,
+        0, -30, -30, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -30,
         // State 66
-        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -32,
+        0, -26, -26, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -26,
         // State 67
+        0, -27, -27, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -27,
+        // State 68
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -31,
+        // State 69
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -34,
+        // State 70
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -32,
+        // State 71
         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -35,
+        // State 72
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -37,
+        // State 73
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -38,
+        // State 74
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -33,
+        // State 75
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -36,
+        // State 76
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -39,
+        // State 77
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -40,
     ];
     fn __action(state: i8, integer: usize) -> i8 {
         __ACTION[(state as usize) * 23 + integer]
@@ -192,11 +217,11 @@ mod __parse__Program {
         // State 5
         0,
         // State 6
-        -46,
+        -51,
         // State 7
         0,
         // State 8
-        -47,
+        -52,
         // State 9
         0,
         // State 10
@@ -234,13 +259,13 @@ mod __parse__Program {
         // State 26
         0,
         // State 27
-        -55,
+        0,
         // State 28
         0,
         // State 29
         0,
         // State 30
-        -52,
+        0,
         // State 31
         0,
         // State 32
@@ -248,13 +273,13 @@ mod __parse__Program {
         // State 33
         0,
         // State 34
-        0,
+        -60,
         // State 35
         0,
         // State 36
         0,
         // State 37
-        0,
+        -57,
         // State 38
         0,
         // State 39
@@ -262,7 +287,7 @@ mod __parse__Program {
         // State 40
         0,
         // State 41
-        -41,
+        0,
         // State 42
         0,
         // State 43
@@ -274,96 +299,125 @@ mod __parse__Program {
         // State 46
         0,
         // State 47
-        -42,
+        0,
         // State 48
-        -36,
+        -46,
         // State 49
-        -38,
+        0,
         // State 50
-        -37,
+        0,
         // State 51
-        -26,
+        0,
         // State 52
-        -27,
+        0,
         // State 53
         0,
         // State 54
-        0,
+        -47,
         // State 55
-        0,
+        -41,
         // State 56
-        0,
+        -43,
         // State 57
-        0,
+        -42,
         // State 58
-        -24,
+        -28,
         // State 59
-        -25,
+        -29,
         // State 60
-        -28,
+        0,
         // State 61
-        -30,
+        0,
         // State 62
-        -29,
+        0,
         // State 63
-        -31,
+        0,
         // State 64
-        -33,
+        0,
         // State 65
-        -34,
+        -30,
         // State 66
-        -32,
+        -26,
         // State 67
+        -27,
+        // State 68
+        -31,
+        // State 69
+        -34,
+        // State 70
+        -32,
+        // State 71
         -35,
+        // State 72
+        -37,
+        // State 73
+        -38,
+        // State 74
+        -33,
+        // State 75
+        -36,
+        // State 76
+        -39,
+        // State 77
+        -40,
     ];
     fn __goto(state: i8, nt: usize) -> i8 {
         match nt {
             2 => 5,
             5 => 2,
-            6 => 10,
-            7 => 35,
-            8 => match state {
-                3 => 34,
-                _ => 28,
-            },
-            9 => 48,
-            10 => 49,
-            11 => match state {
-                8 => 47,
-                _ => 41,
+            8 => 10,
+            9 => 42,
+            10 => match state {
+                3 => 41,
+                _ => 35,
             },
-            13 => 8,
-            14 => match state {
-                17 => 22,
-                22 => 25,
-                7 => 43,
-                _ => 21,
+            11 => 55,
+            12 => 56,
+            13 => match state {
+                8 => 54,
+                _ => 48,
             },
-            15 => 27,
+            15 => 8,
             16 => match state {
-                5 => 38,
-                _ => 36,
+                15 => 22,
+                16 => 23,
+                17 => 24,
+                20 => 26,
+                22 => 28,
+                24 => 29,
+                7 => 50,
+                _ => 20,
             },
+            17 => 34,
             18 => match state {
+                5 => 45,
+                _ => 43,
+            },
+            20 => match state {
                 10 => 18,
                 11 => 19,
-                13 => 20,
-                19 => 23,
-                20 => 24,
-                24 => 26,
-                2 => 31,
-                4..=5 => 37,
-                6 | 8 => 42,
-                9 => 50,
-                12 => 60,
-                14 => 61,
-                15 => 62,
-                18 => 63,
-                21 => 64,
-                23 => 65,
-                25 => 66,
-                26 => 67,
-                _ => 29,
+                13 => 21,
+                19 => 25,
+                21 => 27,
+                26 => 30,
+                27 => 31,
+                30 => 32,
+                32 => 33,
+                2 => 38,
+                4..=5 => 44,
+                6 | 8 => 49,
+                9 => 57,
+                12 => 68,
+                14 => 69,
+                15 => 70,
+                18 => 71,
+                23 => 72,
+                25 => 73,
+                28 => 74,
+                29 => 75,
+                31 => 76,
+                33 => 77,
+                _ => 36,
             },
             _ => 0,
         }
@@ -406,6 +460,7 @@ mod __parse__Program {
     pub struct __StateMachine<'input>
     where 
     {
+        file_id: usize,
         input: &'input str,
         __phantom: ::std::marker::PhantomData<(&'input ())>,
     }
@@ -413,7 +468,7 @@ mod __parse__Program {
     where 
     {
         type Location = usize;
-        type Error = &'static str;
+        type Error = (usize, &'static str, usize);
         type Token = Token<'input>;
         type TokenIndex = usize;
         type Symbol = __Symbol<'input>;
@@ -487,6 +542,7 @@ mod __parse__Program {
             symbols: &mut Vec<__state_machine::SymbolTriple<Self>>,
         ) -> Option<__state_machine::ParseResult<Self>> {
             __reduce(
+                self.file_id,
                 self.input,
                 action,
                 start_location,
@@ -569,12 +625,14 @@ mod __parse__Program {
             'input,
         >(
             &self,
+            file_id: usize,
             input: &'input str,
-        ) -> Result<Netlist<'input>, __lalrpop_util::ParseError<usize, Token<'input>, &'static str>>
+        ) -> Result<Netlist<'input>, __lalrpop_util::ParseError<usize, Token<'input>, (usize, &'static str, usize)>>
         {
             let mut __tokens = self.builder.matcher(input);
             __state_machine::Parser::drive(
                 __StateMachine {
+                    file_id,
                     input,
                     __phantom: ::std::marker::PhantomData::<(&())>,
                 },
@@ -585,183 +643,242 @@ mod __parse__Program {
     pub(crate) fn __reduce<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __action: i8,
         __lookahead_start: Option<&usize>,
         __states: &mut ::std::vec::Vec<i8>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
-    ) -> Option<Result<Netlist<'input>,__lalrpop_util::ParseError<usize, Token<'input>, &'static str>>>
+    ) -> Option<Result<Netlist<'input>,__lalrpop_util::ParseError<usize, Token<'input>, (usize, &'static str, usize)>>>
     {
         let (__pop_states, __nonterminal) = match __action {
             0 => {
-                __reduce0(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce0(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             1 => {
-                __reduce1(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce1(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             2 => {
-                __reduce2(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce2(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             3 => {
-                __reduce3(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce3(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             4 => {
-                __reduce4(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce4(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             5 => {
-                __reduce5(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce5(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             6 => {
-                __reduce6(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce6(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             7 => {
-                __reduce7(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce7(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             8 => {
-                __reduce8(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce8(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             9 => {
-                __reduce9(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce9(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             10 => {
-                __reduce10(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce10(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             11 => {
-                __reduce11(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce11(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             12 => {
-                __reduce12(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce12(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             13 => {
-                __reduce13(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce13(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             14 => {
-                __reduce14(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce14(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             15 => {
-                __reduce15(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce15(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             16 => {
-                __reduce16(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce16(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             17 => {
-                __reduce17(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce17(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             18 => {
-                __reduce18(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce18(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             19 => {
-                __reduce19(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce19(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             20 => {
-                __reduce20(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce20(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             21 => {
-                __reduce21(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce21(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             22 => {
-                __reduce22(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce22(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             23 => {
-                __reduce23(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce23(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             24 => {
-                __reduce24(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce24(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             25 => {
-                __reduce25(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce25(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             26 => {
-                __reduce26(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce26(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             27 => {
-                __reduce27(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce27(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             28 => {
-                __reduce28(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce28(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             29 => {
-                __reduce29(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                // Const = r#"[0-9]+"# => ActionFn(66);
+                let __sym0 = __pop_Variant0(__symbols);
+                let __start = __sym0.0.clone();
+                let __end = __sym0.2.clone();
+                let __nt = match super::__action66::<>(file_id, input, __sym0) {
+                    Ok(v) => v,
+                    Err(e) => return Some(Err(e)),
+                };
+                __symbols.push((__start, __Symbol::Variant9(__nt), __end));
+                (1, 11)
             }
             30 => {
-                __reduce30(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce30(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             31 => {
-                __reduce31(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce31(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             32 => {
-                __reduce32(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce32(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             33 => {
-                __reduce33(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce33(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             34 => {
-                __reduce34(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce34(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             35 => {
-                __reduce35(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                // Expr = "SLICE", Num, Num, Var => ActionFn(67);
+                assert!(__symbols.len() >= 4);
+                let __sym3 = __pop_Variant3(__symbols);
+                let __sym2 = __pop_Variant5(__symbols);
+                let __sym1 = __pop_Variant5(__symbols);
+                let __sym0 = __pop_Variant0(__symbols);
+                let __start = __sym0.0.clone();
+                let __end = __sym3.2.clone();
+                let __nt = match super::__action67::<>(file_id, input, __sym0, __sym1, __sym2, __sym3) {
+                    Ok(v) => v,
+                    Err(e) => return Some(Err(e)),
+                };
+                __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+                (4, 12)
             }
             36 => {
-                __reduce36(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                // Expr = "SELECT", Num, Var => ActionFn(68);
+                assert!(__symbols.len() >= 3);
+                let __sym2 = __pop_Variant3(__symbols);
+                let __sym1 = __pop_Variant5(__symbols);
+                let __sym0 = __pop_Variant0(__symbols);
+                let __start = __sym0.0.clone();
+                let __end = __sym2.2.clone();
+                let __nt = match super::__action68::<>(file_id, input, __sym0, __sym1, __sym2) {
+                    Ok(v) => v,
+                    Err(e) => return Some(Err(e)),
+                };
+                __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+                (3, 12)
             }
             37 => {
-                __reduce37(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce37(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             38 => {
-                __reduce38(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce38(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             39 => {
-                __reduce39(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce39(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             40 => {
-                __reduce40(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce40(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             41 => {
-                __reduce41(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce41(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             42 => {
-                __reduce42(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce42(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             43 => {
-                __reduce43(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce43(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             44 => {
-                __reduce44(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce44(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             45 => {
-                __reduce45(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce45(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             46 => {
-                __reduce46(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce46(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             47 => {
-                __reduce47(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                // Num = r#"[0-9]+"# => ActionFn(69);
+                let __sym0 = __pop_Variant0(__symbols);
+                let __start = __sym0.0.clone();
+                let __end = __sym0.2.clone();
+                let __nt = match super::__action69::<>(file_id, input, __sym0) {
+                    Ok(v) => v,
+                    Err(e) => return Some(Err(e)),
+                };
+                __symbols.push((__start, __Symbol::Variant5(__nt), __end));
+                (1, 16)
             }
             48 => {
-                __reduce48(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce48(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             49 => {
-                __reduce49(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce49(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             50 => {
-                __reduce50(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce50(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             51 => {
-                __reduce51(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce51(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             52 => {
-                __reduce52(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce52(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             53 => {
-                __reduce53(input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+                __reduce53(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
             }
             54 => {
+                __reduce54(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+            }
+            55 => {
+                __reduce55(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+            }
+            56 => {
+                __reduce56(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+            }
+            57 => {
+                __reduce57(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+            }
+            58 => {
+                __reduce58(file_id, input, __lookahead_start, __symbols, ::std::marker::PhantomData::<(&())>)
+            }
+            59 => {
                 // __Program = Program => ActionFn(0);
-                let __sym0 = __pop_Variant12(__symbols);
+                let __sym0 = __pop_Variant13(__symbols);
                 let __start = __sym0.0.clone();
                 let __end = __sym0.2.clone();
-                let __nt = super::__action0::<>(input, __sym0);
+                let __nt = super::__action0::<>(file_id, input, __sym0);
                 return Some(Ok(__nt));
             }
             _ => panic!("invalid action code {}", __action)
@@ -777,14 +894,14 @@ mod __parse__Program {
     fn __symbol_type_mismatch() -> ! {
         panic!("symbol type mismatch")
     }
-    fn __pop_Variant9<
+    fn __pop_Variant11<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, (&'input str, Expr<'input>), usize)
+    ) -> (usize, (Var<'input>, Expr<'input>), usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant9(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant11(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
@@ -792,54 +909,54 @@ mod __parse__Program {
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, (&'input str, usize), usize)
+    ) -> (usize, (Var<'input>, usize), usize)
      {
         match __symbols.pop() {
             Some((__l, __Symbol::Variant1(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant4<
+    fn __pop_Variant6<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
     ) -> (usize, BiOp, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant4(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant6(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant8<
+    fn __pop_Variant10<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
     ) -> (usize, Expr<'input>, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant8(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant10(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant12<
+    fn __pop_Variant13<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
     ) -> (usize, Netlist<'input>, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant12(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant13(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant5<
+    fn __pop_Variant3<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, Vec<(&'input str, usize)>, usize)
+    ) -> (usize, Var<'input>, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant5(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant3(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
@@ -847,43 +964,43 @@ mod __parse__Program {
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, Vec<bool>, usize)
+    ) -> (usize, Vec<(Var<'input>, usize)>, usize)
      {
         match __symbols.pop() {
             Some((__l, __Symbol::Variant7(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant6<
+    fn __pop_Variant8<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, Vec<&'input str>, usize)
+    ) -> (usize, Vec<Var<'input>>, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant6(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant8(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant11<
+    fn __pop_Variant9<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, usize, usize)
+    ) -> (usize, Vec<bool>, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant11(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant9(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant13<
+    fn __pop_Variant5<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, ::std::option::Option<(&'input str, usize)>, usize)
+    ) -> (usize, usize, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant13(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant5(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
@@ -891,21 +1008,32 @@ mod __parse__Program {
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, ::std::option::Option<&'input str>, usize)
+    ) -> (usize, ::std::option::Option<(Var<'input>, usize)>, usize)
      {
         match __symbols.pop() {
             Some((__l, __Symbol::Variant14(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant10<
+    fn __pop_Variant15<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, ::std::vec::Vec<(&'input str, Expr<'input>)>, usize)
+    ) -> (usize, ::std::option::Option<Var<'input>>, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant10(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant15(__v), __r)) => (__l, __v, __r),
+            _ => __symbol_type_mismatch()
+        }
+    }
+    fn __pop_Variant12<
+      'input,
+    >(
+        __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
+    ) -> (usize, ::std::vec::Vec<(Var<'input>, Expr<'input>)>, usize)
+     {
+        match __symbols.pop() {
+            Some((__l, __Symbol::Variant12(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
@@ -913,21 +1041,21 @@ mod __parse__Program {
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, ::std::vec::Vec<(&'input str, usize)>, usize)
+    ) -> (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize)
      {
         match __symbols.pop() {
             Some((__l, __Symbol::Variant2(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
-    fn __pop_Variant3<
+    fn __pop_Variant4<
       'input,
     >(
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>
-    ) -> (usize, ::std::vec::Vec<&'input str>, usize)
+    ) -> (usize, ::std::vec::Vec<Var<'input>>, usize)
      {
         match __symbols.pop() {
-            Some((__l, __Symbol::Variant3(__v), __r)) => (__l, __v, __r),
+            Some((__l, __Symbol::Variant4(__v), __r)) => (__l, __v, __r),
             _ => __symbol_type_mismatch()
         }
     }
@@ -945,678 +1073,754 @@ mod __parse__Program {
     pub(crate) fn __reduce0<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<SizedVar> ",") = SizedVar, "," => ActionFn(41);
+        // (<SizedVar> ",") = SizedVar, "," => ActionFn(46);
         assert!(__symbols.len() >= 2);
         let __sym1 = __pop_Variant0(__symbols);
         let __sym0 = __pop_Variant1(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action41::<>(input, __sym0, __sym1);
+        let __nt = super::__action46::<>(file_id, input, __sym0, __sym1);
         __symbols.push((__start, __Symbol::Variant1(__nt), __end));
         (2, 0)
     }
     pub(crate) fn __reduce1<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<SizedVar> ",")* =  => ActionFn(39);
+        // (<SizedVar> ",")* =  => ActionFn(44);
         let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
         let __end = __start.clone();
-        let __nt = super::__action39::<>(input, &__start, &__end);
+        let __nt = super::__action44::<>(file_id, input, &__start, &__end);
         __symbols.push((__start, __Symbol::Variant2(__nt), __end));
         (0, 1)
     }
     pub(crate) fn __reduce2<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<SizedVar> ",")* = (<SizedVar> ",")+ => ActionFn(40);
+        // (<SizedVar> ",")* = (<SizedVar> ",")+ => ActionFn(45);
         let __sym0 = __pop_Variant2(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action40::<>(input, __sym0);
+        let __nt = super::__action45::<>(file_id, input, __sym0);
         __symbols.push((__start, __Symbol::Variant2(__nt), __end));
         (1, 1)
     }
     pub(crate) fn __reduce3<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<SizedVar> ",")+ = SizedVar, "," => ActionFn(48);
+        // (<SizedVar> ",")+ = SizedVar, "," => ActionFn(53);
         assert!(__symbols.len() >= 2);
         let __sym1 = __pop_Variant0(__symbols);
         let __sym0 = __pop_Variant1(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action48::<>(input, __sym0, __sym1);
+        let __nt = super::__action53::<>(file_id, input, __sym0, __sym1);
         __symbols.push((__start, __Symbol::Variant2(__nt), __end));
         (2, 2)
     }
     pub(crate) fn __reduce4<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<SizedVar> ",")+ = (<SizedVar> ",")+, SizedVar, "," => ActionFn(49);
+        // (<SizedVar> ",")+ = (<SizedVar> ",")+, SizedVar, "," => ActionFn(54);
         assert!(__symbols.len() >= 3);
         let __sym2 = __pop_Variant0(__symbols);
         let __sym1 = __pop_Variant1(__symbols);
         let __sym0 = __pop_Variant2(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym2.2.clone();
-        let __nt = super::__action49::<>(input, __sym0, __sym1, __sym2);
+        let __nt = super::__action54::<>(file_id, input, __sym0, __sym1, __sym2);
         __symbols.push((__start, __Symbol::Variant2(__nt), __end));
         (3, 2)
     }
     pub(crate) fn __reduce5<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<Var> ",") = Var, "," => ActionFn(36);
+        // (<Var> ",") = Var, "," => ActionFn(41);
         assert!(__symbols.len() >= 2);
         let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant0(__symbols);
+        let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action36::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant0(__nt), __end));
+        let __nt = super::__action41::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant3(__nt), __end));
         (2, 3)
     }
     pub(crate) fn __reduce6<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<Var> ",")* =  => ActionFn(34);
+        // (<Var> ",")* =  => ActionFn(39);
         let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
         let __end = __start.clone();
-        let __nt = super::__action34::<>(input, &__start, &__end);
-        __symbols.push((__start, __Symbol::Variant3(__nt), __end));
+        let __nt = super::__action39::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
         (0, 4)
     }
     pub(crate) fn __reduce7<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<Var> ",")* = (<Var> ",")+ => ActionFn(35);
-        let __sym0 = __pop_Variant3(__symbols);
+        // (<Var> ",")* = (<Var> ",")+ => ActionFn(40);
+        let __sym0 = __pop_Variant4(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action35::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant3(__nt), __end));
+        let __nt = super::__action40::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
         (1, 4)
     }
     pub(crate) fn __reduce8<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<Var> ",")+ = Var, "," => ActionFn(52);
+        // (<Var> ",")+ = Var, "," => ActionFn(57);
         assert!(__symbols.len() >= 2);
         let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant0(__symbols);
+        let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action52::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant3(__nt), __end));
+        let __nt = super::__action57::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
         (2, 5)
     }
     pub(crate) fn __reduce9<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // (<Var> ",")+ = (<Var> ",")+, Var, "," => ActionFn(53);
+        // (<Var> ",")+ = (<Var> ",")+, Var, "," => ActionFn(58);
         assert!(__symbols.len() >= 3);
         let __sym2 = __pop_Variant0(__symbols);
-        let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant3(__symbols);
+        let __sym1 = __pop_Variant3(__symbols);
+        let __sym0 = __pop_Variant4(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym2.2.clone();
-        let __nt = super::__action53::<>(input, __sym0, __sym1, __sym2);
-        __symbols.push((__start, __Symbol::Variant3(__nt), __end));
+        let __nt = super::__action58::<>(file_id, input, __sym0, __sym1, __sym2);
+        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
         (3, 5)
     }
     pub(crate) fn __reduce10<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // BiOp = "AND" => ActionFn(19);
-        let __sym0 = __pop_Variant0(__symbols);
-        let __start = __sym0.0.clone();
-        let __end = __sym0.2.clone();
-        let __nt = super::__action19::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
-        (1, 6)
+        // @L =  => ActionFn(36);
+        let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
+        let __end = __start.clone();
+        let __nt = super::__action36::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
+        (0, 6)
     }
     pub(crate) fn __reduce11<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // BiOp = "OR" => ActionFn(20);
-        let __sym0 = __pop_Variant0(__symbols);
-        let __start = __sym0.0.clone();
-        let __end = __sym0.2.clone();
-        let __nt = super::__action20::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
-        (1, 6)
+        // @R =  => ActionFn(35);
+        let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
+        let __end = __start.clone();
+        let __nt = super::__action35::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
+        (0, 7)
     }
     pub(crate) fn __reduce12<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // BiOp = "XOR" => ActionFn(21);
+        // BiOp = "AND" => ActionFn(21);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action21::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
-        (1, 6)
+        let __nt = super::__action21::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
+        (1, 8)
     }
     pub(crate) fn __reduce13<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // BiOp = "NAND" => ActionFn(22);
+        // BiOp = "OR" => ActionFn(22);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action22::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
-        (1, 6)
+        let __nt = super::__action22::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
+        (1, 8)
     }
     pub(crate) fn __reduce14<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // BiOp = "CONCAT" => ActionFn(23);
+        // BiOp = "XOR" => ActionFn(23);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action23::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant4(__nt), __end));
-        (1, 6)
+        let __nt = super::__action23::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
+        (1, 8)
     }
     pub(crate) fn __reduce15<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<SizedVar> = SizedVar => ActionFn(58);
-        let __sym0 = __pop_Variant1(__symbols);
+        // BiOp = "NAND" => ActionFn(24);
+        let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action58::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
-        (1, 7)
+        let __nt = super::__action24::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
+        (1, 8)
     }
     pub(crate) fn __reduce16<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<SizedVar> =  => ActionFn(59);
-        let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
-        let __end = __start.clone();
-        let __nt = super::__action59::<>(input, &__start, &__end);
-        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
-        (0, 7)
+        // BiOp = "CONCAT" => ActionFn(25);
+        let __sym0 = __pop_Variant0(__symbols);
+        let __start = __sym0.0.clone();
+        let __end = __sym0.2.clone();
+        let __nt = super::__action25::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
+        (1, 8)
     }
     pub(crate) fn __reduce17<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<SizedVar> = (<SizedVar> ",")+, SizedVar => ActionFn(60);
-        assert!(__symbols.len() >= 2);
-        let __sym1 = __pop_Variant1(__symbols);
-        let __sym0 = __pop_Variant2(__symbols);
+        // Comma<SizedVar> = SizedVar => ActionFn(73);
+        let __sym0 = __pop_Variant1(__symbols);
         let __start = __sym0.0.clone();
-        let __end = __sym1.2.clone();
-        let __nt = super::__action60::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
-        (2, 7)
+        let __end = __sym0.2.clone();
+        let __nt = super::__action73::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
+        (1, 9)
     }
     pub(crate) fn __reduce18<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<SizedVar> = (<SizedVar> ",")+ => ActionFn(61);
-        let __sym0 = __pop_Variant2(__symbols);
-        let __start = __sym0.0.clone();
-        let __end = __sym0.2.clone();
-        let __nt = super::__action61::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
-        (1, 7)
+        // Comma<SizedVar> =  => ActionFn(74);
+        let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
+        let __end = __start.clone();
+        let __nt = super::__action74::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
+        (0, 9)
     }
     pub(crate) fn __reduce19<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<Var> = Var => ActionFn(62);
-        let __sym0 = __pop_Variant0(__symbols);
+        // Comma<SizedVar> = (<SizedVar> ",")+, SizedVar => ActionFn(75);
+        assert!(__symbols.len() >= 2);
+        let __sym1 = __pop_Variant1(__symbols);
+        let __sym0 = __pop_Variant2(__symbols);
         let __start = __sym0.0.clone();
-        let __end = __sym0.2.clone();
-        let __nt = super::__action62::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
-        (1, 8)
+        let __end = __sym1.2.clone();
+        let __nt = super::__action75::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
+        (2, 9)
     }
     pub(crate) fn __reduce20<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<Var> =  => ActionFn(63);
-        let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
-        let __end = __start.clone();
-        let __nt = super::__action63::<>(input, &__start, &__end);
-        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
-        (0, 8)
+        // Comma<SizedVar> = (<SizedVar> ",")+ => ActionFn(76);
+        let __sym0 = __pop_Variant2(__symbols);
+        let __start = __sym0.0.clone();
+        let __end = __sym0.2.clone();
+        let __nt = super::__action76::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
+        (1, 9)
     }
     pub(crate) fn __reduce21<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<Var> = (<Var> ",")+, Var => ActionFn(64);
-        assert!(__symbols.len() >= 2);
-        let __sym1 = __pop_Variant0(__symbols);
+        // Comma<Var> = Var => ActionFn(77);
         let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
-        let __end = __sym1.2.clone();
-        let __nt = super::__action64::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
-        (2, 8)
+        let __end = __sym0.2.clone();
+        let __nt = super::__action77::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
+        (1, 10)
     }
     pub(crate) fn __reduce22<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Comma<Var> = (<Var> ",")+ => ActionFn(65);
-        let __sym0 = __pop_Variant3(__symbols);
-        let __start = __sym0.0.clone();
-        let __end = __sym0.2.clone();
-        let __nt = super::__action65::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant6(__nt), __end));
-        (1, 8)
+        // Comma<Var> =  => ActionFn(78);
+        let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
+        let __end = __start.clone();
+        let __nt = super::__action78::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
+        (0, 10)
     }
     pub(crate) fn __reduce23<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Const = Const, "0" => ActionFn(24);
+        // Comma<Var> = (<Var> ",")+, Var => ActionFn(79);
         assert!(__symbols.len() >= 2);
-        let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant7(__symbols);
+        let __sym1 = __pop_Variant3(__symbols);
+        let __sym0 = __pop_Variant4(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action24::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
-        (2, 9)
+        let __nt = super::__action79::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
+        (2, 10)
     }
     pub(crate) fn __reduce24<
         'input,
     >(
+        file_id: usize,
+        input: &'input str,
+        __lookahead_start: Option<&usize>,
+        __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
+        _: ::std::marker::PhantomData<(&'input ())>,
+    ) -> (usize, usize)
+    {
+        // Comma<Var> = (<Var> ",")+ => ActionFn(80);
+        let __sym0 = __pop_Variant4(__symbols);
+        let __start = __sym0.0.clone();
+        let __end = __sym0.2.clone();
+        let __nt = super::__action80::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
+        (1, 10)
+    }
+    pub(crate) fn __reduce25<
+        'input,
+    >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Const = Const, "1" => ActionFn(25);
+        // Const = Const, "0" => ActionFn(26);
         assert!(__symbols.len() >= 2);
         let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant7(__symbols);
+        let __sym0 = __pop_Variant9(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action25::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
-        (2, 9)
+        let __nt = super::__action26::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant9(__nt), __end));
+        (2, 11)
     }
-    pub(crate) fn __reduce25<
+    pub(crate) fn __reduce26<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Const = "0" => ActionFn(26);
-        let __sym0 = __pop_Variant0(__symbols);
+        // Const = Const, "1" => ActionFn(27);
+        assert!(__symbols.len() >= 2);
+        let __sym1 = __pop_Variant0(__symbols);
+        let __sym0 = __pop_Variant9(__symbols);
         let __start = __sym0.0.clone();
-        let __end = __sym0.2.clone();
-        let __nt = super::__action26::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
-        (1, 9)
+        let __end = __sym1.2.clone();
+        let __nt = super::__action27::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant9(__nt), __end));
+        (2, 11)
     }
-    pub(crate) fn __reduce26<
+    pub(crate) fn __reduce27<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Const = "1" => ActionFn(27);
+        // Const = "0" => ActionFn(28);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action27::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant7(__nt), __end));
-        (1, 9)
+        let __nt = super::__action28::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant9(__nt), __end));
+        (1, 11)
     }
-    pub(crate) fn __reduce27<
+    pub(crate) fn __reduce28<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = "NOT", Var => ActionFn(9);
-        assert!(__symbols.len() >= 2);
-        let __sym1 = __pop_Variant0(__symbols);
+        // Const = "1" => ActionFn(29);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
-        let __end = __sym1.2.clone();
-        let __nt = super::__action9::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (2, 10)
+        let __end = __sym0.2.clone();
+        let __nt = super::__action29::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant9(__nt), __end));
+        (1, 11)
     }
-    pub(crate) fn __reduce28<
+    pub(crate) fn __reduce30<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = "ROM", Var => ActionFn(10);
+        // Expr = "NOT", Var => ActionFn(9);
         assert!(__symbols.len() >= 2);
-        let __sym1 = __pop_Variant0(__symbols);
+        let __sym1 = __pop_Variant3(__symbols);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action10::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (2, 10)
+        let __nt = super::__action9::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (2, 12)
     }
-    pub(crate) fn __reduce29<
+    pub(crate) fn __reduce31<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = "REG", Var => ActionFn(11);
+        // Expr = "ROM", Var => ActionFn(10);
         assert!(__symbols.len() >= 2);
-        let __sym1 = __pop_Variant0(__symbols);
+        let __sym1 = __pop_Variant3(__symbols);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action11::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (2, 10)
+        let __nt = super::__action10::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (2, 12)
     }
-    pub(crate) fn __reduce30<
+    pub(crate) fn __reduce32<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = BiOp, Var, Var => ActionFn(12);
-        assert!(__symbols.len() >= 3);
-        let __sym2 = __pop_Variant0(__symbols);
-        let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant4(__symbols);
+        // Expr = "ROM", Num, Num, Var => ActionFn(11);
+        assert!(__symbols.len() >= 4);
+        let __sym3 = __pop_Variant3(__symbols);
+        let __sym2 = __pop_Variant5(__symbols);
+        let __sym1 = __pop_Variant5(__symbols);
+        let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
-        let __end = __sym2.2.clone();
-        let __nt = super::__action12::<>(input, __sym0, __sym1, __sym2);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (3, 10)
+        let __end = __sym3.2.clone();
+        let __nt = super::__action11::<>(file_id, input, __sym0, __sym1, __sym2, __sym3);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (4, 12)
     }
-    pub(crate) fn __reduce31<
+    pub(crate) fn __reduce33<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = "SLICE", Num, Num, Var => ActionFn(13);
-        assert!(__symbols.len() >= 4);
-        let __sym3 = __pop_Variant0(__symbols);
-        let __sym2 = __pop_Variant11(__symbols);
-        let __sym1 = __pop_Variant11(__symbols);
+        // Expr = "REG", Var => ActionFn(12);
+        assert!(__symbols.len() >= 2);
+        let __sym1 = __pop_Variant3(__symbols);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
-        let __end = __sym3.2.clone();
-        let __nt = super::__action13::<>(input, __sym0, __sym1, __sym2, __sym3);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (4, 10)
+        let __end = __sym1.2.clone();
+        let __nt = super::__action12::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (2, 12)
     }
-    pub(crate) fn __reduce32<
+    pub(crate) fn __reduce34<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = "SELECT", Num, Var => ActionFn(14);
+        // Expr = BiOp, Var, Var => ActionFn(13);
         assert!(__symbols.len() >= 3);
-        let __sym2 = __pop_Variant0(__symbols);
-        let __sym1 = __pop_Variant11(__symbols);
-        let __sym0 = __pop_Variant0(__symbols);
+        let __sym2 = __pop_Variant3(__symbols);
+        let __sym1 = __pop_Variant3(__symbols);
+        let __sym0 = __pop_Variant6(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym2.2.clone();
-        let __nt = super::__action14::<>(input, __sym0, __sym1, __sym2);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (3, 10)
+        let __nt = super::__action13::<>(file_id, input, __sym0, __sym1, __sym2);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (3, 12)
     }
-    pub(crate) fn __reduce33<
+    pub(crate) fn __reduce37<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = "MUX", Var, Var, Var => ActionFn(15);
+        // Expr = "MUX", Var, Var, Var => ActionFn(16);
         assert!(__symbols.len() >= 4);
-        let __sym3 = __pop_Variant0(__symbols);
-        let __sym2 = __pop_Variant0(__symbols);
-        let __sym1 = __pop_Variant0(__symbols);
+        let __sym3 = __pop_Variant3(__symbols);
+        let __sym2 = __pop_Variant3(__symbols);
+        let __sym1 = __pop_Variant3(__symbols);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym3.2.clone();
-        let __nt = super::__action15::<>(input, __sym0, __sym1, __sym2, __sym3);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (4, 10)
+        let __nt = super::__action16::<>(file_id, input, __sym0, __sym1, __sym2, __sym3);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (4, 12)
     }
-    pub(crate) fn __reduce34<
+    pub(crate) fn __reduce38<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = "RAM", Var, Var, Var, Var => ActionFn(16);
+        // Expr = "RAM", Var, Var, Var, Var => ActionFn(17);
         assert!(__symbols.len() >= 5);
-        let __sym4 = __pop_Variant0(__symbols);
-        let __sym3 = __pop_Variant0(__symbols);
-        let __sym2 = __pop_Variant0(__symbols);
-        let __sym1 = __pop_Variant0(__symbols);
+        let __sym4 = __pop_Variant3(__symbols);
+        let __sym3 = __pop_Variant3(__symbols);
+        let __sym2 = __pop_Variant3(__symbols);
+        let __sym1 = __pop_Variant3(__symbols);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym4.2.clone();
-        let __nt = super::__action16::<>(input, __sym0, __sym1, __sym2, __sym3, __sym4);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (5, 10)
+        let __nt = super::__action17::<>(file_id, input, __sym0, __sym1, __sym2, __sym3, __sym4);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (5, 12)
+    }
+    pub(crate) fn __reduce39<
+        'input,
+    >(
+        file_id: usize,
+        input: &'input str,
+        __lookahead_start: Option<&usize>,
+        __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
+        _: ::std::marker::PhantomData<(&'input ())>,
+    ) -> (usize, usize)
+    {
+        // Expr = "RAM", Num, Num, Var, Var, Var, Var => ActionFn(18);
+        assert!(__symbols.len() >= 7);
+        let __sym6 = __pop_Variant3(__symbols);
+        let __sym5 = __pop_Variant3(__symbols);
+        let __sym4 = __pop_Variant3(__symbols);
+        let __sym3 = __pop_Variant3(__symbols);
+        let __sym2 = __pop_Variant5(__symbols);
+        let __sym1 = __pop_Variant5(__symbols);
+        let __sym0 = __pop_Variant0(__symbols);
+        let __start = __sym0.0.clone();
+        let __end = __sym6.2.clone();
+        let __nt = super::__action18::<>(file_id, input, __sym0, __sym1, __sym2, __sym3, __sym4, __sym5, __sym6);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (7, 12)
     }
-    pub(crate) fn __reduce35<
+    pub(crate) fn __reduce40<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = Const => ActionFn(17);
-        let __sym0 = __pop_Variant7(__symbols);
+        // Expr = Const => ActionFn(19);
+        let __sym0 = __pop_Variant9(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action17::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (1, 10)
+        let __nt = super::__action19::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (1, 12)
     }
-    pub(crate) fn __reduce36<
+    pub(crate) fn __reduce41<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Expr = Var => ActionFn(18);
-        let __sym0 = __pop_Variant0(__symbols);
+        // Expr = Var => ActionFn(20);
+        let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action18::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant8(__nt), __end));
-        (1, 10)
+        let __nt = super::__action20::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
+        (1, 12)
     }
-    pub(crate) fn __reduce37<
+    pub(crate) fn __reduce42<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
@@ -1625,104 +1829,92 @@ mod __parse__Program {
     {
         // Instr = Var, "=", Expr => ActionFn(6);
         assert!(__symbols.len() >= 3);
-        let __sym2 = __pop_Variant8(__symbols);
+        let __sym2 = __pop_Variant10(__symbols);
         let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant0(__symbols);
+        let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym2.2.clone();
-        let __nt = super::__action6::<>(input, __sym0, __sym1, __sym2);
-        __symbols.push((__start, __Symbol::Variant9(__nt), __end));
-        (3, 11)
+        let __nt = super::__action6::<>(file_id, input, __sym0, __sym1, __sym2);
+        __symbols.push((__start, __Symbol::Variant11(__nt), __end));
+        (3, 13)
     }
-    pub(crate) fn __reduce38<
+    pub(crate) fn __reduce43<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Instr* =  => ActionFn(28);
+        // Instr* =  => ActionFn(31);
         let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
         let __end = __start.clone();
-        let __nt = super::__action28::<>(input, &__start, &__end);
-        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
-        (0, 12)
+        let __nt = super::__action31::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant12(__nt), __end));
+        (0, 14)
     }
-    pub(crate) fn __reduce39<
+    pub(crate) fn __reduce44<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Instr* = Instr+ => ActionFn(29);
-        let __sym0 = __pop_Variant10(__symbols);
+        // Instr* = Instr+ => ActionFn(32);
+        let __sym0 = __pop_Variant12(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action29::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
-        (1, 12)
+        let __nt = super::__action32::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant12(__nt), __end));
+        (1, 14)
     }
-    pub(crate) fn __reduce40<
+    pub(crate) fn __reduce45<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Instr+ = Instr => ActionFn(42);
-        let __sym0 = __pop_Variant9(__symbols);
+        // Instr+ = Instr => ActionFn(47);
+        let __sym0 = __pop_Variant11(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action42::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
-        (1, 13)
+        let __nt = super::__action47::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant12(__nt), __end));
+        (1, 15)
     }
-    pub(crate) fn __reduce41<
+    pub(crate) fn __reduce46<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Instr+ = Instr+, Instr => ActionFn(43);
+        // Instr+ = Instr+, Instr => ActionFn(48);
         assert!(__symbols.len() >= 2);
-        let __sym1 = __pop_Variant9(__symbols);
-        let __sym0 = __pop_Variant10(__symbols);
+        let __sym1 = __pop_Variant11(__symbols);
+        let __sym0 = __pop_Variant12(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym1.2.clone();
-        let __nt = super::__action43::<>(input, __sym0, __sym1);
-        __symbols.push((__start, __Symbol::Variant10(__nt), __end));
-        (2, 13)
-    }
-    pub(crate) fn __reduce42<
-        'input,
-    >(
-        input: &'input str,
-        __lookahead_start: Option<&usize>,
-        __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
-        _: ::std::marker::PhantomData<(&'input ())>,
-    ) -> (usize, usize)
-    {
-        // Num = r#"[0-9]+"# => ActionFn(2);
-        let __sym0 = __pop_Variant0(__symbols);
-        let __start = __sym0.0.clone();
-        let __end = __sym0.2.clone();
-        let __nt = super::__action2::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant11(__nt), __end));
-        (1, 14)
+        let __nt = super::__action48::<>(file_id, input, __sym0, __sym1);
+        __symbols.push((__start, __Symbol::Variant12(__nt), __end));
+        (2, 15)
     }
-    pub(crate) fn __reduce43<
+    pub(crate) fn __reduce48<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
@@ -1733,13 +1925,14 @@ mod __parse__Program {
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action3::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant11(__nt), __end));
-        (1, 14)
+        let __nt = super::__action3::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
+        (1, 16)
     }
-    pub(crate) fn __reduce44<
+    pub(crate) fn __reduce49<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
@@ -1750,62 +1943,65 @@ mod __parse__Program {
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action4::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant11(__nt), __end));
-        (1, 14)
+        let __nt = super::__action4::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant5(__nt), __end));
+        (1, 16)
     }
-    pub(crate) fn __reduce45<
+    pub(crate) fn __reduce50<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Program = "INPUT", Comma<Var>, "OUTPUT", Comma<Var>, "VAR", Comma<SizedVar>, "IN" => ActionFn(56);
+        // Program = "INPUT", Comma<Var>, "OUTPUT", Comma<Var>, "VAR", Comma<SizedVar>, "IN" => ActionFn(71);
         assert!(__symbols.len() >= 7);
         let __sym6 = __pop_Variant0(__symbols);
-        let __sym5 = __pop_Variant5(__symbols);
+        let __sym5 = __pop_Variant7(__symbols);
         let __sym4 = __pop_Variant0(__symbols);
-        let __sym3 = __pop_Variant6(__symbols);
+        let __sym3 = __pop_Variant8(__symbols);
         let __sym2 = __pop_Variant0(__symbols);
-        let __sym1 = __pop_Variant6(__symbols);
+        let __sym1 = __pop_Variant8(__symbols);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym6.2.clone();
-        let __nt = super::__action56::<>(input, __sym0, __sym1, __sym2, __sym3, __sym4, __sym5, __sym6);
-        __symbols.push((__start, __Symbol::Variant12(__nt), __end));
-        (7, 15)
+        let __nt = super::__action71::<>(file_id, input, __sym0, __sym1, __sym2, __sym3, __sym4, __sym5, __sym6);
+        __symbols.push((__start, __Symbol::Variant13(__nt), __end));
+        (7, 17)
     }
-    pub(crate) fn __reduce46<
+    pub(crate) fn __reduce51<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Program = "INPUT", Comma<Var>, "OUTPUT", Comma<Var>, "VAR", Comma<SizedVar>, "IN", Instr+ => ActionFn(57);
+        // Program = "INPUT", Comma<Var>, "OUTPUT", Comma<Var>, "VAR", Comma<SizedVar>, "IN", Instr+ => ActionFn(72);
         assert!(__symbols.len() >= 8);
-        let __sym7 = __pop_Variant10(__symbols);
+        let __sym7 = __pop_Variant12(__symbols);
         let __sym6 = __pop_Variant0(__symbols);
-        let __sym5 = __pop_Variant5(__symbols);
+        let __sym5 = __pop_Variant7(__symbols);
         let __sym4 = __pop_Variant0(__symbols);
-        let __sym3 = __pop_Variant6(__symbols);
+        let __sym3 = __pop_Variant8(__symbols);
         let __sym2 = __pop_Variant0(__symbols);
-        let __sym1 = __pop_Variant6(__symbols);
+        let __sym1 = __pop_Variant8(__symbols);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym7.2.clone();
-        let __nt = super::__action57::<>(input, __sym0, __sym1, __sym2, __sym3, __sym4, __sym5, __sym6, __sym7);
-        __symbols.push((__start, __Symbol::Variant12(__nt), __end));
-        (8, 15)
+        let __nt = super::__action72::<>(file_id, input, __sym0, __sym1, __sym2, __sym3, __sym4, __sym5, __sym6, __sym7);
+        __symbols.push((__start, __Symbol::Variant13(__nt), __end));
+        (8, 17)
     }
-    pub(crate) fn __reduce47<
+    pub(crate) fn __reduce52<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
@@ -1813,16 +2009,17 @@ mod __parse__Program {
     ) -> (usize, usize)
     {
         // SizedVar = Var => ActionFn(7);
-        let __sym0 = __pop_Variant0(__symbols);
+        let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action7::<>(input, __sym0);
+        let __nt = super::__action7::<>(file_id, input, __sym0);
         __symbols.push((__start, __Symbol::Variant1(__nt), __end));
-        (1, 16)
+        (1, 18)
     }
-    pub(crate) fn __reduce48<
+    pub(crate) fn __reduce53<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
@@ -1831,97 +2028,102 @@ mod __parse__Program {
     {
         // SizedVar = Var, ":", Num => ActionFn(8);
         assert!(__symbols.len() >= 3);
-        let __sym2 = __pop_Variant11(__symbols);
+        let __sym2 = __pop_Variant5(__symbols);
         let __sym1 = __pop_Variant0(__symbols);
-        let __sym0 = __pop_Variant0(__symbols);
+        let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym2.2.clone();
-        let __nt = super::__action8::<>(input, __sym0, __sym1, __sym2);
+        let __nt = super::__action8::<>(file_id, input, __sym0, __sym1, __sym2);
         __symbols.push((__start, __Symbol::Variant1(__nt), __end));
-        (3, 16)
+        (3, 18)
     }
-    pub(crate) fn __reduce49<
+    pub(crate) fn __reduce54<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // SizedVar? = SizedVar => ActionFn(37);
+        // SizedVar? = SizedVar => ActionFn(42);
         let __sym0 = __pop_Variant1(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action37::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant13(__nt), __end));
-        (1, 17)
+        let __nt = super::__action42::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant14(__nt), __end));
+        (1, 19)
     }
-    pub(crate) fn __reduce50<
+    pub(crate) fn __reduce55<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // SizedVar? =  => ActionFn(38);
+        // SizedVar? =  => ActionFn(43);
         let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
         let __end = __start.clone();
-        let __nt = super::__action38::<>(input, &__start, &__end);
-        __symbols.push((__start, __Symbol::Variant13(__nt), __end));
-        (0, 17)
+        let __nt = super::__action43::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant14(__nt), __end));
+        (0, 19)
     }
-    pub(crate) fn __reduce51<
+    pub(crate) fn __reduce56<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Var = r#"[a-zA-Z_][a-zA-Z_0-9]*"# => ActionFn(1);
+        // Var = r#"[a-zA-Z_][a-zA-Z_0-9]*"# => ActionFn(70);
         let __sym0 = __pop_Variant0(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action1::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant0(__nt), __end));
-        (1, 18)
+        let __nt = super::__action70::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant3(__nt), __end));
+        (1, 20)
     }
-    pub(crate) fn __reduce52<
+    pub(crate) fn __reduce57<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Var? = Var => ActionFn(32);
-        let __sym0 = __pop_Variant0(__symbols);
+        // Var? = Var => ActionFn(37);
+        let __sym0 = __pop_Variant3(__symbols);
         let __start = __sym0.0.clone();
         let __end = __sym0.2.clone();
-        let __nt = super::__action32::<>(input, __sym0);
-        __symbols.push((__start, __Symbol::Variant14(__nt), __end));
-        (1, 19)
+        let __nt = super::__action37::<>(file_id, input, __sym0);
+        __symbols.push((__start, __Symbol::Variant15(__nt), __end));
+        (1, 21)
     }
-    pub(crate) fn __reduce53<
+    pub(crate) fn __reduce58<
         'input,
     >(
+        file_id: usize,
         input: &'input str,
         __lookahead_start: Option<&usize>,
         __symbols: &mut ::std::vec::Vec<(usize,__Symbol<'input>,usize)>,
         _: ::std::marker::PhantomData<(&'input ())>,
     ) -> (usize, usize)
     {
-        // Var? =  => ActionFn(33);
+        // Var? =  => ActionFn(38);
         let __start = __lookahead_start.cloned().or_else(|| __symbols.last().map(|s| s.2.clone())).unwrap_or_default();
         let __end = __start.clone();
-        let __nt = super::__action33::<>(input, &__start, &__end);
-        __symbols.push((__start, __Symbol::Variant14(__nt), __end));
-        (0, 19)
+        let __nt = super::__action38::<>(file_id, input, &__start, &__end);
+        __symbols.push((__start, __Symbol::Variant15(__nt), __end));
+        (0, 21)
     }
 }
 pub use self::__parse__Program::ProgramParser;
@@ -1929,7 +2131,9 @@ pub use self::__parse__Program::ProgramParser;
 mod __intern_token {
     #![allow(unused_imports)]
     use std::str::FromStr;
+    use lalrpop_util::ParseError;
     use crate::frontend::from_netlist::parse_ast::*;
+    use crate::ast::parse_ast::Loc;
     #[allow(unused_extern_crates)]
     extern crate lalrpop_util as __lalrpop_util;
     #[allow(unused_imports)]
@@ -1959,7 +2163,8 @@ mod __intern_token {
             ("^(SLICE)", false),
             ("^(VAR)", false),
             ("^(XOR)", false),
-            (r"^(\s*)", true),
+            ("^([\t-\r \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}]*)", true),
+            ("^(\\#[\0-\t\u{b}-\u{10ffff}]*)", true),
         ];
         __lalrpop_util::lexer::MatcherBuilder::new(__strs.iter().copied()).unwrap()
     }
@@ -1970,6 +2175,7 @@ pub use self::__lalrpop_util::lexer::Token;
 fn __action0<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, Netlist<'input>, usize),
 ) -> Netlist<'input>
@@ -1981,28 +2187,36 @@ fn __action0<
 fn __action1<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, &'input str, usize),
-) -> &'input str
+    (_, l, _): (usize, usize, usize),
+    (_, v, _): (usize, &'input str, usize),
+    (_, r, _): (usize, usize, usize),
+) -> Var<'input>
 {
-    __0
+    Loc::new((file_id, l, r), v)
 }
 
 #[allow(unused_variables)]
 fn __action2<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
+    (_, l, _): (usize, usize, usize),
     (_, s, _): (usize, &'input str, usize),
-) -> usize
+    (_, r, _): (usize, usize, usize),
+) -> Result<usize,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
 {
-    usize::from_str(s).unwrap()
+    usize::from_str(s)
+        .map_err(|_| ParseError::User { error: (l, "this number is too large", r) })
 }
 
 #[allow(unused_variables)]
 fn __action3<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> usize
@@ -2014,6 +2228,7 @@ fn __action3<
 fn __action4<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> usize
@@ -2025,23 +2240,24 @@ fn __action4<
 fn __action5<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, _, _): (usize, &'input str, usize),
-    (_, i, _): (usize, Vec<&'input str>, usize),
+    (_, i, _): (usize, Vec<Var<'input>>, usize),
     (_, _, _): (usize, &'input str, usize),
-    (_, o, _): (usize, Vec<&'input str>, usize),
+    (_, o, _): (usize, Vec<Var<'input>>, usize),
     (_, _, _): (usize, &'input str, usize),
-    (_, v, _): (usize, Vec<(&'input str, usize)>, usize),
+    (_, v, _): (usize, Vec<(Var<'input>, usize)>, usize),
     (_, _, _): (usize, &'input str, usize),
-    (_, instr, _): (usize, ::std::vec::Vec<(&'input str, Expr<'input>)>, usize),
+    (_, instr, _): (usize, ::std::vec::Vec<(Var<'input>, Expr<'input>)>, usize),
 ) -> Netlist<'input>
 {
     {
         Netlist {
-            inputs: i.into_iter().collect(), 
-            outputs: o.into_iter().collect(),
-            vars: v.into_iter().collect(),
-            instr: instr.into_iter().collect()
+            inputs: i,
+            outputs: o,
+            vars: v,
+            instr
         }
     }
 }
@@ -2050,11 +2266,12 @@ fn __action5<
 fn __action6<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, &'input str, usize),
+    (_, v, _): (usize, Var<'input>, usize),
     (_, _, _): (usize, &'input str, usize),
     (_, e, _): (usize, Expr<'input>, usize),
-) -> (&'input str, Expr<'input>)
+) -> (Var<'input>, Expr<'input>)
 {
     (v, e)
 }
@@ -2063,9 +2280,10 @@ fn __action6<
 fn __action7<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, &'input str, usize),
-) -> (&'input str, usize)
+    (_, v, _): (usize, Var<'input>, usize),
+) -> (Var<'input>, usize)
 {
     (v, 1)
 }
@@ -2074,11 +2292,12 @@ fn __action7<
 fn __action8<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, &'input str, usize),
+    (_, v, _): (usize, Var<'input>, usize),
     (_, _, _): (usize, &'input str, usize),
     (_, n, _): (usize, usize, usize),
-) -> (&'input str, usize)
+) -> (Var<'input>, usize)
 {
     (v, n)
 }
@@ -2087,9 +2306,10 @@ fn __action8<
 fn __action9<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, _, _): (usize, &'input str, usize),
-    (_, v, _): (usize, &'input str, usize),
+    (_, v, _): (usize, Var<'input>, usize),
 ) -> Expr<'input>
 {
     Expr::Not(v)
@@ -2099,99 +2319,150 @@ fn __action9<
 fn __action10<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, _, _): (usize, &'input str, usize),
-    (_, v, _): (usize, &'input str, usize),
+    (_, v, _): (usize, Var<'input>, usize),
 ) -> Expr<'input>
 {
-    Expr::Rom(v)
+    Expr::Rom(None, v)
 }
 
 #[allow(unused_variables)]
 fn __action11<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, _, _): (usize, &'input str, usize),
-    (_, v, _): (usize, &'input str, usize),
+    (_, n1, _): (usize, usize, usize),
+    (_, n2, _): (usize, usize, usize),
+    (_, v, _): (usize, Var<'input>, usize),
 ) -> Expr<'input>
 {
-    Expr::Reg(v)
+    Expr::Rom(Some((n1, n2)), v)
 }
 
 #[allow(unused_variables)]
 fn __action12<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, op, _): (usize, BiOp, usize),
-    (_, v1, _): (usize, &'input str, usize),
-    (_, v2, _): (usize, &'input str, usize),
+    (_, _, _): (usize, &'input str, usize),
+    (_, v, _): (usize, Var<'input>, usize),
 ) -> Expr<'input>
 {
-    Expr::BiOp(op, v1, v2)
+    Expr::Reg(v)
 }
 
 #[allow(unused_variables)]
 fn __action13<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, _, _): (usize, &'input str, usize),
-    (_, n1, _): (usize, usize, usize),
-    (_, n2, _): (usize, usize, usize),
-    (_, v, _): (usize, &'input str, usize),
+    (_, op, _): (usize, BiOp, usize),
+    (_, v1, _): (usize, Var<'input>, usize),
+    (_, v2, _): (usize, Var<'input>, usize),
 ) -> Expr<'input>
 {
-    Expr::Slice(v, n1, n2)
+    Expr::BiOp(op, v1, v2)
 }
 
 #[allow(unused_variables)]
 fn __action14<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, _, _): (usize, &'input str, usize),
     (_, n1, _): (usize, usize, usize),
-    (_, v, _): (usize, &'input str, usize),
-) -> Expr<'input>
+    (_, l, _): (usize, usize, usize),
+    (_, n2, _): (usize, usize, usize),
+    (_, r, _): (usize, usize, usize),
+    (_, v, _): (usize, Var<'input>, usize),
+) -> Result<Expr<'input>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
 {
-    Expr::Slice(v, n1, n1+1)
+    match n2.checked_add(1) {
+        Some(end) => Ok(Expr::Slice(v, n1, end)),
+        None => Err(ParseError::User { error: (l, "this index is too large", r) }),
+    }
 }
 
 #[allow(unused_variables)]
 fn __action15<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, _, _): (usize, &'input str, usize),
-    (_, v1, _): (usize, &'input str, usize),
-    (_, v2, _): (usize, &'input str, usize),
-    (_, v3, _): (usize, &'input str, usize),
-) -> Expr<'input>
+    (_, l, _): (usize, usize, usize),
+    (_, n1, _): (usize, usize, usize),
+    (_, r, _): (usize, usize, usize),
+    (_, v, _): (usize, Var<'input>, usize),
+) -> Result<Expr<'input>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
 {
-    Expr::Mux(v1, v2, v3)
+    match n1.checked_add(1) {
+        Some(end) => Ok(Expr::Slice(v, n1, end)),
+        None => Err(ParseError::User { error: (l, "this index is too large", r) }),
+    }
 }
 
 #[allow(unused_variables)]
 fn __action16<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, _, _): (usize, &'input str, usize),
-    (_, v1, _): (usize, &'input str, usize),
-    (_, v2, _): (usize, &'input str, usize),
-    (_, v3, _): (usize, &'input str, usize),
-    (_, v4, _): (usize, &'input str, usize),
+    (_, v1, _): (usize, Var<'input>, usize),
+    (_, v2, _): (usize, Var<'input>, usize),
+    (_, v3, _): (usize, Var<'input>, usize),
 ) -> Expr<'input>
 {
-    Expr::Ram(v1, v2, v3, v4)
+    Expr::Mux(v1, v2, v3)
 }
 
 #[allow(unused_variables)]
 fn __action17<
     'input,
 >(
+    file_id: usize,
+    input: &'input str,
+    (_, _, _): (usize, &'input str, usize),
+    (_, v1, _): (usize, Var<'input>, usize),
+    (_, v2, _): (usize, Var<'input>, usize),
+    (_, v3, _): (usize, Var<'input>, usize),
+    (_, v4, _): (usize, Var<'input>, usize),
+) -> Expr<'input>
+{
+    Expr::Ram(None, v1, v2, v3, v4)
+}
+
+#[allow(unused_variables)]
+fn __action18<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    (_, _, _): (usize, &'input str, usize),
+    (_, n1, _): (usize, usize, usize),
+    (_, n2, _): (usize, usize, usize),
+    (_, v1, _): (usize, Var<'input>, usize),
+    (_, v2, _): (usize, Var<'input>, usize),
+    (_, v3, _): (usize, Var<'input>, usize),
+    (_, v4, _): (usize, Var<'input>, usize),
+) -> Expr<'input>
+{
+    Expr::Ram(Some((n1, n2)), v1, v2, v3, v4)
+}
+
+#[allow(unused_variables)]
+fn __action19<
+    'input,
+>(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, Vec<bool>, usize),
 ) -> Expr<'input>
@@ -2200,20 +2471,22 @@ fn __action17<
 }
 
 #[allow(unused_variables)]
-fn __action18<
+fn __action20<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, &'input str, usize),
+    (_, __0, _): (usize, Var<'input>, usize),
 ) -> Expr<'input>
 {
     Expr::Var(__0)
 }
 
 #[allow(unused_variables)]
-fn __action19<
+fn __action21<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> BiOp
@@ -2222,9 +2495,10 @@ fn __action19<
 }
 
 #[allow(unused_variables)]
-fn __action20<
+fn __action22<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> BiOp
@@ -2233,9 +2507,10 @@ fn __action20<
 }
 
 #[allow(unused_variables)]
-fn __action21<
+fn __action23<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> BiOp
@@ -2244,9 +2519,10 @@ fn __action21<
 }
 
 #[allow(unused_variables)]
-fn __action22<
+fn __action24<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> BiOp
@@ -2255,9 +2531,10 @@ fn __action22<
 }
 
 #[allow(unused_variables)]
-fn __action23<
+fn __action25<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> BiOp
@@ -2266,9 +2543,10 @@ fn __action23<
 }
 
 #[allow(unused_variables)]
-fn __action24<
+fn __action26<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, mut v, _): (usize, Vec<bool>, usize),
     (_, _, _): (usize, &'input str, usize),
@@ -2278,9 +2556,10 @@ fn __action24<
 }
 
 #[allow(unused_variables)]
-fn __action25<
+fn __action27<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, mut v, _): (usize, Vec<bool>, usize),
     (_, _, _): (usize, &'input str, usize),
@@ -2290,9 +2569,10 @@ fn __action25<
 }
 
 #[allow(unused_variables)]
-fn __action26<
+fn __action28<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> Vec<bool>
@@ -2301,9 +2581,10 @@ fn __action26<
 }
 
 #[allow(unused_variables)]
-fn __action27<
+fn __action29<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     (_, __0, _): (usize, &'input str, usize),
 ) -> Vec<bool>
@@ -2312,36 +2593,57 @@ fn __action27<
 }
 
 #[allow(unused_variables)]
-fn __action28<
+fn __action30<
     'input,
 >(
+    file_id: usize,
+    input: &'input str,
+    (_, l, _): (usize, usize, usize),
+    (_, s, _): (usize, &'input str, usize),
+    (_, r, _): (usize, usize, usize),
+) -> Result<Vec<bool>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    s.chars().map(|c| match c {
+        '0' => Ok(false),
+        '1' => Ok(true),
+        _ => Err(ParseError::User { error: (l, "constants must only contain 0 and 1", r) }),
+    }).collect()
+}
+
+#[allow(unused_variables)]
+fn __action31<
+    'input,
+>(
+    file_id: usize,
     input: &'input str,
     __lookbehind: &usize,
     __lookahead: &usize,
-) -> ::std::vec::Vec<(&'input str, Expr<'input>)>
+) -> ::std::vec::Vec<(Var<'input>, Expr<'input>)>
 {
     vec![]
 }
 
 #[allow(unused_variables)]
-fn __action29<
+fn __action32<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<(&'input str, Expr<'input>)>, usize),
-) -> ::std::vec::Vec<(&'input str, Expr<'input>)>
+    (_, v, _): (usize, ::std::vec::Vec<(Var<'input>, Expr<'input>)>, usize),
+) -> ::std::vec::Vec<(Var<'input>, Expr<'input>)>
 {
     v
 }
 
 #[allow(unused_variables)]
-fn __action30<
+fn __action33<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<(&'input str, usize)>, usize),
-    (_, e, _): (usize, ::std::option::Option<(&'input str, usize)>, usize),
-) -> Vec<(&'input str, usize)>
+    (_, v, _): (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize),
+    (_, e, _): (usize, ::std::option::Option<(Var<'input>, usize)>, usize),
+) -> Vec<(Var<'input>, usize)>
 {
     match e { 
         None => v,
@@ -2354,13 +2656,14 @@ fn __action30<
 }
 
 #[allow(unused_variables)]
-fn __action31<
+fn __action34<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<&'input str>, usize),
-    (_, e, _): (usize, ::std::option::Option<&'input str>, usize),
-) -> Vec<&'input str>
+    (_, v, _): (usize, ::std::vec::Vec<Var<'input>>, usize),
+    (_, e, _): (usize, ::std::option::Option<Var<'input>>, usize),
+) -> Vec<Var<'input>>
 {
     match e { 
         None => v,
@@ -2373,232 +2676,280 @@ fn __action31<
 }
 
 #[allow(unused_variables)]
-fn __action32<
+fn __action35<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, &'input str, usize),
-) -> ::std::option::Option<&'input str>
+    __lookbehind: &usize,
+    __lookahead: &usize,
+) -> usize
+{
+    __lookbehind.clone()
+}
+
+#[allow(unused_variables)]
+fn __action36<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __lookbehind: &usize,
+    __lookahead: &usize,
+) -> usize
+{
+    __lookahead.clone()
+}
+
+#[allow(unused_variables)]
+fn __action37<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    (_, __0, _): (usize, Var<'input>, usize),
+) -> ::std::option::Option<Var<'input>>
 {
     Some(__0)
 }
 
 #[allow(unused_variables)]
-fn __action33<
+fn __action38<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __lookbehind: &usize,
     __lookahead: &usize,
-) -> ::std::option::Option<&'input str>
+) -> ::std::option::Option<Var<'input>>
 {
     None
 }
 
 #[allow(unused_variables)]
-fn __action34<
+fn __action39<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __lookbehind: &usize,
     __lookahead: &usize,
-) -> ::std::vec::Vec<&'input str>
+) -> ::std::vec::Vec<Var<'input>>
 {
     vec![]
 }
 
 #[allow(unused_variables)]
-fn __action35<
+fn __action40<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<&'input str>, usize),
-) -> ::std::vec::Vec<&'input str>
+    (_, v, _): (usize, ::std::vec::Vec<Var<'input>>, usize),
+) -> ::std::vec::Vec<Var<'input>>
 {
     v
 }
 
 #[allow(unused_variables)]
-fn __action36<
+fn __action41<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, &'input str, usize),
+    (_, __0, _): (usize, Var<'input>, usize),
     (_, _, _): (usize, &'input str, usize),
-) -> &'input str
+) -> Var<'input>
 {
     __0
 }
 
 #[allow(unused_variables)]
-fn __action37<
+fn __action42<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, (&'input str, usize), usize),
-) -> ::std::option::Option<(&'input str, usize)>
+    (_, __0, _): (usize, (Var<'input>, usize), usize),
+) -> ::std::option::Option<(Var<'input>, usize)>
 {
     Some(__0)
 }
 
 #[allow(unused_variables)]
-fn __action38<
+fn __action43<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __lookbehind: &usize,
     __lookahead: &usize,
-) -> ::std::option::Option<(&'input str, usize)>
+) -> ::std::option::Option<(Var<'input>, usize)>
 {
     None
 }
 
 #[allow(unused_variables)]
-fn __action39<
+fn __action44<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __lookbehind: &usize,
     __lookahead: &usize,
-) -> ::std::vec::Vec<(&'input str, usize)>
+) -> ::std::vec::Vec<(Var<'input>, usize)>
 {
     vec![]
 }
 
 #[allow(unused_variables)]
-fn __action40<
+fn __action45<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<(&'input str, usize)>, usize),
-) -> ::std::vec::Vec<(&'input str, usize)>
+    (_, v, _): (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize),
+) -> ::std::vec::Vec<(Var<'input>, usize)>
 {
     v
 }
 
 #[allow(unused_variables)]
-fn __action41<
+fn __action46<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, (&'input str, usize), usize),
+    (_, __0, _): (usize, (Var<'input>, usize), usize),
     (_, _, _): (usize, &'input str, usize),
-) -> (&'input str, usize)
+) -> (Var<'input>, usize)
 {
     __0
 }
 
 #[allow(unused_variables)]
-fn __action42<
+fn __action47<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, (&'input str, Expr<'input>), usize),
-) -> ::std::vec::Vec<(&'input str, Expr<'input>)>
+    (_, __0, _): (usize, (Var<'input>, Expr<'input>), usize),
+) -> ::std::vec::Vec<(Var<'input>, Expr<'input>)>
 {
     vec![__0]
 }
 
 #[allow(unused_variables)]
-fn __action43<
+fn __action48<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<(&'input str, Expr<'input>)>, usize),
-    (_, e, _): (usize, (&'input str, Expr<'input>), usize),
-) -> ::std::vec::Vec<(&'input str, Expr<'input>)>
+    (_, v, _): (usize, ::std::vec::Vec<(Var<'input>, Expr<'input>)>, usize),
+    (_, e, _): (usize, (Var<'input>, Expr<'input>), usize),
+) -> ::std::vec::Vec<(Var<'input>, Expr<'input>)>
 {
     { let mut v = v; v.push(e); v }
 }
 
 #[allow(unused_variables)]
-fn __action44<
+fn __action49<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, (&'input str, usize), usize),
-) -> ::std::vec::Vec<(&'input str, usize)>
+    (_, __0, _): (usize, (Var<'input>, usize), usize),
+) -> ::std::vec::Vec<(Var<'input>, usize)>
 {
     vec![__0]
 }
 
 #[allow(unused_variables)]
-fn __action45<
+fn __action50<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<(&'input str, usize)>, usize),
-    (_, e, _): (usize, (&'input str, usize), usize),
-) -> ::std::vec::Vec<(&'input str, usize)>
+    (_, v, _): (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize),
+    (_, e, _): (usize, (Var<'input>, usize), usize),
+) -> ::std::vec::Vec<(Var<'input>, usize)>
 {
     { let mut v = v; v.push(e); v }
 }
 
 #[allow(unused_variables)]
-fn __action46<
+fn __action51<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, __0, _): (usize, &'input str, usize),
-) -> ::std::vec::Vec<&'input str>
+    (_, __0, _): (usize, Var<'input>, usize),
+) -> ::std::vec::Vec<Var<'input>>
 {
     vec![__0]
 }
 
 #[allow(unused_variables)]
-fn __action47<
+fn __action52<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    (_, v, _): (usize, ::std::vec::Vec<&'input str>, usize),
-    (_, e, _): (usize, &'input str, usize),
-) -> ::std::vec::Vec<&'input str>
+    (_, v, _): (usize, ::std::vec::Vec<Var<'input>>, usize),
+    (_, e, _): (usize, Var<'input>, usize),
+) -> ::std::vec::Vec<Var<'input>>
 {
     { let mut v = v; v.push(e); v }
 }
 
 #[allow(unused_variables)]
-fn __action48<
+fn __action53<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, (&'input str, usize), usize),
+    __0: (usize, (Var<'input>, usize), usize),
     __1: (usize, &'input str, usize),
-) -> ::std::vec::Vec<(&'input str, usize)>
+) -> ::std::vec::Vec<(Var<'input>, usize)>
 {
     let __start0 = __0.0.clone();
     let __end0 = __1.2.clone();
-    let __temp0 = __action41(
+    let __temp0 = __action46(
+        file_id,
         input,
         __0,
         __1,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action44(
+    __action49(
+        file_id,
         input,
         __temp0,
     )
 }
 
 #[allow(unused_variables)]
-fn __action49<
+fn __action54<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<(&'input str, usize)>, usize),
-    __1: (usize, (&'input str, usize), usize),
+    __0: (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize),
+    __1: (usize, (Var<'input>, usize), usize),
     __2: (usize, &'input str, usize),
-) -> ::std::vec::Vec<(&'input str, usize)>
+) -> ::std::vec::Vec<(Var<'input>, usize)>
 {
     let __start0 = __1.0.clone();
     let __end0 = __2.2.clone();
-    let __temp0 = __action41(
+    let __temp0 = __action46(
+        file_id,
         input,
         __1,
         __2,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action45(
+    __action50(
+        file_id,
         input,
         __0,
         __temp0,
@@ -2606,22 +2957,25 @@ fn __action49<
 }
 
 #[allow(unused_variables)]
-fn __action50<
+fn __action55<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::option::Option<(&'input str, usize)>, usize),
-) -> Vec<(&'input str, usize)>
+    __0: (usize, ::std::option::Option<(Var<'input>, usize)>, usize),
+) -> Vec<(Var<'input>, usize)>
 {
     let __start0 = __0.0.clone();
     let __end0 = __0.0.clone();
-    let __temp0 = __action39(
+    let __temp0 = __action44(
+        file_id,
         input,
         &__start0,
         &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action30(
+    __action33(
+        file_id,
         input,
         __temp0,
         __0,
@@ -2629,22 +2983,25 @@ fn __action50<
 }
 
 #[allow(unused_variables)]
-fn __action51<
+fn __action56<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<(&'input str, usize)>, usize),
-    __1: (usize, ::std::option::Option<(&'input str, usize)>, usize),
-) -> Vec<(&'input str, usize)>
+    __0: (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize),
+    __1: (usize, ::std::option::Option<(Var<'input>, usize)>, usize),
+) -> Vec<(Var<'input>, usize)>
 {
     let __start0 = __0.0.clone();
     let __end0 = __0.2.clone();
-    let __temp0 = __action40(
+    let __temp0 = __action45(
+        file_id,
         input,
         __0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action30(
+    __action33(
+        file_id,
         input,
         __temp0,
         __1,
@@ -2652,122 +3009,427 @@ fn __action51<
 }
 
 #[allow(unused_variables)]
-fn __action52<
+fn __action57<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, &'input str, usize),
+    __0: (usize, Var<'input>, usize),
     __1: (usize, &'input str, usize),
-) -> ::std::vec::Vec<&'input str>
+) -> ::std::vec::Vec<Var<'input>>
 {
     let __start0 = __0.0.clone();
     let __end0 = __1.2.clone();
-    let __temp0 = __action36(
+    let __temp0 = __action41(
+        file_id,
         input,
         __0,
         __1,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action46(
+    __action51(
+        file_id,
         input,
         __temp0,
     )
 }
 
 #[allow(unused_variables)]
-fn __action53<
+fn __action58<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<&'input str>, usize),
-    __1: (usize, &'input str, usize),
+    __0: (usize, ::std::vec::Vec<Var<'input>>, usize),
+    __1: (usize, Var<'input>, usize),
     __2: (usize, &'input str, usize),
-) -> ::std::vec::Vec<&'input str>
+) -> ::std::vec::Vec<Var<'input>>
 {
     let __start0 = __1.0.clone();
     let __end0 = __2.2.clone();
+    let __temp0 = __action41(
+        file_id,
+        input,
+        __1,
+        __2,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action52(
+        file_id,
+        input,
+        __0,
+        __temp0,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action59<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, ::std::option::Option<Var<'input>>, usize),
+) -> Vec<Var<'input>>
+{
+    let __start0 = __0.0.clone();
+    let __end0 = __0.0.clone();
+    let __temp0 = __action39(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action34(
+        file_id,
+        input,
+        __temp0,
+        __0,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action60<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, ::std::vec::Vec<Var<'input>>, usize),
+    __1: (usize, ::std::option::Option<Var<'input>>, usize),
+) -> Vec<Var<'input>>
+{
+    let __start0 = __0.0.clone();
+    let __end0 = __0.2.clone();
+    let __temp0 = __action40(
+        file_id,
+        input,
+        __0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action34(
+        file_id,
+        input,
+        __temp0,
+        __1,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action61<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+    __1: (usize, usize, usize),
+) -> Result<Vec<bool>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    let __start0 = __0.0.clone();
+    let __end0 = __0.0.clone();
+    let __temp0 = __action36(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action30(
+        file_id,
+        input,
+        __temp0,
+        __0,
+        __1,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action62<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+    __1: (usize, usize, usize),
+    __2: (usize, usize, usize),
+    __3: (usize, usize, usize),
+    __4: (usize, Var<'input>, usize),
+) -> Result<Expr<'input>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    let __start0 = __1.2.clone();
+    let __end0 = __2.0.clone();
     let __temp0 = __action36(
+        file_id,
         input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action14(
+        file_id,
+        input,
+        __0,
         __1,
+        __temp0,
         __2,
+        __3,
+        __4,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action63<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+    __1: (usize, usize, usize),
+    __2: (usize, usize, usize),
+    __3: (usize, Var<'input>, usize),
+) -> Result<Expr<'input>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    let __start0 = __0.2.clone();
+    let __end0 = __1.0.clone();
+    let __temp0 = __action36(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action47(
+    __action15(
+        file_id,
         input,
         __0,
         __temp0,
+        __1,
+        __2,
+        __3,
     )
 }
 
 #[allow(unused_variables)]
-fn __action54<
+fn __action64<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::option::Option<&'input str>, usize),
-) -> Vec<&'input str>
+    __0: (usize, &'input str, usize),
+    __1: (usize, usize, usize),
+) -> Result<usize,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
 {
     let __start0 = __0.0.clone();
     let __end0 = __0.0.clone();
-    let __temp0 = __action34(
+    let __temp0 = __action36(
+        file_id,
         input,
         &__start0,
         &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action31(
+    __action2(
+        file_id,
         input,
         __temp0,
         __0,
+        __1,
     )
 }
 
 #[allow(unused_variables)]
-fn __action55<
+fn __action65<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<&'input str>, usize),
-    __1: (usize, ::std::option::Option<&'input str>, usize),
-) -> Vec<&'input str>
+    __0: (usize, &'input str, usize),
+    __1: (usize, usize, usize),
+) -> Var<'input>
 {
     let __start0 = __0.0.clone();
+    let __end0 = __0.0.clone();
+    let __temp0 = __action36(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action1(
+        file_id,
+        input,
+        __temp0,
+        __0,
+        __1,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action66<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+) -> Result<Vec<bool>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    let __start0 = __0.2.clone();
     let __end0 = __0.2.clone();
     let __temp0 = __action35(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action61(
+        file_id,
         input,
         __0,
+        __temp0,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action67<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+    __1: (usize, usize, usize),
+    __2: (usize, usize, usize),
+    __3: (usize, Var<'input>, usize),
+) -> Result<Expr<'input>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    let __start0 = __2.2.clone();
+    let __end0 = __3.0.clone();
+    let __temp0 = __action35(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action31(
+    __action62(
+        file_id,
         input,
+        __0,
+        __1,
+        __2,
         __temp0,
+        __3,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action68<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+    __1: (usize, usize, usize),
+    __2: (usize, Var<'input>, usize),
+) -> Result<Expr<'input>,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    let __start0 = __1.2.clone();
+    let __end0 = __2.0.clone();
+    let __temp0 = __action35(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action63(
+        file_id,
+        input,
+        __0,
         __1,
+        __temp0,
+        __2,
     )
 }
 
 #[allow(unused_variables)]
-fn __action56<
+fn __action69<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+) -> Result<usize,__lalrpop_util::ParseError<usize,Token<'input>,(usize, &'static str, usize)>>
+{
+    let __start0 = __0.2.clone();
+    let __end0 = __0.2.clone();
+    let __temp0 = __action35(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action64(
+        file_id,
+        input,
+        __0,
+        __temp0,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action70<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __0: (usize, &'input str, usize),
-    __1: (usize, Vec<&'input str>, usize),
+) -> Var<'input>
+{
+    let __start0 = __0.2.clone();
+    let __end0 = __0.2.clone();
+    let __temp0 = __action35(
+        file_id,
+        input,
+        &__start0,
+        &__end0,
+    );
+    let __temp0 = (__start0, __temp0, __end0);
+    __action65(
+        file_id,
+        input,
+        __0,
+        __temp0,
+    )
+}
+
+#[allow(unused_variables)]
+fn __action71<
+    'input,
+>(
+    file_id: usize,
+    input: &'input str,
+    __0: (usize, &'input str, usize),
+    __1: (usize, Vec<Var<'input>>, usize),
     __2: (usize, &'input str, usize),
-    __3: (usize, Vec<&'input str>, usize),
+    __3: (usize, Vec<Var<'input>>, usize),
     __4: (usize, &'input str, usize),
-    __5: (usize, Vec<(&'input str, usize)>, usize),
+    __5: (usize, Vec<(Var<'input>, usize)>, usize),
     __6: (usize, &'input str, usize),
 ) -> Netlist<'input>
 {
     let __start0 = __6.2.clone();
     let __end0 = __6.2.clone();
-    let __temp0 = __action28(
+    let __temp0 = __action31(
+        file_id,
         input,
         &__start0,
         &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
     __action5(
+        file_id,
         input,
         __0,
         __1,
@@ -2781,28 +3443,31 @@ fn __action56<
 }
 
 #[allow(unused_variables)]
-fn __action57<
+fn __action72<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __0: (usize, &'input str, usize),
-    __1: (usize, Vec<&'input str>, usize),
+    __1: (usize, Vec<Var<'input>>, usize),
     __2: (usize, &'input str, usize),
-    __3: (usize, Vec<&'input str>, usize),
+    __3: (usize, Vec<Var<'input>>, usize),
     __4: (usize, &'input str, usize),
-    __5: (usize, Vec<(&'input str, usize)>, usize),
+    __5: (usize, Vec<(Var<'input>, usize)>, usize),
     __6: (usize, &'input str, usize),
-    __7: (usize, ::std::vec::Vec<(&'input str, Expr<'input>)>, usize),
+    __7: (usize, ::std::vec::Vec<(Var<'input>, Expr<'input>)>, usize),
 ) -> Netlist<'input>
 {
     let __start0 = __7.0.clone();
     let __end0 = __7.2.clone();
-    let __temp0 = __action29(
+    let __temp0 = __action32(
+        file_id,
         input,
         __7,
     );
     let __temp0 = (__start0, __temp0, __end0);
     __action5(
+        file_id,
         input,
         __0,
         __1,
@@ -2816,66 +3481,75 @@ fn __action57<
 }
 
 #[allow(unused_variables)]
-fn __action58<
+fn __action73<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, (&'input str, usize), usize),
-) -> Vec<(&'input str, usize)>
+    __0: (usize, (Var<'input>, usize), usize),
+) -> Vec<(Var<'input>, usize)>
 {
     let __start0 = __0.0.clone();
     let __end0 = __0.2.clone();
-    let __temp0 = __action37(
+    let __temp0 = __action42(
+        file_id,
         input,
         __0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action50(
+    __action55(
+        file_id,
         input,
         __temp0,
     )
 }
 
 #[allow(unused_variables)]
-fn __action59<
+fn __action74<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __lookbehind: &usize,
     __lookahead: &usize,
-) -> Vec<(&'input str, usize)>
+) -> Vec<(Var<'input>, usize)>
 {
     let __start0 = __lookbehind.clone();
     let __end0 = __lookahead.clone();
-    let __temp0 = __action38(
+    let __temp0 = __action43(
+        file_id,
         input,
         &__start0,
         &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action50(
+    __action55(
+        file_id,
         input,
         __temp0,
     )
 }
 
 #[allow(unused_variables)]
-fn __action60<
+fn __action75<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<(&'input str, usize)>, usize),
-    __1: (usize, (&'input str, usize), usize),
-) -> Vec<(&'input str, usize)>
+    __0: (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize),
+    __1: (usize, (Var<'input>, usize), usize),
+) -> Vec<(Var<'input>, usize)>
 {
     let __start0 = __1.0.clone();
     let __end0 = __1.2.clone();
-    let __temp0 = __action37(
+    let __temp0 = __action42(
+        file_id,
         input,
         __1,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action51(
+    __action56(
+        file_id,
         input,
         __0,
         __temp0,
@@ -2883,22 +3557,25 @@ fn __action60<
 }
 
 #[allow(unused_variables)]
-fn __action61<
+fn __action76<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<(&'input str, usize)>, usize),
-) -> Vec<(&'input str, usize)>
+    __0: (usize, ::std::vec::Vec<(Var<'input>, usize)>, usize),
+) -> Vec<(Var<'input>, usize)>
 {
     let __start0 = __0.2.clone();
     let __end0 = __0.2.clone();
-    let __temp0 = __action38(
+    let __temp0 = __action43(
+        file_id,
         input,
         &__start0,
         &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action51(
+    __action56(
+        file_id,
         input,
         __0,
         __temp0,
@@ -2906,66 +3583,75 @@ fn __action61<
 }
 
 #[allow(unused_variables)]
-fn __action62<
+fn __action77<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, &'input str, usize),
-) -> Vec<&'input str>
+    __0: (usize, Var<'input>, usize),
+) -> Vec<Var<'input>>
 {
     let __start0 = __0.0.clone();
     let __end0 = __0.2.clone();
-    let __temp0 = __action32(
+    let __temp0 = __action37(
+        file_id,
         input,
         __0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action54(
+    __action59(
+        file_id,
         input,
         __temp0,
     )
 }
 
 #[allow(unused_variables)]
-fn __action63<
+fn __action78<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
     __lookbehind: &usize,
     __lookahead: &usize,
-) -> Vec<&'input str>
+) -> Vec<Var<'input>>
 {
     let __start0 = __lookbehind.clone();
     let __end0 = __lookahead.clone();
-    let __temp0 = __action33(
+    let __temp0 = __action38(
+        file_id,
         input,
         &__start0,
         &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action54(
+    __action59(
+        file_id,
         input,
         __temp0,
     )
 }
 
 #[allow(unused_variables)]
-fn __action64<
+fn __action79<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<&'input str>, usize),
-    __1: (usize, &'input str, usize),
-) -> Vec<&'input str>
+    __0: (usize, ::std::vec::Vec<Var<'input>>, usize),
+    __1: (usize, Var<'input>, usize),
+) -> Vec<Var<'input>>
 {
     let __start0 = __1.0.clone();
     let __end0 = __1.2.clone();
-    let __temp0 = __action32(
+    let __temp0 = __action37(
+        file_id,
         input,
         __1,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action55(
+    __action60(
+        file_id,
         input,
         __0,
         __temp0,
@@ -2973,22 +3659,25 @@ fn __action64<
 }
 
 #[allow(unused_variables)]
-fn __action65<
+fn __action80<
     'input,
 >(
+    file_id: usize,
     input: &'input str,
-    __0: (usize, ::std::vec::Vec<&'input str>, usize),
-) -> Vec<&'input str>
+    __0: (usize, ::std::vec::Vec<Var<'input>>, usize),
+) -> Vec<Var<'input>>
 {
     let __start0 = __0.2.clone();
     let __end0 = __0.2.clone();
-    let __temp0 = __action33(
+    let __temp0 = __action38(
+        file_id,
         input,
         &__start0,
         &__end0,
     );
     let __temp0 = (__start0, __temp0, __end0);
-    __action55(
+    __action60(
+        file_id,
         input,
         __0,
         __temp0,
@@ -2996,16 +3685,16 @@ fn __action65<
 }
 
 pub trait __ToTriple<'input, > {
-    fn to_triple(value: Self) -> Result<(usize,Token<'input>,usize), __lalrpop_util::ParseError<usize, Token<'input>, &'static str>>;
+    fn to_triple(value: Self) -> Result<(usize,Token<'input>,usize), __lalrpop_util::ParseError<usize, Token<'input>, (usize, &'static str, usize)>>;
 }
 
 impl<'input, > __ToTriple<'input, > for (usize, Token<'input>, usize) {
-    fn to_triple(value: Self) -> Result<(usize,Token<'input>,usize), __lalrpop_util::ParseError<usize, Token<'input>, &'static str>> {
+    fn to_triple(value: Self) -> Result<(usize,Token<'input>,usize), __lalrpop_util::ParseError<usize, Token<'input>, (usize, &'static str, usize)>> {
         Ok(value)
     }
 }
-impl<'input, > __ToTriple<'input, > for Result<(usize, Token<'input>, usize), &'static str> {
-    fn to_triple(value: Self) -> Result<(usize,Token<'input>,usize), __lalrpop_util::ParseError<usize, Token<'input>, &'static str>> {
+impl<'input, > __ToTriple<'input, > for Result<(usize, Token<'input>, usize), (usize, &'static str, usize)> {
+    fn to_triple(value: Self) -> Result<(usize,Token<'input>,usize), __lalrpop_util::ParseError<usize, Token<'input>, (usize, &'static str, usize)>> {
         match value {
             Ok(v) => Ok(v),
             Err(error) => Err(__lalrpop_util::ParseError::User { error }),
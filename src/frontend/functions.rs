@@ -1,24 +1,32 @@
 use std::fmt::Display;
 
 use crate::ast::parse_ast::*;
-use crate::frontend::constants::{compute_const, compute_consts_in_statement, ComputeConstError};
-use ahash::AHashMap;
+use crate::frontend::constants::{
+    compute_const, compute_consts_in_automaton, compute_consts_in_statement, ComputeConstError,
+};
+use ahash::{AHashMap, AHashSet};
 use global_counter::global_counter;
+use itertools::Itertools;
 /*
 This file recusively inlines functions.
 It simply alternates between inlining all functions, then computing all constants.
 Then as all constants in automata are values again, it can inlines the remaining functions, and so on.
 
-It stops if the depth exceed a constant currently, probably a cli parameter eventually
+The automata with static arguments are instantiated in the same loop : once the static arguments
+of a use are values, it uses a copy of the automaton in which the constants are computed, named after
+the values (for example counter<8>). The functions of the copy are inlined in the next iterations.
+
+Every call keeps the chain of inlined calls it comes from, with the values of their static arguments,
+and it stops if this chain is deeper than the recursion depth given as an argument.
 */
-//TODO : make the recursion depth an argument instead of a builtin const
+//the default recursion depth
 pub const REC_DEPTH: u32 = 1000;
 global_counter!(FN_CALL_VARIABLE, u32, 0);
 
 //error handling
 #[derive(Debug)]
 pub enum ExpandFnError {
-    StackOverflow(Loc<String>), //Actually just recursion depth...
+    StackOverflow(Vec<Loc<String>>), //the chain of calls which was too deep
     WrongNumber(WrongNumberType, Pos, String, usize, usize),
     ReplaceConstError(ComputeConstError),
     UnknowFunction(Pos, String),
@@ -48,17 +56,17 @@ impl From<ComputeConstError> for ExpandFnError {
 pub fn expand_functions(
     prog: &mut Program,
     type_map: &mut AHashMap<String, (i32, Pos)>,
+    rec_depth: u32,
 ) -> Result<(), ExpandFnError> {
-    let mut changed = Some(Loc {
-        loc: (0, 0, 0),
-        value: String::new(),
-    });
-    let mut counter = 0;
+    let mut changed = Some(Vec::new());
     while changed.is_some() {
         changed = replace_fn_calls(prog, type_map)?;
-        counter += 1;
-        if counter >= REC_DEPTH && changed.is_some() {
-            return Err(ExpandFnError::StackOverflow(changed.unwrap()));
+        changed = deepest(changed, instantiate_automata(prog)?);
+        if let Some(calls) = changed {
+            if calls.len() > rec_depth as usize {
+                return Err(ExpandFnError::StackOverflow(calls));
+            }
+            changed = Some(calls);
         }
     }
     //erase the functions as they are no longer needed after that
@@ -70,27 +78,114 @@ pub fn expand_functions(
 fn replace_fn_calls(
     prog: &mut Program,
     type_map: &mut AHashMap<String, (i32, Pos)>,
-) -> Result<Option<Loc<String>>, ExpandFnError> {
+) -> Result<Option<Vec<Loc<String>>>, ExpandFnError> {
     let mut changed = None;
-    for (_mod_name, automaton) in prog.automata.iter_mut() {
+    //the constants of the automata with static arguments are not known yet, their instances are used
+    for (_mod_name, automaton) in prog
+        .automata
+        .iter_mut()
+        .filter(|(_, a)| a.static_args.is_empty())
+    {
         for (_, state) in automaton.states.iter_mut() {
-            changed = changed.or(replace_fn_calls_in_statements(
-                &mut state.statements,
-                &mut prog.functions,
-                type_map,
-            )?);
+            changed = deepest(
+                changed,
+                replace_fn_calls_in_statements(
+                    &mut state.statements,
+                    &mut prog.functions,
+                    type_map,
+                )?,
+            );
         }
     }
     Ok(changed)
 }
 
+//an instance to make : the instantiated automaton, the values of the static arguments
+//and the chain of calls which led to it
+type Instance = (String, Vec<i32>, Vec<Loc<String>>);
+
+//This replaces the uses of automata with static arguments by the uses of their instances, and makes the new ones.
+//it returns the deepest chain of calls which led to a new instance, to report infinite recursion errors.
+fn instantiate_automata(prog: &mut Program) -> Result<Option<Vec<Loc<String>>>, ExpandFnError> {
+    let static_args: AHashMap<String, usize> = prog
+        .automata
+        .iter()
+        .map(|(name, a)| (name.clone(), a.static_args.len()))
+        .collect();
+    //the instances to make
+    let mut instances: AHashMap<String, Instance> = AHashMap::new();
+    let mut changed = None;
+    for (_, automaton) in prog
+        .automata
+        .iter_mut()
+        .filter(|(_, a)| a.static_args.is_empty())
+    {
+        for (_, state) in automaton.states.iter_mut() {
+            for statement in state.statements.iter_mut() {
+                let ext = match statement {
+                    Statement::ExtAutomaton(ext) => ext,
+                    _ => continue,
+                };
+                //unknown automata are reported when they are collapsed
+                let expected = match static_args.get(&ext.name.value) {
+                    Some(n) => *n,
+                    None => continue,
+                };
+                if expected != ext.static_args.len() {
+                    return Err(ExpandFnError::WrongNumber(
+                        WrongNumberType::StaticArgs,
+                        ext.static_args.loc,
+                        ext.name.to_string(),
+                        expected,
+                        ext.static_args.len(),
+                    ));
+                }
+                if expected == 0 {
+                    continue;
+                }
+                let empty_map = AHashMap::new();
+                let values = ext
+                    .static_args
+                    .iter()
+                    .map(|c| compute_const(c, &empty_map))
+                    .collect::<Result<Vec<i32>, ComputeConstError>>()?;
+                let name = call_name(&ext.name, &values);
+                if !static_args.contains_key(&name) && !instances.contains_key(&name) {
+                    let mut calls = ext.callers.clone();
+                    calls.push(Loc::new(ext.name.loc, name.clone()));
+                    changed = deepest(changed, Some(calls.clone()));
+                    instances.insert(name.clone(), (ext.name.to_string(), values, calls));
+                }
+                ext.name.value = name;
+                ext.static_args.clear();
+            }
+        }
+    }
+    for (name, (automaton_name, values, calls)) in instances {
+        let mut instance = prog.automata.get(&automaton_name).unwrap().clone();
+        let consts = instance
+            .static_args
+            .drain(..)
+            .zip(values)
+            .map(|(arg, v)| (arg, Const::Value(v)))
+            .collect();
+        compute_consts_in_automaton(&mut instance, &consts)?;
+        for (_, state) in instance.states.iter_mut() {
+            set_callers(&mut state.statements, &calls);
+        }
+        instance.name = name.clone();
+        prog.automata.insert(name, instance);
+    }
+    Ok(changed)
+}
+
 //This replaces a vec of statements with a new vec of statements, where function calls are inlined
-//it returns the name of a function which was inlined, to report infinite recursion errors.
+//it returns the deepest chain of calls which was inlined, to report infinite recursion errors.
 fn replace_fn_calls_in_statements(
     statements: &mut Vec<Statement>,
     functions: &mut AHashMap<String, Function>,
     type_map: &mut AHashMap<String, (i32, Pos)>,
-) -> Result<Option<Loc<String>>, ExpandFnError> {
+) -> Result<Option<Vec<Loc<String>>>, ExpandFnError> {
     let mut new_vec: Vec<Statement> = Vec::new();
     let mut changed = None;
     for stat in statements.drain(..) {
@@ -110,8 +205,9 @@ fn replace_fn_calls_in_statements(
                             loc: assign.var.loc.clone(),
                             value: vec![assign.var.clone()],
                         };
-                        inline_function(func, fn_call, outputs, &mut new_vec, type_map)?;
-                        changed = Some(fn_call.name.clone());
+                        let calls =
+                            inline_function(func, fn_call, outputs, &mut new_vec, type_map)?;
+                        changed = deepest(changed, Some(calls));
                     }
                 }
                 //and the select the other var assign and add them as well
@@ -125,28 +221,45 @@ fn replace_fn_calls_in_statements(
                         .collect(),
                 );
             }
-            Statement::If(mut if_struct) => {
+            Statement::If(mut if_struct) => match if_struct.condition {
                 //As the condition is always a value, this is a good place select the right block and ignore the other.
-                if let Const::Value(v) = if_struct.condition {
+                IfCondition::Const(Const::Value(v)) => {
                     if v == 0 {
-                        changed = changed.or(replace_fn_calls_in_statements(
-                            &mut if_struct.else_block,
-                            functions,
-                            type_map,
-                        )?);
+                        changed = deepest(
+                            changed,
+                            replace_fn_calls_in_statements(
+                                &mut if_struct.else_block,
+                                functions,
+                                type_map,
+                            )?,
+                        );
                         new_vec.append(&mut if_struct.else_block);
                     } else {
-                        changed = changed.or(replace_fn_calls_in_statements(
-                            &mut if_struct.if_block,
-                            functions,
-                            type_map,
-                        )?);
+                        changed = deepest(
+                            changed,
+                            replace_fn_calls_in_statements(
+                                &mut if_struct.if_block,
+                                functions,
+                                type_map,
+                            )?,
+                        );
                         new_vec.append(&mut if_struct.if_block);
                     }
-                } else {
+                }
+                //both blocks of a dynamic if are kept, they become muxes once the functions are inlined
+                IfCondition::Signal(_) => {
+                    for block in [&mut if_struct.if_block, &mut if_struct.else_block] {
+                        changed = deepest(
+                            changed,
+                            replace_fn_calls_in_statements(block, functions, type_map)?,
+                        );
+                    }
+                    new_vec.push(Statement::If(if_struct));
+                }
+                IfCondition::Const(_) => {
                     panic!("Non-constant condition in if condition, when it should be. Should not happen.")
                 }
-            }
+            },
             Statement::FnAssign(mut fn_assign) => {
                 //A simple function assign, just inline it
                 let func =
@@ -160,11 +273,13 @@ fn replace_fn_calls_in_statements(
                     loc: fn_assign.f.name.loc.clone(),
                     value: fn_assign.vars,
                 };
-                inline_function(func, &mut fn_assign.f, outputs, &mut new_vec, type_map)?;
-                changed = Some(fn_assign.f.name.clone());
+                let calls =
+                    inline_function(func, &mut fn_assign.f, outputs, &mut new_vec, type_map)?;
+                changed = deepest(changed, Some(calls));
             }
-            Statement::ExtAutomaton(_) => {
-                panic!("SHould not happen: nested automaton in function inlining")
+            //the automata are collapsed after the functions are inlined
+            Statement::ExtAutomaton(ext_automaton) => {
+                new_vec.push(Statement::ExtAutomaton(ext_automaton))
             }
         }
     }
@@ -175,13 +290,14 @@ fn replace_fn_calls_in_statements(
 //This inlines a function.
 //This means generating a certain amount of statements and intermediary variables,
 //and the binding those to the outputs, which are specifier
+//it returns the chain of calls which led to this one, including it.
 fn inline_function(
     func: &Function,
     fncall: &mut FnCall,
     outputs: Loc<Vec<Loc<Var>>>,
     out_statements: &mut Vec<Statement>,
     type_map: &mut AHashMap<String, (i32, Pos)>,
-) -> Result<(), ExpandFnError> {
+) -> Result<Vec<Loc<String>>, ExpandFnError> {
     //check the number of arguments
     if fncall.static_args.len() != func.static_args.len() {
         return Err(ExpandFnError::WrongNumber(
@@ -218,6 +334,16 @@ fn inline_function(
         compute_const(c, &empty_map)?;
         static_args_map.insert(func.static_args[i].clone(), c.clone());
     }
+    let values: Vec<i32> = fncall
+        .static_args
+        .iter()
+        .filter_map(|c| match c {
+            Const::Value(v) => Some(*v),
+            _ => None,
+        })
+        .collect();
+    let mut calls = fncall.callers.clone();
+    calls.push(Loc::new(fncall.name.loc, call_name(&func.name, &values)));
     //string with static args
     let args_string: String = static_args_map
         .iter()
@@ -281,10 +407,50 @@ fn inline_function(
         &args_string,
         counter,
     );
+    set_callers(&mut func_body, &calls);
 
     //push in the right order
     out_statements.append(&mut func_body);
-    Ok(())
+    Ok(calls)
+}
+
+//the name of a call with the values of its static arguments, as in adder<32>
+fn call_name(name: &str, values: &[i32]) -> String {
+    if values.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}<{}>", name, values.iter().join(", "))
+    }
+}
+
+//keeps the deepest of two chains of calls
+fn deepest(a: Option<Vec<Loc<String>>>, b: Option<Vec<Loc<String>>>) -> Option<Vec<Loc<String>>> {
+    match (a, b) {
+        (Some(a), Some(b)) if b.len() > a.len() => Some(b),
+        (a, b) => a.or(b),
+    }
+}
+
+//sets the chain of calls of the functions and automata used by inlined statements
+fn set_callers(statements: &mut Vec<Statement>, callers: &[Loc<String>]) {
+    for statement in statements {
+        match statement {
+            Statement::Assign(assigns) => {
+                //the expressions are flattened, so the calls are at the root
+                for assign in assigns {
+                    if let Expr::FnCall(fn_call) = &mut assign.expr.value {
+                        fn_call.callers = callers.to_vec();
+                    }
+                }
+            }
+            Statement::If(if_struct) => {
+                set_callers(&mut if_struct.if_block, callers);
+                set_callers(&mut if_struct.else_block, callers);
+            }
+            Statement::FnAssign(fn_assign) => fn_assign.f.callers = callers.to_vec(),
+            Statement::ExtAutomaton(ext) => ext.callers = callers.to_vec(),
+        }
+    }
 }
 
 //replace constants in functions using the provided static parameters
@@ -327,7 +493,7 @@ where
     match expr {
         Expr::Not(e) => map_vars_in_expr(e, f),
         Expr::Slice(e, _, _) => map_vars_in_expr(e, f),
-        Expr::BiOp(_, e1, e2) => {
+        Expr::BiOp(_, e1, e2) | Expr::Arith(_, e1, e2) => {
             map_vars_in_expr(e1, f);
             map_vars_in_expr(e2, f);
         }
@@ -336,7 +502,7 @@ where
             map_vars_in_expr(e2, f);
             map_vars_in_expr(e3, f);
         }
-        Expr::Reg(_, e) => map_vars_in_expr(e, f),
+        Expr::Reg(_, e) | Expr::Builtin(_, e) => map_vars_in_expr(e, f),
         Expr::Ram(RamStruct {
             read_addr,
             write_enable,
@@ -356,6 +522,7 @@ where
             name: _,
             args,
             static_args: _,
+            callers: _,
         }) => {
             for arg in &mut **args {
                 map_vars_in_expr(arg, f);
@@ -378,6 +545,9 @@ where
             }
         }
         Statement::If(ifstruct) => {
+            if let IfCondition::Signal(v) = &mut ifstruct.condition {
+                f(&mut v.value)
+            }
             for stat in &mut ifstruct.if_block {
                 map_vars_in_statement(stat, f);
             }
@@ -392,6 +562,7 @@ where
                     name: _,
                     args,
                     static_args: _,
+                    callers: _,
                 },
         }) => {
             for arg in &mut **args {
@@ -401,8 +572,149 @@ where
                 f(&mut v.value)
             }
         }
-        Statement::ExtAutomaton(_) => {
-            panic!("Should not happen: nested automaton in function inlining")
+        Statement::ExtAutomaton(ExtAutomaton {
+            inputs,
+            outputs,
+            name: _,
+            static_args: _,
+            callers: _,
+        }) => {
+            for input in &mut **inputs {
+                map_vars_in_expr(input, f);
+            }
+            for v in &mut **outputs {
+                f(&mut v.value)
+            }
+        }
+    }
+}
+
+//the built-ins are read like calls : a call is a built-in only if no function has its name,
+//so the functions of the user can be named like them
+pub fn resolve_builtins(prog: &mut Program) -> Result<(), ExpandFnError> {
+    let names: AHashSet<String> = prog.functions.keys().cloned().collect();
+    for (_, m) in &mut prog.automata {
+        for shared in &mut m.shared {
+            resolve_builtins_in_expr(&mut shared.expr, &names)?;
+        }
+        for (_, state) in &mut m.states {
+            resolve_builtins_in_statements(&mut state.statements, &names)?;
+            for transition in &mut state.transitions {
+                if let TrCond::Expr(expr) = &mut transition.condition.value {
+                    resolve_builtins_in_expr(expr, &names)?;
+                }
+            }
+        }
+    }
+    for (_, function) in &mut prog.functions {
+        resolve_builtins_in_statements(&mut function.statements, &names)?;
+    }
+    Ok(())
+}
+
+fn resolve_builtins_in_statements(
+    statements: &mut Vec<Statement>,
+    names: &AHashSet<String>,
+) -> Result<(), ExpandFnError> {
+    for statement in statements {
+        match statement {
+            Statement::Assign(assigns) => {
+                for assign in assigns {
+                    resolve_builtins_in_expr(&mut assign.expr, names)?;
+                }
+            }
+            Statement::If(ifstruct) => {
+                resolve_builtins_in_statements(&mut ifstruct.if_block, names)?;
+                resolve_builtins_in_statements(&mut ifstruct.else_block, names)?;
+            }
+            Statement::FnAssign(FnAssign { f, .. }) => {
+                for arg in &mut *f.args {
+                    resolve_builtins_in_expr(arg, names)?;
+                }
+            }
+            Statement::ExtAutomaton(e) => {
+                for input in &mut *e.inputs {
+                    resolve_builtins_in_expr(input, names)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resolve_builtins_in_expr(
+    expr: &mut Expr,
+    names: &AHashSet<String>,
+) -> Result<(), ExpandFnError> {
+    match expr {
+        Expr::Not(e) => resolve_builtins_in_expr(e, names),
+        Expr::Slice(e, _, _) | Expr::Reg(_, e) | Expr::Builtin(_, e) => {
+            resolve_builtins_in_expr(e, names)
+        }
+        Expr::BiOp(_, e1, e2) | Expr::Arith(_, e1, e2) => {
+            resolve_builtins_in_expr(e1, names)?;
+            resolve_builtins_in_expr(e2, names)
+        }
+        Expr::Mux(e1, e2, e3) => {
+            resolve_builtins_in_expr(e1, names)?;
+            resolve_builtins_in_expr(e2, names)?;
+            resolve_builtins_in_expr(e3, names)
+        }
+        Expr::Ram(RamStruct {
+            read_addr,
+            write_enable,
+            write_addr,
+            write_data,
+        }) => {
+            resolve_builtins_in_expr(read_addr, names)?;
+            resolve_builtins_in_expr(write_enable, names)?;
+            resolve_builtins_in_expr(write_addr, names)?;
+            resolve_builtins_in_expr(write_data, names)
+        }
+        Expr::Rom(RomStruct { read_addr, .. }) => resolve_builtins_in_expr(read_addr, names),
+        Expr::FnCall(f) => {
+            for arg in &mut *f.args {
+                resolve_builtins_in_expr(arg, names)?;
+            }
+            //repeat<n>(x) is the only built-in with a static argument
+            let static_args = match f.name.as_str() {
+                _ if names.contains(&*f.name.value) => return Ok(()),
+                "repeat" => 1,
+                "reverse" | "and_reduce" | "or_reduce" | "xor_reduce" => 0,
+                _ => return Ok(()),
+            };
+            if f.static_args.len() != static_args {
+                return Err(ExpandFnError::WrongNumber(
+                    WrongNumberType::StaticArgs,
+                    f.static_args.loc,
+                    f.name.to_string(),
+                    static_args,
+                    f.static_args.len(),
+                ));
+            }
+            if f.args.len() != 1 {
+                return Err(ExpandFnError::WrongNumber(
+                    WrongNumberType::Args,
+                    f.args.loc,
+                    f.name.to_string(),
+                    1,
+                    f.args.len(),
+                ));
+            }
+            let arg = f.args.value.pop().unwrap();
+            let builtin = match f.name.as_str() {
+                "repeat" => Builtin::Repeat(Loc::new(
+                    f.static_args.loc,
+                    f.static_args.value.pop().unwrap(),
+                )),
+                "reverse" => Builtin::Reverse,
+                "and_reduce" => Builtin::Reduce(BiOp::And),
+                "or_reduce" => Builtin::Reduce(BiOp::Or),
+                _ => Builtin::Reduce(BiOp::Xor),
+            };
+            *expr = Expr::Builtin(builtin, Box::new(arg));
+            Ok(())
         }
+        Expr::Var(_) | Expr::Last(_) | Expr::Const(_) => Ok(()),
     }
 }
@@ -7,13 +7,18 @@ use global_counter::global_counter;
 This file collapses external automata calls.
 Basically, it makes new shared variables for the output, and replaces the automaton call by shared var assignation.
 Then it renames every shared var and state in the called automaton, and copies all the states and shared variables in the main automaton.
+The copied states keep the name of the calling state as their parent : they can only be active when it is.
+When the calling state is left, the called automaton is paused, and it resumes where it was when the calling state
+is entered again, or restarts from its init states if it is entered through a reset transition.
 
 This repeats until there are no more external automata.
 
-See the pdf report for a bit more detail
-Warning : this file contains long and convoluted code.
+It runs after the functions are inlined, as they can use automata, and after the expressions are flattened :
+the statements it adds must be flat as well. The transitions are made explicit before that.
+
+An automaton terminates when it takes a transition to "terminate". The caller can get one more return
+variable than the automaton has outputs : it is true when the automaton has terminated.
 */
-//TODO: instant transitions
 
 //Errors
 #[derive(Debug)]
@@ -30,16 +35,22 @@ impl Display for WrongNumberType {
     }
 }
 pub enum CollapseAutomataError {
-    CyclicAutomatonCall(String),
+    //the uses of modules which form the cycle : position, calling module, called module
+    CyclicAutomatonCall(Vec<(Pos, String, String)>),
     UnknownAutomaton(Pos, String),
     NoMainAutomaton,
     UnknownVar(Pos, String),
     WrongNumber(WrongNumberType, Pos, usize, usize),
+    LocalVarInUnless(Pos, String), //strong transitions are evaluated before the state
+    //the instantaneous transitions which form a loop : position, state, next state
+    InstantLoop(Vec<(Pos, String, String)>),
 }
 
 //Two different module counter : for the inlined variables, and for the inputs.
 global_counter!(INLINE_MODULE_COUNTER, u32, 0);
 global_counter!(MODULE_INPUT_COUNTER, u32, 0);
+//and one for the conditions shared by several default transitions
+global_counter!(DEFAULT_CONDITION_COUNTER, u32, 0);
 type Result<T> = std::result::Result<T, CollapseAutomataError>;
 
 fn get_input_name(name: &String) -> String {
@@ -47,92 +58,73 @@ fn get_input_name(name: &String) -> String {
     MODULE_INPUT_COUNTER.inc();
     format!("{}#mod_input#{}", name, counter)
 }
-//makes all transitions shared variables : add them as shared vars,
-//assign them the original expression, and then replace the expression
-//in the transition with just the variable.
-pub fn make_transitions_shared(prog: &mut Program, iter: u32) {
-    for (mod_name, automaton) in prog.automata.iter_mut() {
-        let shared_map: AHashSet<String> = automaton
-            .shared
-            .iter()
-            .map(|v_a| v_a.var.value.clone())
-            .chain(automaton.states.iter().map(|(name, _)| name.clone()))
-            .chain(automaton.inputs.iter().map(|a| a.name.clone()))
-            .collect();
-        for (state_name, state) in automaton.states.iter_mut() {
-            let mut statement = Vec::new();
-            for (i, transition) in state.transitions.iter_mut().enumerate() {
-                if let TrCond::Expr(e) = &mut transition.condition.value {
-                    if let Expr::Var(v) = e {
-                        if shared_map.contains(&v.value) {
-                            continue;
-                        }
-                    }
-                    let new_name = Loc::new(
-                        transition.condition.loc,
-                        format!("s_r{}$t{}${}${}", iter, i, state_name, mod_name),
-                    );
-                    automaton.shared.push(VarAssign {
-                        var: new_name.clone(),
-                        expr: Loc::new(new_name.loc, Expr::Const(ConstExpr::Known(vec![false]))),
-                    });
-                    let old_expr = std::mem::replace(e, Expr::Var(new_name.clone()));
-                    statement.push(VarAssign {
-                        var: new_name,
-                        expr: Loc::new(transition.condition.loc, old_expr),
-                    });
-                }
-            }
-            state.statements.push(Statement::Assign(statement));
-        }
-    }
-}
 
-//replace default transition with "not any other transition"
+fn get_default_name(state: &String) -> String {
+    let counter = DEFAULT_CONDITION_COUNTER.get_cloned();
+    DEFAULT_CONDITION_COUNTER.inc();
+    format!("{}#default#{}", state, counter)
+}
+//replace default transition with "not any other transition".
+//The first transition whose condition is true is taken, so each condition
+//is restricted to the case where none of the previous ones are true.
 pub fn make_transitions_explicit(prog: &mut Program) {
     for (_mod_name, automaton) in prog.automata.iter_mut() {
         for (_state_name, state) in automaton.states.iter_mut() {
-            if !state.transitions.iter().any(|t| {
-                if let TrCond::Default = t.condition.value {
-                    true
-                } else {
-                    false
-                }
-            }) {
+            if !state.transitions.iter().any(|t| t.condition.is_default()) {
                 state.transitions.push(Transition {
                     condition: Loc::new(state.name.loc, TrCond::Default),
                     state: Loc::new(state.name.loc, Some(state.name.value.clone())),
                     reset: false,
+                    instant: false,
                 })
             }
-            let all_conditions = state
-                .transitions
-                .iter()
-                .fold(None, |prev, transition| {
-                    match (prev, &transition.condition.value) {
-                        (None, TrCond::Default) => None,
-                        (Some(p), TrCond::Default) => Some(p),
-                        (None, TrCond::Expr(e)) => {
-                            Some(Loc::new(transition.condition.loc, e.clone()))
-                        }
-                        (Some(p), TrCond::Expr(e)) => Some(Loc::new(
-                            p.loc,
-                            Expr::BiOp(
+            //the disjunction of the conditions seen so far
+            let mut previous: Option<Loc<Expr>> = None;
+            for transition in state.transitions.iter_mut() {
+                if let TrCond::Expr(e) = &transition.condition.value {
+                    let loc = transition.condition.loc;
+                    let e = Loc::new(loc, e.clone());
+                    if let Some(p) = &previous {
+                        transition.condition = Loc::new(
+                            loc,
+                            TrCond::Expr(Expr::BiOp(
                                 BiOp::And,
-                                Box::new(Loc::new(transition.condition.loc, e.clone())),
-                                Box::new(p),
-                            ),
-                        )),
+                                Box::new(e.clone()),
+                                Box::new(Loc::new(p.loc, Expr::Not(Box::new(p.value.clone())))),
+                            )),
+                        );
                     }
-                })
-                .unwrap_or(Loc::new(
-                    state.name.loc,
-                    Expr::Const(ConstExpr::Known(vec![false])),
-                ));
-            let default_condition = Loc::new(
+                    previous = Some(match previous {
+                        None => e,
+                        Some(p) => Loc::new(p.loc, Expr::BiOp(BiOp::Or, Box::new(e), Box::new(p))),
+                    });
+                }
+            }
+            let all_conditions = previous.unwrap_or(Loc::new(
+                state.name.loc,
+                Expr::Const(ConstExpr::Known(vec![false])),
+            ));
+            let mut default_condition = Loc::new(
                 all_conditions.loc,
                 Expr::Not(Box::new(all_conditions.value)),
             );
+            //several default transitions are taken together. They share the variable of their condition,
+            //so the states they lead to are known to be active at the same time (see concurrency.rs)
+            if state
+                .transitions
+                .iter()
+                .filter(|t| t.condition.is_default())
+                .count()
+                > 1
+            {
+                let loc = default_condition.loc;
+                let name = get_default_name(&state.name.value);
+                state.statements.push(Statement::Assign(vec![VarAssign {
+                    var: Loc::new(loc, name.clone()),
+                    expr: default_condition,
+                }]));
+                default_condition = Loc::new(loc, Expr::Var(Loc::new(loc, name)));
+            }
             for transition in state.transitions.iter_mut() {
                 if transition.condition.is_default() {
                     transition.condition = Loc::new(
@@ -146,147 +138,59 @@ pub fn make_transitions_explicit(prog: &mut Program) {
 }
 
 //replace each external automaton call with a parallel automaton.
-pub fn collapse_automata(prog: &mut Program) -> Result<()> {
-    make_transitions_shared(prog, 1);
-    make_transitions_explicit(prog);
+//the size constraints of the variables of the inlined automata are copied to their new names.
+pub fn collapse_automata(
+    prog: &mut Program,
+    type_constraints: &mut AHashMap<String, (i32, Pos)>,
+) -> Result<()> {
+    //the calls are inlined one level at a time, which would never end with a cycle
+    check_cyclic_calls(prog)?;
     let mut changed = true;
     let mut new_states = Vec::new();
     let mut new_init_states = Vec::new();
     let mut new_shared = Vec::new();
     //collapse automaton while something keeps changing.
-    //TODO : detect cycles and fail if there is one. Currently the compiler just hangs / the stack overflows.
     while changed {
         changed = false;
         let main_automaton = prog
             .automata
             .get("main")
             .ok_or(CollapseAutomataError::NoMainAutomaton)?;
-        //It need to be able to "predict" if a state will be entered, and so the parents of each state are needed.
-        let state_parents = compute_states_parents(main_automaton);
         //iterates on states with external automaton calls
         for (_, state) in main_automaton.states.iter() {
-            let extern_automata = state
+            if !state
                 .statements
                 .iter()
-                .filter_map(|s| {
-                    if let Statement::ExtAutomaton(e) = s {
-                        Some(e)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<&ExtAutomaton>>();
-            if extern_automata.is_empty() {
+                .any(|s| matches!(s, Statement::ExtAutomaton(_)))
+            {
                 continue;
             }
             changed = true;
-            let exit_condition = get_exit_condition(state);
-            //get the global conditions for "entering the state through a [reset/resume] transition"
-            let (mut in_conditions_reset, mut in_conditions_resume): (
-                Vec<(bool, Loc<Expr>)>,
-                Vec<(bool, Loc<Expr>)>,
-            ) = state_parents
-                .get(&*state.name.value)
-                .unwrap()
-                .iter()
-                .map(|s| {
-                    let parent = main_automaton.states.get(*s).unwrap();
-                    parent.transitions.iter().filter_map(|transition| {
-                        if transition.state.value.is_some()
-                            && transition.state.value.clone().unwrap() == state.name.value
-                        {
-                            Some((
-                                transition.reset,
-                                Loc::new(
-                                    transition.condition.loc,
-                                    //condition = the transition condition is true, and the automaton is currently executing the parent state
-                                    Expr::BiOp(
-                                        BiOp::And,
-                                        Box::new(Loc::new(
-                                            transition.condition.loc,
-                                            transition.condition.clone().value.unwrap(),
-                                        )),
-                                        Box::new(Loc::new(
-                                            state.name.loc,
-                                            Expr::Var(state.name.clone()),
-                                        )),
-                                    ),
-                                ),
-                            ))
-                        } else {
-                            None
-                        }
-                    })
-                })
-                .flatten()
-                .partition(|(b, _)| *b);
-            //Fold the resume and reset conditions in one expression
-            let reset_condition = in_conditions_reset
-                .drain(..)
-                .fold(None, |e, (_, n)| {
-                    if let Some(e) = e {
-                        Some(Loc::new(
-                            n.loc,
-                            Expr::BiOp(BiOp::And, Box::new(e), Box::new(n)),
-                        ))
-                    } else {
-                        Some(n)
-                    }
-                })
-                .unwrap_or(Loc::new(
-                    state.name.loc,
-                    Expr::Const(ConstExpr::Known(vec![false])),
-                ));
-            let resume_condition = in_conditions_resume
-                .drain(..)
-                .fold(None, |e: Option<Loc<TrCond>>, (_, n)| {
-                    if let Some(e) = e {
-                        Some(Loc::new(
-                            n.loc,
-                            TrCond::Expr(Expr::BiOp(
-                                BiOp::And,
-                                Box::new(Loc::new(e.loc, e.value.clone().unwrap())),
-                                Box::new(n),
-                            )),
-                        ))
-                    } else {
-                        Some(Loc::new(n.loc, TrCond::Expr(n.value)))
-                    }
-                })
-                .unwrap_or(Loc::new(
-                    state.name.loc,
-                    TrCond::Expr(Expr::Const(ConstExpr::Known(vec![false]))),
-                ));
             //This is the state which reads the value of the automaton and write them to shared variables.
             let mut link_state = State {
                 name: state.name.clone(),
-                statements: state
-                    .statements
-                    .iter()
-                    .filter_map(|s| {
-                        if let Statement::ExtAutomaton(_) = s {
-                            None
-                        } else {
-                            Some(s.clone())
-                        }
-                    })
-                    .collect(),
+                statements: Vec::new(),
                 transitions: state.transitions.clone(),
                 weak: state.weak,
+                parent: state.parent.clone(),
             };
-            //then iterates through the external automaton calls of the state
-            for ExtAutomaton {
-                inputs,
-                outputs,
-                name,
-            } in extern_automata
-            {
+            //the external automaton calls are replaced in place, as their outputs can be read by the next statements
+            for statement in state.statements.iter() {
+                //the static arguments were replaced by an instance of the automaton
+                let ExtAutomaton {
+                    inputs,
+                    outputs,
+                    name,
+                    static_args: _,
+                    callers: _,
+                } = match statement {
+                    Statement::ExtAutomaton(e) => e,
+                    _ => {
+                        link_state.statements.push(statement.clone());
+                        continue;
+                    }
+                };
                 //checks the name
-                if name.value == main_automaton.name {
-                    return Err(CollapseAutomataError::CyclicAutomatonCall(
-                        name.value.clone(),
-                    ));
-                }
                 let pos = name.loc;
                 let automaton = prog.automata.get(&name.value).ok_or(
                     CollapseAutomataError::UnknownAutomaton(name.loc, name.value.clone()),
@@ -300,13 +204,15 @@ pub fn collapse_automata(prog: &mut Program) -> Result<()> {
                         inputs.len(),
                     ));
                 }
-                //checks the outputs
-                if automaton.outputs.len() != outputs.len() {
+                //checks the outputs. There can be one more, which tells whether the automaton terminated
+                if automaton.outputs.len() != outputs.len()
+                    && automaton.outputs.len() + 1 != outputs.len()
+                {
                     return Err(CollapseAutomataError::WrongNumber(
                         WrongNumberType::ReturnVars,
                         name.loc,
-                        automaton.inputs.len(),
-                        inputs.len(),
+                        automaton.outputs.len(),
+                        outputs.len(),
                     ));
                 }
                 //links the variables from the outputs / inputs of external automaton to the call
@@ -328,28 +234,47 @@ pub fn collapse_automata(prog: &mut Program) -> Result<()> {
                             expr: expr.clone(),
                         }]));
                 }
-                let (mut states, mut init_states, mut shared, automaton_outputs) = make_automaton(
-                    &resume_condition,
-                    &reset_condition,
-                    exit_condition.clone(),
-                    in_names,
-                    automaton,
-                    main_automaton.init_states.contains(&state.name),
-                )?;
+                let (mut states, mut init_states, mut shared, automaton_outputs) =
+                    make_automaton(&state.name, in_names, automaton, type_constraints)?;
+                for (o, auto_o) in outputs.iter().zip(automaton_outputs.iter()) {
+                    link_state
+                        .statements
+                        .push(Statement::Assign(vec![VarAssign {
+                            var: o.clone(),
+                            expr: Loc::new(pos, Expr::Var(Loc::new(pos, auto_o.to_string()))),
+                        }]));
+                }
+                //the automaton has terminated when none of its states are active
+                if let Some(o) = outputs.get(automaton_outputs.len()) {
+                    let mut any_active = Expr::Const(ConstExpr::Known(vec![false]));
+                    for s in states.iter() {
+                        let name = Loc::new(pos, get_input_name(&o.value));
+                        link_state
+                            .statements
+                            .push(Statement::Assign(vec![VarAssign {
+                                var: name.clone(),
+                                expr: Loc::new(
+                                    pos,
+                                    Expr::BiOp(
+                                        BiOp::Or,
+                                        Box::new(Loc::new(pos, any_active)),
+                                        Box::new(Loc::new(pos, Expr::Var(s.name.clone()))),
+                                    ),
+                                ),
+                            }]));
+                        any_active = Expr::Var(name);
+                    }
+                    link_state
+                        .statements
+                        .push(Statement::Assign(vec![VarAssign {
+                            var: o.clone(),
+                            expr: Loc::new(pos, Expr::Not(Box::new(any_active))),
+                        }]));
+                }
                 //Add the new states, init states, shared variables, and link states, to the main automaton
                 new_init_states.append(&mut init_states);
                 new_states.append(&mut states);
                 new_shared.append(&mut shared);
-                link_state.statements.push(Statement::Assign(
-                    outputs
-                        .iter()
-                        .zip(automaton_outputs.iter())
-                        .map(|(o, auto_o)| VarAssign {
-                            var: o.clone(),
-                            expr: Loc::new(pos, Expr::Var(Loc::new(pos, auto_o.to_string()))),
-                        })
-                        .collect(),
-                ))
             }
             new_states.push(link_state);
         }
@@ -379,63 +304,193 @@ pub fn collapse_automata(prog: &mut Program) -> Result<()> {
     Ok(())
 }
 
-//Get a condition for the exit of a state.
-fn get_exit_condition(state: &State) -> Loc<Expr> {
-    let mut expr = Loc::new(state.name.loc, Expr::Var(state.name.clone()));
-    for transition in state.transitions.iter() {
-        expr = Loc::new(
-            transition.condition.loc,
-            Expr::BiOp(
-                BiOp::And,
-                Box::new(Loc::new(
-                    transition.condition.loc,
-                    transition.condition.value.clone().unwrap(),
-                )),
-                Box::new(expr),
-            ),
-        );
+//builds the graph of the module calls, and fails if a cycle can be reached from main.
+fn check_cyclic_calls(prog: &Program) -> Result<()> {
+    //the modules used by each module, with the position of the use
+    let mut calls: AHashMap<&str, Vec<(Pos, &str)>> = AHashMap::new();
+    for (name, automaton) in prog.automata.iter() {
+        let mut uses: Vec<(Pos, &str)> = automaton
+            .states
+            .values()
+            .flat_map(|state| state.statements.iter())
+            .filter_map(|s| {
+                if let Statement::ExtAutomaton(e) = s {
+                    Some((e.name.loc, &*e.name.value))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        //the states are in a hashmap, so this gives the same cycle every time
+        uses.sort();
+        calls.insert(name, uses);
+    }
+    if !prog.automata.contains_key("main") {
+        return Ok(()); //this is reported when collapsing the automata
+    }
+    match find_cycle("main", &calls, &mut Vec::new(), &mut AHashSet::new()) {
+        Some(cycle) => Err(CollapseAutomataError::CyclicAutomatonCall(cycle)),
+        None => Ok(()),
     }
-    expr
 }
-//compute all the parents of a state (the states that have a transition to this one)
-fn compute_states_parents(automaton: &Automaton) -> AHashMap<&str, Vec<&str>> {
-    let mut state_parents = AHashMap::new();
-    for (_, state) in automaton.states.iter() {
-        if !state_parents.contains_key(&*state.name.value) {
-            state_parents.insert(&*state.name.value, Vec::new());
+//depth first search in a graph given by its edges and their positions.
+//path contains the edges leading from the first node to the current one.
+fn find_cycle<'a>(
+    node: &'a str,
+    edges: &AHashMap<&'a str, Vec<(Pos, &'a str)>>,
+    path: &mut Vec<(Pos, &'a str, &'a str)>,
+    done: &mut AHashSet<&'a str>,
+) -> Option<Vec<(Pos, String, String)>> {
+    if done.contains(node) {
+        return None;
+    }
+    //unknown nodes have no edges, they are reported later
+    for (pos, next) in edges.get(node).into_iter().flatten() {
+        path.push((*pos, node, next));
+        if let Some(start) = path.iter().position(|(_, from, _)| from == next) {
+            return Some(
+                path[start..]
+                    .iter()
+                    .map(|(pos, from, to)| (*pos, from.to_string(), to.to_string()))
+                    .collect(),
+            );
         }
-        for transition in state.transitions.iter() {
-            if transition.state.is_none() {
-                continue;
+        if let Some(cycle) = find_cycle(next, edges, path, done) {
+            return Some(cycle);
+        }
+        path.pop();
+    }
+    done.insert(node);
+    None
+}
+//A state can't be entered twice in the same cycle, so the instantaneous transitions must not form a loop.
+//(in the states with strong transitions, they are already taken at the beginning of the cycle,
+//and => is the same as ->)
+pub fn check_instant_transitions(prog: &Program) -> Result<()> {
+    let mut names: Vec<&String> = prog.automata.keys().collect();
+    names.sort();
+    for name in names {
+        let automaton = &prog.automata[name];
+        let mut edges: AHashMap<&str, Vec<(Pos, &str)>> = AHashMap::new();
+        for (state_name, state) in automaton.states.iter().filter(|(_, s)| s.weak) {
+            edges.insert(
+                state_name,
+                state
+                    .transitions
+                    .iter()
+                    .filter(|t| t.instant)
+                    .filter_map(|t| t.state.value.as_deref().map(|next| (t.state.loc, next)))
+                    .collect(),
+            );
+        }
+        let mut states: Vec<&str> = edges.keys().copied().collect();
+        states.sort();
+        let mut done = AHashSet::new();
+        for state in states {
+            if let Some(cycle) = find_cycle(state, &edges, &mut Vec::new(), &mut done) {
+                return Err(CollapseAutomataError::InstantLoop(cycle));
             }
-            if !state_parents.contains_key(&**transition.state.as_ref().unwrap()) {
-                state_parents.insert(&**transition.state.as_ref().unwrap(), Vec::new());
+        }
+    }
+    Ok(())
+}
+//The conditions of strong transitions are computed before the body of the state,
+//so they cannot use its local variables.
+pub fn check_strong_transitions(prog: &Program) -> Result<()> {
+    for (_, automaton) in prog.automata.iter() {
+        let shared: AHashSet<&str> = automaton
+            .shared
+            .iter()
+            .map(|v| &*v.var.value)
+            .chain(automaton.outputs.iter().map(|a| &*a.name))
+            .collect();
+        for (_, state) in automaton.states.iter().filter(|(_, s)| !s.weak) {
+            let mut locals = Vec::new();
+            assigned_vars(&state.statements, &mut locals);
+            for transition in &state.transitions {
+                if let TrCond::Expr(e) = &transition.condition.value {
+                    let mut used = Vec::new();
+                    expr_vars(e, &mut used);
+                    if let Some(v) = used
+                        .into_iter()
+                        .find(|v| locals.contains(&&*v.value) && !shared.contains(&*v.value))
+                    {
+                        return Err(CollapseAutomataError::LocalVarInUnless(
+                            v.loc,
+                            v.value.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+//the variables assigned in some statements
+fn assigned_vars<'a>(statements: &'a [Statement], vars: &mut Vec<&'a str>) {
+    for statement in statements {
+        match statement {
+            Statement::Assign(assigns) => vars.extend(assigns.iter().map(|a| &*a.var.value)),
+            Statement::If(IfStruct {
+                if_block,
+                else_block,
+                ..
+            }) => {
+                assigned_vars(if_block, vars);
+                assigned_vars(else_block, vars)
+            }
+            Statement::FnAssign(f) => vars.extend(f.vars.iter().map(|v| &*v.value)),
+            Statement::ExtAutomaton(e) => vars.extend(e.outputs.iter().map(|v| &*v.value)),
+        }
+    }
+}
+//the variables read by an expression (last does not count, it is not the current value)
+pub fn expr_vars<'a>(expr: &'a Expr, vars: &mut Vec<&'a Loc<Var>>) {
+    match expr {
+        Expr::Var(v) => vars.push(v),
+        Expr::Const(_) | Expr::Last(_) => (),
+        Expr::Not(e) => expr_vars(e, vars),
+        Expr::Slice(e, _, _) | Expr::Reg(_, e) | Expr::Builtin(_, e) => expr_vars(e, vars),
+        Expr::BiOp(_, e1, e2) | Expr::Arith(_, e1, e2) => {
+            expr_vars(e1, vars);
+            expr_vars(e2, vars)
+        }
+        Expr::Mux(e1, e2, e3) => {
+            expr_vars(e1, vars);
+            expr_vars(e2, vars);
+            expr_vars(e3, vars)
+        }
+        Expr::Ram(RamStruct {
+            read_addr,
+            write_enable,
+            write_addr,
+            write_data,
+        }) => {
+            expr_vars(read_addr, vars);
+            expr_vars(write_enable, vars);
+            expr_vars(write_addr, vars);
+            expr_vars(write_data, vars)
+        }
+        Expr::Rom(RomStruct { read_addr, .. }) => expr_vars(read_addr, vars),
+        Expr::FnCall(FnCall { args, .. }) => {
+            for e in args.iter() {
+                expr_vars(e, vars)
             }
-            state_parents
-                .get_mut(&**transition.state.as_ref().unwrap())
-                .unwrap()
-                .push(&*state.name.value)
         }
     }
-    state_parents
 }
+
 //utility function for renaming things
 fn get_rename(counter: u32, name: &str, namespace: &str) -> String {
     format!("inline_mod${}${}${}$", name, namespace, counter)
 }
 
-fn get_pause_name(counter: u32, name: &str, namespace: &str) -> String {
-    format!("inline_mod_pause${}${}${}$", name, namespace, counter)
-}
-
-//Build the inline automaton corresponding to a automaton and a context
+//Build the inline automaton corresponding to a automaton called by the state parent
 pub fn make_automaton(
-    resume_condition: &Loc<TrCond>,
-    reset_condition: &Loc<Expr>,
-    exit_condition: Loc<Expr>,
+    parent: &Loc<String>,
     mut inputs: Vec<String>,
     automaton: &Automaton,
-    is_init: bool,
+    type_constraints: &mut AHashMap<String, (i32, Pos)>,
 ) -> Result<(Vec<State>, Vec<Loc<String>>, Vec<VarAssign>, Vec<String>)> {
     //the return vars are : new_states, init states, new shared, outputs
 
@@ -444,7 +499,6 @@ pub fn make_automaton(
     INLINE_MODULE_COUNTER.inc();
     let mut shared_rename_map = AHashMap::new();
     let mut shared = Vec::new();
-    let mut states = Vec::new();
     //get the renamed inputs
     for (s, rename) in automaton.inputs.iter().zip(inputs.drain(..)) {
         shared_rename_map.insert(s.name.clone(), rename);
@@ -472,36 +526,28 @@ pub fn make_automaton(
         shared.push(new_var.clone());
         shared_rename_map.insert(var.var.value.clone(), name);
     }
-    //the reset transition is the same for every pause state, so it is pre-computed for the whole automaton here
-    let reset_transition = automaton
-        .init_states
-        .iter()
-        .map(|s| {
-            let new_name = Loc::new(s.loc, Some(get_rename(counter, &s.value, &automaton.name)));
-            Transition {
-                condition: Loc::new(
-                    reset_condition.loc,
-                    TrCond::Expr(reset_condition.value.clone()),
-                ),
-                state: new_name,
-                reset: true,
+    //sorted, so that the states are always inlined in the same order
+    let mut states = automaton.states.values().collect::<Vec<&State>>();
+    states.sort_by(|s1, s2| s1.name.value.cmp(&s2.name.value));
+    //the variables computed by inlined functions keep their sizes
+    for state in states.iter() {
+        let mut locals = Vec::new();
+        assigned_vars(&state.statements, &mut locals);
+        for var in locals {
+            if let Some(constraint) = type_constraints.get(var).cloned() {
+                let name = shared_rename_map.get(var).cloned().unwrap_or(get_rename(
+                    counter,
+                    var,
+                    &automaton.name,
+                ));
+                type_constraints.insert(name, constraint);
             }
-        })
-        .collect();
-    //get the pause state and the execution state for each state of the called automaton
-    for (_, state) in &automaton.states {
-        let (new_state, pause_state) = make_state(
-            counter,
-            &automaton.name,
-            &resume_condition,
-            &reset_transition,
-            &exit_condition,
-            &shared_rename_map,
-            state,
-        );
-        states.push(new_state);
-        states.push(pause_state);
+        }
     }
+    let states = states
+        .into_iter()
+        .map(|state| make_state(counter, &automaton.name, parent, &shared_rename_map, state))
+        .collect();
     //get the outputs' name
     let outputs = automaton
         .outputs
@@ -516,164 +562,60 @@ pub fn make_automaton(
                 .clone())
         })
         .collect::<Result<Vec<String>>>()?;
-    //the new init states, which is either au pause state if the calling automaton is not init, or an exec state
-    //if the calling automaton is init.
+    //the init states are selected from the start, and wait for the parent to be active
     let init_states = automaton
         .init_states
         .iter()
-        .map(|n| {
-            if is_init {
-                Loc::new(n.loc, get_rename(counter, &*n.value, &*automaton.name))
-            } else {
-                Loc::new(n.loc, get_pause_name(counter, &*n.value, &*automaton.name))
-            }
-        })
+        .map(|n| Loc::new(n.loc, get_rename(counter, &*n.value, &*automaton.name)))
         .collect();
     Ok((states, init_states, shared, outputs))
 }
 
-//Transform a state into a pause mode and the actual state, for inlining, given a state and its context
+//Rename a state and everything in it, for inlining
 pub fn make_state(
     counter: u32,
     namespace: &str,
-    resume_condition: &Loc<TrCond>,
-    reset_transitions: &Vec<Transition>,
-    exit_condition: &Loc<Expr>,
+    parent: &Loc<String>,
     shared_rename_map: &AHashMap<String, String>,
     state: &State,
-) -> (State, State) {
-    let new_name = get_rename(counter, &state.name, namespace);
-    let pos = state.name.loc;
+) -> State {
     let statements = state
         .statements
         .iter()
         .map(|s| replace_var_in_statement(s, &shared_rename_map, counter, namespace))
         .collect::<Vec<Statement>>();
-    //get the new transitions
     let transitions = state
         .transitions
         .iter()
-        .map(|transition| {
-            let renamed_expr = Loc::new(
+        .map(|transition| Transition {
+            condition: Loc::new(
                 transition.condition.loc,
-                replace_var_in_expr(
+                TrCond::Expr(replace_var_in_expr(
                     transition.condition.unwrap_ref(),
                     shared_rename_map,
                     counter,
                     namespace,
-                ),
-            );
-            //the condition when the calling automaton stays in the node
-            let transition_stay = Loc::new(
-                renamed_expr.loc,
-                TrCond::Expr(Expr::BiOp(
-                    BiOp::And,
-                    Box::new(renamed_expr.clone()),
-                    Box::new(Loc::new(
-                        exit_condition.loc,
-                        Expr::Not(Box::new(exit_condition.value.clone())),
-                    )),
                 )),
-            );
-            //the condition when it exits the node (and the called automaton is then paused)
-            let transition_pause = Loc::new(
-                renamed_expr.loc,
-                TrCond::Expr(Expr::BiOp(
-                    BiOp::And,
-                    Box::new(renamed_expr.clone()),
-                    Box::new(exit_condition.clone()),
-                )),
-            );
-            let new_state_name = Loc::new(
+            ),
+            state: Loc::new(
                 transition.state.loc,
                 transition
                     .state
                     .value
                     .clone()
                     .map(|s| get_rename(counter, &s, namespace)),
-            );
-            let pause_state_name = Loc::new(
-                transition.state.loc,
-                transition
-                    .state
-                    .value
-                    .clone()
-                    .map(|s| get_pause_name(counter, &s, namespace)),
-            );
-            vec![
-                Transition {
-                    condition: transition_stay,
-                    state: new_state_name,
-                    reset: transition.reset,
-                },
-                Transition {
-                    condition: transition_pause,
-                    state: pause_state_name,
-                    reset: transition.reset,
-                },
-            ]
-        })
-        .flatten()
-        .collect::<Vec<Transition>>();
-    //make the condition for which the automaton must be paused
-    let mut pause_transitions = reset_transitions.clone();
-    let reset_condition = reset_transitions
-        .iter()
-        .fold(None, |prev: Option<Loc<TrCond>>, transition| {
-            if let Some(prev) = prev {
-                Some(Loc::new(
-                    pos,
-                    TrCond::Expr(Expr::BiOp(
-                        BiOp::Or,
-                        Box::new(Loc::new(prev.loc, prev.value.clone().unwrap())),
-                        Box::new(Loc::new(
-                            transition.condition.loc,
-                            transition.condition.value.clone().unwrap(),
-                        )),
-                    )),
-                ))
-            } else {
-                Some(transition.condition.clone())
-            }
+            ),
+            reset: transition.reset,
+            instant: transition.instant,
         })
-        .unwrap();
-    pause_transitions.push(Transition {
-        condition: resume_condition.clone(),
-        state: Loc::new(pos, Some(new_name.clone())),
-        reset: false,
-    });
-    //Stay paused while we don't come back to the state.
-    pause_transitions.push(Transition {
-        condition: Loc::new(
-            pos,
-            TrCond::Expr(Expr::Not(Box::new(Expr::BiOp(
-                BiOp::Or,
-                Box::new(Loc::new(
-                    resume_condition.loc,
-                    resume_condition.value.clone().unwrap(),
-                )),
-                Box::new(Loc::new(
-                    reset_condition.loc,
-                    reset_condition.value.unwrap(),
-                )),
-            )))),
-        ),
-        state: Loc::new(pos, Some(get_pause_name(counter, &state.name, namespace))),
-        reset: false,
-    });
-    let pause_state = State {
-        name: Loc::new(pos, get_pause_name(counter, &state.name, namespace)),
-        statements: Vec::new(),
-        weak: true,
-        transitions: pause_transitions,
-    };
-    let stay_state = State {
-        name: Loc::new(pos, get_rename(counter, &state.name, namespace)),
+        .collect();
+    State {
+        name: Loc::new(state.name.loc, get_rename(counter, &state.name, namespace)),
         statements,
-        weak: state.weak,
         transitions,
-    };
-    (stay_state, pause_state)
+        weak: state.weak,
+        parent: Some(parent.value.clone()),
+    }
 }
 
 //rename vars in a statement
@@ -718,7 +660,17 @@ fn replace_var_in_statement(
                 .iter()
                 .map(|s| replace_var_in_statement(s, replace_map, counter, automaton_name))
                 .collect(),
-            condition: condition.clone(),
+            condition: match condition {
+                IfCondition::Signal(v) => IfCondition::Signal(Loc::new(
+                    v.loc,
+                    replace_map.get(&v.value).cloned().unwrap_or(get_rename(
+                        counter,
+                        &v.value,
+                        automaton_name,
+                    )),
+                )),
+                c => c.clone(),
+            },
         }),
         Statement::FnAssign(FnAssign {
             vars,
@@ -727,6 +679,7 @@ fn replace_var_in_statement(
                     name,
                     args,
                     static_args,
+                    callers,
                 },
         }) => Statement::FnAssign(FnAssign {
             vars: vars
@@ -757,6 +710,7 @@ fn replace_var_in_statement(
                         .collect(),
                 ),
                 static_args: static_args.clone(),
+                callers: callers.clone(),
             },
         }),
         Statement::ExtAutomaton(e) => Statement::ExtAutomaton(ExtAutomaton {
@@ -789,6 +743,8 @@ fn replace_var_in_statement(
                     .collect(),
             ),
             name: e.name.clone(),
+            static_args: e.static_args.clone(),
+            callers: e.callers.clone(),
         }),
     }
 }
@@ -843,6 +799,17 @@ fn replace_var_in_expr(
                 replace_var_in_expr(e2, replace_map, counter, automaton_name),
             )),
         ),
+        Expr::Arith(op, e1, e2) => Expr::Arith(
+            op.clone(),
+            Box::new(Loc::new(
+                e1.loc,
+                replace_var_in_expr(e1, replace_map, counter, automaton_name),
+            )),
+            Box::new(Loc::new(
+                e2.loc,
+                replace_var_in_expr(e2, replace_map, counter, automaton_name),
+            )),
+        ),
         Expr::Mux(e1, e2, e3) => Expr::Mux(
             Box::new(Loc::new(
                 e1.loc,
@@ -864,6 +831,13 @@ fn replace_var_in_expr(
                 replace_var_in_expr(e, replace_map, counter, automaton_name),
             )),
         ),
+        Expr::Builtin(b, e) => Expr::Builtin(
+            b.clone(),
+            Box::new(Loc::new(
+                e.loc,
+                replace_var_in_expr(e, replace_map, counter, automaton_name),
+            )),
+        ),
         Expr::Ram(RamStruct {
             read_addr,
             write_enable,
@@ -901,6 +875,7 @@ fn replace_var_in_expr(
             name,
             args,
             static_args,
+            callers,
         }) => Expr::FnCall(FnCall {
             name: name.clone(),
             args: Loc::new(
@@ -916,6 +891,7 @@ fn replace_var_in_expr(
                     .collect(),
             ),
             static_args: static_args.clone(),
+            callers: callers.clone(),
         }),
     }
 }
@@ -39,24 +39,29 @@ contain cycles.
 */
 
 use crate::ast::{graph_automaton::*, typed_ast as typ};
+use crate::frontend::arith;
 
 use ahash::AHashMap;
 use std::rc::Rc;
 use typ::*;
 
 pub fn make_graph(prog: &typ::Program) -> ProgramGraph {
-    let state_rename_map = prog
-        .states
+    //the hashmaps are sorted, so the same program always gives the same graph
+    let mut prog_states = prog.states.iter().collect::<Vec<_>>();
+    prog_states.sort_by_key(|(s, _)| *s);
+    let mut prog_shared = prog.shared.iter().collect::<Vec<_>>();
+    prog_shared.sort_by_key(|(s, _)| *s);
+    let state_rename_map = prog_states
         .iter()
         .enumerate()
-        .map(|(i, (name, _))| (name.clone(), i))
+        .map(|(i, (name, _))| (name.to_string(), i))
         .collect::<AHashMap<String, usize>>();
     let shared_rename_map = prog
         .inputs
         .iter()
         .map(|s| &s.value)
-        .chain(prog.states.iter().map(|(name, _)| name))
-        .chain(prog.shared.iter().map(|(s, _)| s))
+        .chain(prog_states.iter().map(|(name, _)| *name))
+        .chain(prog_shared.iter().map(|(s, _)| *s))
         .enumerate()
         .map(|(i, s)| (s.clone(), i))
         .collect::<AHashMap<String, usize>>();
@@ -65,14 +70,13 @@ pub fn make_graph(prog: &typ::Program) -> ProgramGraph {
         .iter()
         .map(|s| vec![false; s.size])
         .chain(
-            prog.states
+            prog_states
                 .iter()
                 .map(|(name, _)| vec![prog.init_states.contains(name)]),
         )
-        .chain(prog.shared.iter().map(|(_s, init)| init.clone()))
+        .chain(prog_shared.iter().map(|(_s, init)| (*init).clone()))
         .collect::<Vec<Vec<bool>>>();
-    let states = prog
-        .states
+    let states = prog_states
         .iter()
         .map(|(_, state)| make_state(state, &state_rename_map, &shared_rename_map))
         .collect::<Vec<ProgramState>>();
@@ -86,7 +90,27 @@ pub fn make_graph(prog: &typ::Program) -> ProgramGraph {
         .iter()
         .map(|v| (v.value.clone(), *shared_rename_map.get(&v.value).unwrap()))
         .collect();
-    let inputs = prog.inputs.iter().map(|var| var.size).collect();
+    let inputs = prog
+        .inputs
+        .iter()
+        .map(|var| (var.value.clone(), var.size))
+        .collect();
+    //the outputs are already traced
+    let mut probes: Vec<(String, usize)> = prog_states
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(prog_shared.iter().map(|(s, _)| *s))
+        .filter(|s| is_user_name(s) && !prog.outputs.iter().any(|o| &o.value == *s))
+        .map(|s| (s.clone(), *shared_rename_map.get(s).unwrap()))
+        .collect();
+    probes.sort();
+    let mut locations: Vec<(String, usize, Pos)> = prog
+        .locations
+        .iter()
+        .filter(|(s, _)| is_user_name(s))
+        .map(|(s, loc)| (s.clone(), *shared_rename_map.get(s).unwrap(), *loc))
+        .collect();
+    locations.sort();
     let schedule = Vec::new(); // the scheduler is disabled
     ProgramGraph {
         init_states,
@@ -95,8 +119,17 @@ pub fn make_graph(prog: &typ::Program) -> ProgramGraph {
         schedule,
         outputs,
         inputs,
+        probes,
+        locations,
+        conflicts: Vec::new(),
     }
 }
+
+//Whether a variable was named by the user, or created by the compiler
+//(flattened expressions, module inputs, function variables)
+fn is_user_name(name: &str) -> bool {
+    !(name.contains('#') || name.starts_with('$'))
+}
 //transform a state into a ProgramState
 fn make_state(
     state: &State,
@@ -121,7 +154,7 @@ fn make_state(
     let transition_outputs = state
         .transitions
         .iter()
-        .map(|(var, state_name, reset)| {
+        .map(|(var, state_name, reset, instant)| {
             let state_id = if state_name.is_none() {
                 None
             } else {
@@ -136,7 +169,7 @@ fn make_state(
                 &mut expr_map,
                 &mut None, //shared variables used in transitions are not added as outputs
             );
-            (state_id, expr_state, *reset)
+            (state_id, expr_state, *reset, *instant)
         })
         .collect();
     let shared_outputs = state
@@ -162,12 +195,42 @@ fn make_state(
             }
         })
         .collect();
+    //the nodes of the variables are built again, they are the same as the ones used by the outputs
+    let mut locations: Vec<(String, Rc<ExprNode>, Pos)> = state
+        .locations
+        .iter()
+        .filter(|(v, _)| match v {
+            Var::Local(s) | Var::Shared(s) => is_user_name(s),
+        })
+        .map(|(v, loc)| {
+            let (name, var_id) = match v {
+                Var::Local(s) => (s, local_rename_map.get(s).cloned()),
+                Var::Shared(s) => (s, None),
+            };
+            let node = expr_to_state(
+                var_id,
+                state,
+                state.statements.get(v).unwrap(),
+                shared_rename_map,
+                &local_rename_map,
+                &mut None,
+                &mut None,
+            );
+            (name.clone(), node, *loc)
+        })
+        .collect();
+    locations.sort_by(|(s1, _, _), (s2, _, _)| s1.cmp(s2));
     ProgramState {
         transition_outputs,
         shared_outputs,
         inputs,
         weak: state.weak,
+        parent: state
+            .parent
+            .as_ref()
+            .map(|p| *state_rename_map.get(p).unwrap()),
         n_vars: local_rename_map.len(),
+        locations,
     }
 }
 //inputs = None means that we are inside a register.
@@ -262,6 +325,36 @@ fn expr_to_state(
             };
             ExprOperation::BiOp(op.clone(), n1, n2)
         }
+        ExprType::Arith(op, e1, e2) => {
+            let n1 = term_to_state(
+                e1,
+                state,
+                shared_rename_map,
+                local_rename_map,
+                expr_map,
+                inputs,
+            );
+            let n2 = term_to_state(
+                e2,
+                state,
+                shared_rename_map,
+                local_rename_map,
+                expr_map,
+                inputs,
+            );
+            arith::lower(op, n1, n2, e1.size, e2.size)
+        }
+        ExprType::Builtin(b, e) => {
+            let n = term_to_state(
+                e,
+                state,
+                shared_rename_map,
+                local_rename_map,
+                expr_map,
+                inputs,
+            );
+            arith::lower_builtin(b, n, e.size)
+        }
         ExprType::Mux(e1, e2, e3) => {
             let n1 = match &e1.value {
                 ExprTermType::Var(v) => var_to_state(
@@ -398,9 +491,9 @@ fn expr_to_state(
                     ..Default::default()
                 }),
             };
-            ExprOperation::Ram(n1, n2, n3, n4)
+            ExprOperation::Ram(e1.size, e4.size, n1, n2, n3, n4)
         }
-        ExprType::Rom(e) => {
+        ExprType::Rom(name, e) => {
             let n = match &e.value {
                 ExprTermType::Var(v) => var_to_state(
                     None,
@@ -416,7 +509,7 @@ fn expr_to_state(
                     ..Default::default()
                 }),
             };
-            ExprOperation::Rom(e.size, n)
+            ExprOperation::Rom(name.clone(), e.size, expr.size, n)
         }
         ExprType::Last(v) => ExprOperation::Last(*shared_rename_map.get(v).unwrap()),
     };
@@ -433,6 +526,31 @@ fn expr_to_state(
     state
 }
 
+fn term_to_state(
+    term: &ExprTerm,
+    state: &State,
+    shared_rename_map: &AHashMap<String, usize>,
+    local_rename_map: &AHashMap<String, usize>,
+    expr_map: &mut Option<AHashMap<usize, Rc<ExprNode>>>,
+    inputs: &mut Option<&mut Vec<usize>>,
+) -> Rc<ExprNode> {
+    match &term.value {
+        ExprTermType::Var(v) => var_to_state(
+            None,
+            state,
+            v,
+            shared_rename_map,
+            local_rename_map,
+            expr_map,
+            inputs,
+        ),
+        ExprTermType::Const(c) => Rc::new(ExprNode {
+            op: ExprOperation::Const(c.clone()),
+            ..Default::default()
+        }),
+    }
+}
+
 fn var_to_state(
     var_id: Option<usize>,
     state: &State,
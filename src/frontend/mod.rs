@@ -2,12 +2,15 @@
 The files in this folder are called in the following order :
 -parser_wrapper/parser.lalrpop (don't look at parser.rs, it is auto-generated)
 -constants.rs
--hierarchical_automata.rs
--nested_expr.rs
--functions.rs (these and hierarchical automata will be swapped at some point,
-as it is necessary to make automata in functions work)
+-dead_code.rs (only warnings, it is used again on the flat graph at the end)
+-nested_expr.rs (after the transitions are made explicit by hierarchical_automata.rs)
+-functions.rs
+-conditionals.rs
+-hierarchical_automata.rs (after the functions, as they can use automata)
 -typing.rs
 -make_graph_automaton.rs (this file will be removed and integrated in the next one someday)
+ (it uses arith.rs to lower the arithmetic operations to gates)
+-concurrency.rs
 (optional : from_netlist/parser.lalrpop)
 -automaton.rs
 
@@ -16,8 +19,12 @@ At the beginning of each file, there is a description of what it does.
 (schedule.rs is unused)
 */
 
+pub(crate) mod arith;
 pub(crate) mod automaton;
+pub(crate) mod concurrency;
+pub(crate) mod conditionals;
 pub(crate) mod constants;
+pub(crate) mod dead_code;
 pub(crate) mod from_netlist;
 pub(crate) mod functions;
 pub(crate) mod hierarchical_automata;
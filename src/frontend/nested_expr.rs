@@ -42,6 +42,7 @@ pub fn flatten(prog: &mut Program) {
             let State {
                 name,
                 weak: _,
+                parent: _,
                 statements,
                 transitions,
             } = state;
@@ -140,8 +141,25 @@ fn flatten_statement(statement: Statement) -> Vec<Statement> {
             res.push(Statement::FnAssign(fn_assign));
             res
         }
-        Statement::ExtAutomaton(_) => {
-            panic!("Should not happen: nested automaton after they are collapsed")
+        Statement::ExtAutomaton(mut ext_automaton) => {
+            //the inputs are flattened like the arguments of functions
+            let pos = ext_automaton.inputs.loc;
+            let automaton_name = ext_automaton.name.value.clone();
+            let mut res = Vec::new();
+            ext_automaton.inputs = Loc::new(
+                pos,
+                ext_automaton
+                    .inputs
+                    .drain(..)
+                    .map(|a| {
+                        let (mut stmts, e_out) = flatten_expr(&automaton_name, a.clone());
+                        res.append(&mut stmts);
+                        Loc::new(a.loc, e_out)
+                    })
+                    .collect::<Vec<Loc<Expr>>>(),
+            );
+            res.push(Statement::ExtAutomaton(ext_automaton));
+            res
         }
     }
 }
@@ -234,6 +252,27 @@ fn flatten_expr(name: &String, expr: Loc<Expr>) -> (Vec<Statement>, Expr) {
             }]));
             Expr::Var(name)
         }
+        Expr::Arith(op, e1, e2) => {
+            let pos1 = e1.loc;
+            let pos2 = e2.loc;
+            let (mut v1, e_out1) = flatten_expr(name, *e1);
+            let (mut v2, e_out2) = flatten_expr(name, *e2);
+            let name = Loc::new(pos1, get_name(name));
+            res.append(&mut v1);
+            res.append(&mut v2);
+            res.push(Statement::Assign(vec![VarAssign {
+                var: name.clone(),
+                expr: Loc::new(
+                    pos1,
+                    Expr::Arith(
+                        op,
+                        Box::new(Loc::new(pos1, e_out1)),
+                        Box::new(Loc::new(pos2, e_out2)),
+                    ),
+                ),
+            }]));
+            Expr::Var(name)
+        }
         Expr::Mux(e1, e2, e3) => {
             let pos1 = e1.loc;
             let pos2 = e2.loc;
@@ -268,6 +307,17 @@ fn flatten_expr(name: &String, expr: Loc<Expr>) -> (Vec<Statement>, Expr) {
             }]));
             Expr::Var(name)
         }
+        Expr::Builtin(b, e_in) => {
+            let pos = e_in.loc;
+            let (mut v, e_out) = flatten_expr(name, *e_in);
+            let name = Loc::new(expr.loc, get_name(name));
+            res.append(&mut v);
+            res.push(Statement::Assign(vec![VarAssign {
+                var: name.clone(),
+                expr: Loc::new(expr.loc, Expr::Builtin(b, Box::new(Loc::new(pos, e_out)))),
+            }]));
+            Expr::Var(name)
+        }
         Expr::Ram(RamStruct {
             read_addr: e1,
             write_enable: e2,
@@ -3,8 +3,8 @@ use std::path::PathBuf;
 use std::{fs::read_to_string, rc::Rc};
 
 use crate::ast::parse_ast::*;
-use ahash::AHashMap;
-use lalrpop_util::lexer::Token;
+use ahash::{AHashMap, AHashSet};
+use lalrpop_util::{lexer::Token, ErrorRecovery, ParseError};
 
 use self::parser::ProgramParser;
 use codespan_reporting::files::SimpleFiles;
@@ -13,6 +13,7 @@ pub enum ParseErrorType {
     Syntax(SyntaxError),
     UnexpectedEOF(usize),
     Other(String),
+    Invalid(usize, usize, String), //a token which is read, but has no meaning, like a number too large
 }
 #[derive(Debug)]
 pub struct SyntaxError {
@@ -29,12 +30,21 @@ fn replace_regex(input: &mut Vec<String>) {
                 Some("Variable name".to_string())
             }
             s if *s == r###"r#"[0-9]+"#"###.to_string() => Some("Number".to_string()),
-            s if *s == r###"r#"[a-zA-Z_][a-zA-Z_0-9]*<"#"###.to_string() => Some(
-                "Function name + \"<\" (without a space between the name and \"<\") ".to_string(),
+            s if *s == STATIC_HEAD => Some(
+                "Function name + \"<static arguments>(\" (without a space between the name and \"<\") "
+                    .to_string(),
             ),
             s if *s == r###"r#"[a-zA-Z_][a-zA-Z_0-9]*\\("#"###.to_string() => Some(
                 "Function name + \"(\" (without a space between the name and \"(\") ".to_string(),
             ),
+            s if *s == r###"r#"0b[01_]+"#"###
+                || *s == r###"r#"0x[0-9a-fA-F_]+"#"###
+                || *s == r###"r#"'b[01_]+"#"###
+                || *s == r###"r#"'h[0-9a-fA-F_]+"#"###
+                || *s == r###"r#"'d[0-9_]+"#"### =>
+            {
+                Some("Literal".to_string())
+            }
             s if *s == r###"r#"import[ ]+\"[^/\\n\"]*(/[^/\\n\"]*)*\""#"###.to_string()
                 || *s == r###"r#"import[ ]+[^/\\n \"]*(/[^/\\n \"]*)*"#"### =>
             {
@@ -60,21 +70,21 @@ impl From<lalrpop_util::ParseError<usize, Token<'_>, &'static str>> for ParseErr
                 })
             }
             lalrpop_util::ParseError::UnrecognizedEOF {
-                /// The end of the final token
+                //The end of the final token
                 location,
 
-                /// The set of expected tokens: these names are taken from the
-                /// grammar and hence may not necessarily be suitable for
-                /// presenting to the user.
-                    expected: _,
+                //The set of expected tokens: these names are taken from the
+                //grammar and hence may not necessarily be suitable for
+                //presenting to the user.
+                expected: _,
             } => ParseErrorType::UnexpectedEOF(location),
             lalrpop_util::ParseError::UnrecognizedToken {
-                /// The unexpected token of type `T` with a span given by the two `L` values.
+                //The unexpected token of type `T` with a span given by the two `L` values.
                 token,
 
-                /// The set of expected tokens: these names are taken from the
-                /// grammar and hence may not necessarily be suitable for
-                /// presenting to the user.
+                //The set of expected tokens: these names are taken from the
+                //grammar and hence may not necessarily be suitable for
+                //presenting to the user.
                 mut expected,
             } => {
                 let (start, token, end) = token;
@@ -101,6 +111,90 @@ impl From<lalrpop_util::ParseError<usize, Token<'_>, &'static str>> for ParseErr
         }
     }
 }
+//how lalrpop writes the token of the calls with static arguments in the expected tokens
+const STATIC_HEAD: &str = r###"r#"[a-zA-Z_][a-zA-Z_0-9]*<([a-zA-Z_0-9+\\-*/, \\t]|\\(([^()\\n]|\\([^()\\n]*\\))*\\))*>\\("#"###;
+
+type StaticResult<'input, T> = Result<T, ParseError<usize, Token<'input>, &'static str>>;
+
+//The static arguments of a call are parsed from the token "name<args>(", between start and end.
+//The positions are the ones in the file.
+pub fn static_args<'input>(
+    file_id: usize,
+    errors: &mut Vec<ErrorRecovery<usize, Token<'input>, &'static str>>,
+    input: &'input str,
+    start: usize,
+    end: usize,
+) -> StaticResult<'input, Loc<Vec<Const>>> {
+    let args = parser::StaticArgsParser::new()
+        .parse(file_id, errors, &input[start..end])
+        .map_err(|e| static_error(e, input, start, end))?;
+    Ok(Loc::new(
+        (file_id, start, end),
+        args.into_iter().map(|c| shift_const(c, start)).collect(),
+    ))
+}
+
+//the static parameters of a declaration are names
+pub fn static_params<'input>(
+    file_id: usize,
+    errors: &mut Vec<ErrorRecovery<usize, Token<'input>, &'static str>>,
+    input: &'input str,
+    start: usize,
+    end: usize,
+) -> StaticResult<'input, Vec<String>> {
+    parser::StaticParamsParser::new()
+        .parse(file_id, errors, &input[start..end])
+        .map_err(|e| static_error(e, input, start, end))
+}
+
+//the end of the static arguments is the ">(" which closes them, not the end of the file
+fn static_error<'input>(
+    error: ParseError<usize, Token<'input>, &'static str>,
+    input: &'input str,
+    start: usize,
+    end: usize,
+) -> ParseError<usize, Token<'input>, &'static str> {
+    match error.map_location(|l| l + start) {
+        ParseError::UnrecognizedEOF { expected, .. } => ParseError::UnrecognizedToken {
+            token: (end, Token(0, &input[end..end + 2]), end + 2),
+            expected,
+        },
+        error => error,
+    }
+}
+
+fn shift_const(c: Const, offset: usize) -> Const {
+    let shift = |(file, l, r): Pos| (file, l + offset, r + offset);
+    match c {
+        Const::Value(v) => Const::Value(v),
+        Const::Var(v) => Const::Var(Loc::new(shift(v.loc), v.value)),
+        Const::BiOp(op, c1, c2) => Const::BiOp(
+            op,
+            Box::new(shift_const(*c1, offset)),
+            Box::new(Loc::new(shift(c2.loc), shift_const(c2.value, offset))),
+        ),
+    }
+}
+
+//reg and rom are read like calls with static arguments, they are keywords
+pub fn static_builtin(mut f: FnCall) -> Expr {
+    if f.static_args.len() != 1 || f.args.len() != 1 {
+        return Expr::FnCall(f);
+    }
+    let c = Loc::new(f.static_args.loc, f.static_args.value.pop().unwrap());
+    match f.name.as_str() {
+        "reg" => Expr::Reg(c, Box::new(f.args.value.pop().unwrap())),
+        "rom" => Expr::Rom(RomStruct {
+            word_size: c.value,
+            read_addr: Box::new(f.args.value.pop().unwrap()),
+        }),
+        _ => {
+            f.static_args.value.push(c.value);
+            Expr::FnCall(f)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct FileError {
     pub file: PathBuf,
@@ -132,37 +226,105 @@ impl From<(usize, ParseErrorType)> for ParserError {
         ParserError::Parse(err.0, err.1)
     }
 }
+//A statement with a syntax error is skipped until a token which can start a statement.
+//When the parser resynchronises in the middle of the broken line, the statement it starts there
+//is broken too : its error, before the first token of the next line, is not reported.
+//The errors come with the end of the tokens dropped to recover from them, if any.
+fn remove_cascading(
+    file: &str,
+    errors: Vec<(ParseErrorType, Option<usize>)>,
+) -> Vec<ParseErrorType> {
+    let mut kept = Vec::new();
+    let mut next_line: Option<usize> = None;
+    for (error, dropped_end) in errors {
+        if let ParseErrorType::Syntax(SyntaxError { l, .. }) = &error {
+            let cascading = next_line.is_some_and(|start| *l <= start);
+            next_line = next_line_start(file, dropped_end.unwrap_or(*l));
+            if cascading {
+                continue;
+            }
+        } else {
+            next_line = None;
+        }
+        kept.push(error)
+    }
+    kept
+}
+
+//the start of the first token of the next line, if the parser resynchronised in the middle of a line
+fn next_line_start(file: &str, resync: usize) -> Option<usize> {
+    let line_start = file[..resync].rfind('\n').map_or(0, |i| i + 1);
+    let line = file[resync..].lines().next().unwrap_or("");
+    if file[line_start..resync].trim().is_empty() || line.trim().is_empty() {
+        return None;
+    }
+    let rest = &file[resync + line.len()..];
+    Some(file.len() - rest.trim_start().len())
+}
+
 //horrible implementation with way too many clones, but it should still be fast enough for any use case.
 //this function parse the main file and every import.
+//the parser recovers from the syntax errors in a statement or at the top level of a file,
+//so every file is parsed to the end and all the errors are reported together.
 pub fn parse(
     main_path: PathBuf,
 ) -> Result<
     (Program, Rc<SimpleFiles<String, String>>),
-    (ParserError, Rc<SimpleFiles<String, String>>),
+    (Vec<ParserError>, Rc<SimpleFiles<String, String>>),
 > {
     let mut files = SimpleFiles::new();
+    let mut errors = Vec::new();
+    let mut visited = AHashSet::new();
     let mut prog_map = AHashMap::<PathBuf, Program>::new();
     let mut queue = Vec::<PathBuf>::new();
     queue.push(main_path.clone());
     while !queue.is_empty() {
         let path = queue.pop().unwrap();
-        if prog_map.contains_key(&path) {
+        //the files with errors are not in prog_map, but they must not be parsed twice either
+        if !visited.insert(path.clone()) {
             continue;
         }
-        let file = read_to_string(path.clone()).map_err(|e| {
-            (
-                FileError {
-                    file: path.clone(),
-                    error: format!("{}", e),
-                }
-                .into(),
-                Rc::new(files.clone()),
-            )
-        })?;
+        let file = match read_to_string(path.clone()) {
+            Ok(file) => file,
+            Err(e) => {
+                errors.push(
+                    FileError {
+                        file: path.clone(),
+                        error: format!("{}", e),
+                    }
+                    .into(),
+                );
+                continue;
+            }
+        };
         let file_id = files.add(path.to_string_lossy().to_string(), file.clone());
-        let mut prog = ProgramParser::new()
-            .parse(file_id, &file)
-            .map_err(|e| ((file_id, e.into()).into(), Rc::new(files.clone())))?;
+        let mut recovered = Vec::new();
+        let result = ProgramParser::new().parse(file_id, &mut recovered, &file);
+        let mut file_errors: Vec<(ParseErrorType, Option<usize>)> = recovered
+            .into_iter()
+            .map(|r| {
+                (
+                    r.error.into(),
+                    r.dropped_tokens.last().map(|(_, _, end)| *end),
+                )
+            })
+            .collect();
+        let prog = match result {
+            Ok(prog) => Some(prog),
+            Err(e) => {
+                file_errors.push((e.into(), None));
+                None
+            }
+        };
+        errors.extend(
+            remove_cascading(&file, file_errors)
+                .into_iter()
+                .map(|e| ParserError::from((file_id, e))),
+        );
+        let mut prog = match prog {
+            Some(prog) => prog,
+            None => continue,
+        };
         let imports = std::mem::take(&mut prog.imports);
         prog_map.insert(path.clone(), prog);
         let mut root_path = path.clone();
@@ -170,11 +332,14 @@ pub fn parse(
         for path_end in imports {
             let mut new_path = root_path.clone();
             new_path.push(path_end);
-            if !prog_map.contains_key(&new_path) {
+            if !visited.contains(&new_path) {
                 queue.push(new_path)
             }
         }
     }
+    if !errors.is_empty() {
+        return Err((errors, Rc::new(files)));
+    }
     let mut main_program = prog_map.remove(&main_path).unwrap();
     for (_, prog) in prog_map {
         for (name, func) in prog.functions {
@@ -198,7 +363,7 @@ mod tests {
     #[test]
     fn test_parser() {
         let file = read_to_string("src/tests/parser/pass/test.tj").unwrap();
-        let prog = ProgramParser::new().parse(0, &file);
+        let prog = ProgramParser::new().parse(0, &mut Vec::new(), &file);
         //println!("{:#?}", prog);
         assert!(prog.is_ok());
     }
@@ -217,8 +382,9 @@ mod tests {
     #[test]
     fn test_fail() {
         let file = read_to_string("src/tests/parser/fail/test.tj").unwrap();
-        let prog = ProgramParser::new().parse(0, &file);
+        let mut errors = Vec::new();
+        let prog = ProgramParser::new().parse(0, &mut errors, &file);
         //println!("{:#?}", prog);
-        assert!(prog.is_err());
+        assert!(prog.is_err() || !errors.is_empty());
     }
 }
@@ -1,4 +1,4 @@
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 
 use crate::ast::parse_ast as untyp;
 use crate::ast::typed_ast as typ;
@@ -10,13 +10,19 @@ This file aims to type the program.
 This is mostly veryfying all operations are between expressions of the right size, and attributing a bus size
 to every expression.
 
-It also transforms the ast in a typed_ast, which is a lot simpler and without location information.
+It also transforms the ast in a typed_ast, which is a lot simpler. The only location information kept
+is where the variables are declared and assigned, which follows the graph down to the backends :
+they write it in comments before the nodes of the named variables.
 
 Scoping is also done in this file. It just means shared variables are particularized.
 
-The compiler / interpreter must fail as little as possible after this point (as there are no more localisation info)
+The compiler / interpreter must fail as little as possible after this point (as the expressions themselves are no longer located)
+
+Every error is reported, not only the first one : the declarations, states and statements are typed independently.
+When a statement cannot be typed, its variable is unknown to the following ones, so the unknown variable
+errors it causes are not reported again.
 */
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum TypingError {
     NegativeSizeBus(Pos, i32),
     MismatchedBusSize(Token, Token), //expected X, got X
@@ -25,11 +31,18 @@ pub enum TypingError {
     UnknownState(String, Pos),
     ExpectedSizeOne(Pos, usize),
     IndexOutOfRange(Pos, i32, usize),
-    LocalVarInUnless(Pos, String), //unless is not fully implemented
     NonSharedInLast(Pos, String),
     ConflictingStateShared(Pos, String, Pos), //shared variables and states cannot have conflicting names
+    LiteralOverflow(Pos, usize), //the value of a sized literal does not fit in its width
+    SingleBitAdd(Pos), //+ used to be the or, so it is an error on single bits rather than a xor
 }
+//the + on buses used to be the or, and is now the addition : each one is reported, so the old designs
+//which used it as the or are not silently changed
 #[derive(Debug)]
+pub enum TypingWarning {
+    BusAdd(Pos),
+}
+#[derive(Debug, PartialEq)]
 pub struct Token {
     pub loc: Pos,
     pub name: Option<String>,
@@ -37,15 +50,28 @@ pub struct Token {
 }
 type Result<T> = std::result::Result<T, TypingError>;
 
+//the values which could be typed. The errors are kept to be reported together
+fn collect_errors<T, C: std::iter::FromIterator<T>>(
+    results: impl Iterator<Item = Result<T>>,
+    errors: &mut Vec<TypingError>,
+) -> C {
+    results
+        .filter_map(|r| r.map_err(|e| errors.push(e)).ok())
+        .collect()
+}
+
 //the main wrapper function
 pub fn type_prog(
     mut prog: untyp::Program,
     mut type_constraints: AHashMap<String, (i32, Pos)>,
-) -> Result<typ::Program> {
+) -> std::result::Result<typ::Program, Vec<TypingError>> {
+    let mut errors = Vec::new();
     let mut shared_types: AHashMap<String, (usize, Pos)> = AHashMap::new();
+    //the declarations of the shared variables are kept to report the combinational cycles
+    let mut locations = AHashMap::new();
     let main_module = prog.automata.get_mut("main").unwrap();
     //build the map of shared variables, and type them as well.
-    let mut shared_map = main_module
+    let shared_map = main_module
         .shared
         .drain(..)
         .map(|untyp::VarAssign { var, expr }| {
@@ -63,6 +89,7 @@ pub fn type_prog(
                             panic!("Should not happen : unknown const in typing");
                         }
                     }
+                    untyp::ConstExpr::Sized(width, literal) => sized_literal(width, literal)?,
                 };
                 if let Some((_, other_loc)) = shared_types.get(&var.value) {
                     return Err(TypingError::DuplicateVar(
@@ -72,149 +99,272 @@ pub fn type_prog(
                     ));
                 }
                 shared_types.insert(var.value.clone(), (v.len(), loc));
+                locations.insert(var.value.clone(), var.loc);
                 Ok((var.value, v))
             } else {
                 panic!("Should not happen : non-value constant encountered while typing")
             }
-        })
-        .collect::<Result<AHashMap<String, Vec<bool>>>>()?;
+        });
+    let mut shared_map: AHashMap<String, Vec<bool>> = collect_errors(shared_map, &mut errors);
     //States name are shared variable which indicated the state of the state (running or not)
     for (name, state) in main_module.states.iter() {
         if shared_map.contains_key(name) {
             let (_, loc) = shared_types.get(name).unwrap();
-            return Err(TypingError::ConflictingStateShared(
+            errors.push(TypingError::ConflictingStateShared(
                 state.name.loc,
                 state.name.value.clone(),
                 *loc,
             ));
+        } else {
+            shared_types.insert(name.clone(), (1, state.name.loc));
         }
-        shared_types.insert(name.clone(), (1, state.name.loc));
     }
     //inputs are added as shared variables.
     //the types are also added in a vector for module typing.
     let mut in_types = Vec::new();
-    let inputs = main_module
-        .inputs
-        .drain(..)
-        .map(|arg| {
-            let loc = arg.size.loc;
-            if let untyp::Const::Value(i) = arg.size.value {
-                let j = usize::try_from(i).map_err(|_| TypingError::NegativeSizeBus(loc, i))?;
-                in_types.push((j, loc));
-                if let Some((_, other_loc)) = shared_types.get(&arg.name) {
-                    return Err(TypingError::DuplicateVar(arg.name.clone(), loc, *other_loc));
-                }
-                shared_types.insert(arg.name.clone(), (j, loc));
-                Ok(typ::Sized {
-                    value: arg.name,
-                    size: j,
-                })
-            } else {
-                panic!("Should not happen : unknown const in typing");
+    let inputs = main_module.inputs.drain(..).map(|arg| {
+        let loc = arg.size.loc;
+        if let untyp::Const::Value(i) = arg.size.value {
+            let j = usize::try_from(i).map_err(|_| TypingError::NegativeSizeBus(loc, i))?;
+            in_types.push((j, loc));
+            if let Some((_, other_loc)) = shared_types.get(&arg.name) {
+                return Err(TypingError::DuplicateVar(arg.name.clone(), loc, *other_loc));
             }
-        })
-        .collect::<Result<Vec<typ::Sized<String>>>>()?;
+            shared_types.insert(arg.name.clone(), (j, loc));
+            Ok(typ::Sized {
+                value: arg.name,
+                size: j,
+            })
+        } else {
+            panic!("Should not happen : unknown const in typing");
+        }
+    });
+    let inputs = collect_errors(inputs, &mut errors);
 
     //outputs can be shared variables or inputs.
     //the types are also added in a vector for use in external module typing.
     let mut out_types = Vec::new();
-    let outputs = main_module
-        .outputs
-        .drain(..)
-        .map(|arg| {
-            let loc = arg.size.loc;
-            if let untyp::Const::Value(i) = arg.size.value {
-                let j = usize::try_from(i).map_err(|_| TypingError::NegativeSizeBus(loc, i))?;
-                out_types.push((j, loc));
-                if let Some((i_decl, loc_decl)) = shared_types.get(&arg.name) {
-                    if *i_decl != j {
-                        let token1 = Token {
-                            loc: *loc_decl,
-                            name: Some(format_var(arg.name.clone())),
-                            length: *i_decl,
-                        };
-                        let token2 = Token {
-                            loc,
-                            name: Some(format_var(arg.name.clone())),
-                            length: j,
-                        };
-                        Err(TypingError::MismatchedBusSize(token1, token2))
-                    } else {
-                        Ok(typ::Sized {
-                            value: arg.name,
-                            size: j,
-                        })
-                    }
+    let outputs = main_module.outputs.drain(..).map(|arg| {
+        let loc = arg.size.loc;
+        if let untyp::Const::Value(i) = arg.size.value {
+            let j = usize::try_from(i).map_err(|_| TypingError::NegativeSizeBus(loc, i))?;
+            out_types.push((j, loc));
+            if let Some((i_decl, loc_decl)) = shared_types.get(&arg.name) {
+                if *i_decl != j {
+                    let token1 = Token {
+                        loc: *loc_decl,
+                        name: Some(format_var(arg.name.clone())),
+                        length: *i_decl,
+                    };
+                    let token2 = Token {
+                        loc,
+                        name: Some(format_var(arg.name.clone())),
+                        length: j,
+                    };
+                    Err(TypingError::MismatchedBusSize(token1, token2))
                 } else {
-                    shared_map.insert(arg.name.clone(), vec![false; j]);
-                    shared_types.insert(arg.name.clone(), (j, loc));
                     Ok(typ::Sized {
                         value: arg.name,
                         size: j,
                     })
                 }
             } else {
-                panic!("Should not happen : unknown const in typing");
+                shared_map.insert(arg.name.clone(), vec![false; j]);
+                shared_types.insert(arg.name.clone(), (j, loc));
+                Ok(typ::Sized {
+                    value: arg.name,
+                    size: j,
+                })
             }
-        })
-        .collect::<Result<Vec<typ::Sized<String>>>>()?;
+        } else {
+            panic!("Should not happen : unknown const in typing");
+        }
+    });
+    let outputs = collect_errors(outputs, &mut errors);
     let states_map = main_module
         .states
         .iter()
         .map(|(_, state)| (state.name.to_string(), state.name.loc))
         .collect::<AHashMap<String, Pos>>();
     //If init states were specified, use them. Else, use the first state.
-    let init_states = main_module
-        .init_states
-        .drain(..)
-        .map(|s| {
+    let init_states = collect_errors(
+        main_module.init_states.drain(..).map(|s| {
             if states_map.contains_key(&s.value) {
                 Ok(s.value)
             } else {
                 Err(TypingError::UnknownState(s.value, s.loc))
             }
-        })
-        .collect::<Result<Vec<String>>>()?;
-    let states = main_module
-        .states
-        .drain()
-        .map(|(_, state)| {
-            Ok((
+        }),
+        &mut errors,
+    );
+    //the states are typed in the order of the source, and so are their errors
+    let mut untyped_states: Vec<untyp::State> =
+        main_module.states.drain().map(|(_, state)| state).collect();
+    untyped_states.sort_by_key(|state| state.name.loc);
+    let states = untyped_states
+        .into_iter()
+        .map(|state| {
+            (
                 state.name.value.clone(),
-                type_state(state, &states_map, &shared_types, &mut type_constraints)?,
-            ))
+                type_state(
+                    state,
+                    &states_map,
+                    &shared_types,
+                    &mut type_constraints,
+                    &mut errors,
+                ),
+            )
         })
-        .collect::<Result<AHashMap<typ::Name, typ::State>>>()?;
+        .collect::<AHashMap<typ::Name, typ::State>>();
+    if !errors.is_empty() {
+        //the functions inlined several times give the same errors each time
+        let mut unique = Vec::new();
+        for error in errors {
+            if !unique.contains(&error) {
+                unique.push(error)
+            }
+        }
+        return Err(unique);
+    }
+    //the states, inputs and outputs are located with their type
+    for (name, (_, loc)) in shared_types {
+        locations.entry(name).or_insert(loc);
+    }
     Ok(typ::Program {
         inputs,
         outputs,
         states,
         shared: shared_map,
         init_states,
+        locations,
     })
 }
 
-//type a state. It has to type all the statements and transitions
+//the + of the program, in the order of the files. They are found before the functions are inlined,
+//so a + in a function is reported once. The ones on single bits are typing errors.
+pub fn find_bus_additions(prog: &untyp::Program) -> Vec<TypingWarning> {
+    let mut found = Vec::new();
+    for automaton in prog.automata.values() {
+        for state in automaton.states.values() {
+            statements_additions(&state.statements, &mut found);
+            for transition in &state.transitions {
+                if let untyp::TrCond::Expr(e) = &transition.condition.value {
+                    expr_additions(e, &mut found)
+                }
+            }
+        }
+    }
+    for function in prog.functions.values() {
+        statements_additions(&function.statements, &mut found)
+    }
+    found.sort_unstable();
+    found.into_iter().map(TypingWarning::BusAdd).collect()
+}
+
+fn statements_additions(statements: &[untyp::Statement], found: &mut Vec<Pos>) {
+    for statement in statements {
+        match statement {
+            untyp::Statement::Assign(assigns) => {
+                for assign in assigns {
+                    expr_additions(&assign.expr, found)
+                }
+            }
+            untyp::Statement::If(untyp::IfStruct {
+                if_block,
+                else_block,
+                ..
+            }) => {
+                statements_additions(if_block, found);
+                statements_additions(else_block, found)
+            }
+            untyp::Statement::FnAssign(untyp::FnAssign { f, .. }) => {
+                for arg in f.args.iter() {
+                    expr_additions(arg, found)
+                }
+            }
+            untyp::Statement::ExtAutomaton(e) => {
+                for input in e.inputs.iter() {
+                    expr_additions(input, found)
+                }
+            }
+        }
+    }
+}
+
+fn expr_additions(expr: &untyp::Expr, found: &mut Vec<Pos>) {
+    match expr {
+        untyp::Expr::Var(_) | untyp::Expr::Last(_) | untyp::Expr::Const(_) => (),
+        untyp::Expr::Not(e) => expr_additions(e, found),
+        untyp::Expr::Slice(e, _, _) | untyp::Expr::Reg(_, e) | untyp::Expr::Builtin(_, e) => {
+            expr_additions(e, found)
+        }
+        untyp::Expr::BiOp(_, e1, e2) => {
+            expr_additions(e1, found);
+            expr_additions(e2, found)
+        }
+        untyp::Expr::Arith(op, e1, e2) => {
+            if *op == untyp::ArithOp::Add {
+                found.push((e1.loc.0, e1.loc.1, e2.loc.2))
+            }
+            expr_additions(e1, found);
+            expr_additions(e2, found)
+        }
+        untyp::Expr::Mux(e1, e2, e3) => {
+            expr_additions(e1, found);
+            expr_additions(e2, found);
+            expr_additions(e3, found)
+        }
+        untyp::Expr::Ram(untyp::RamStruct {
+            read_addr,
+            write_enable,
+            write_addr,
+            write_data,
+        }) => {
+            expr_additions(read_addr, found);
+            expr_additions(write_enable, found);
+            expr_additions(write_addr, found);
+            expr_additions(write_data, found)
+        }
+        untyp::Expr::Rom(untyp::RomStruct { read_addr, .. }) => expr_additions(read_addr, found),
+        untyp::Expr::FnCall(f) => {
+            for arg in f.args.iter() {
+                expr_additions(arg, found)
+            }
+        }
+    }
+}
+
+//type a state. It has to type all the statements and transitions.
+//the errors are added to the others, and the state is only used if there are none
 fn type_state(
     mut state: untyp::State,
     states_map: &AHashMap<String, Pos>,
     shared_types: &AHashMap<String, (usize, Pos)>,
     type_constraints: &mut AHashMap<String, (i32, Pos)>,
-) -> Result<typ::State> {
+    errors: &mut Vec<TypingError>,
+) -> typ::State {
     let mut var_types: AHashMap<String, (usize, Pos)> = AHashMap::new();
-    let statements = state
-        .statements
-        .drain(..)
-        .map(|s| {
-            Ok(type_statement(
-                s,
-                &mut var_types,
-                &shared_types,
-                type_constraints,
-            )?)
-        })
-        .collect::<Result<AHashMap<typ::Var, typ::Expr>>>()?;
-    let weak = state.weak;
+    let mut locations = AHashMap::new();
+    let mut statements = AHashMap::new();
+    //the variables of the statements which could not be typed
+    let mut failed = AHashSet::new();
+    for s in state.statements.drain(..) {
+        let assigned = match &s {
+            untyp::Statement::Assign(assigns) => assigns.first().map(|a| a.var.value.clone()),
+            _ => None,
+        };
+        match type_statement(s, &mut var_types, shared_types, type_constraints) {
+            Ok((var, expr, loc)) => {
+                locations.insert(var.clone(), loc);
+                statements.insert(var, expr);
+            }
+            Err(TypingError::UnknownVar(v, _)) if failed.contains(&v) => failed.extend(assigned),
+            Err(e) => {
+                errors.push(e);
+                failed.extend(assigned)
+            }
+        }
+    }
     let transitions = state
         .transitions
         .drain(..)
@@ -229,9 +379,6 @@ fn type_state(
                     ));
                 }
                 if let Some((size, _loc)) = var_types.get(&s.value) {
-                    if weak == false {
-                        return Err(TypingError::LocalVarInUnless(s.loc, s.value.clone()));
-                    }
                     if *size != 1 {
                         return Err(TypingError::ExpectedSizeOne(s.loc, *size));
                     }
@@ -239,6 +386,7 @@ fn type_state(
                         typ::Var::Local(s.value.clone()),
                         transition.state.value,
                         transition.reset,
+                        transition.instant,
                     ))
                 } else {
                     if let Some((size, _loc)) = shared_types.get(&s.value) {
@@ -249,6 +397,7 @@ fn type_state(
                             typ::Var::Shared(s.value.clone()),
                             transition.state.value,
                             transition.reset,
+                            transition.instant,
                         ))
                     } else {
                         Err(TypingError::UnknownVar(s.value.clone(), s.loc))
@@ -258,22 +407,24 @@ fn type_state(
                 panic!("Should not happen : Expected a variable in transition")
             }
         })
-        .collect::<Result<Vec<(typ::Var, Option<typ::Name>, bool)>>>()?;
-    Ok(typ::State {
-        transitions,
+        .filter(|r| !matches!(r, Err(TypingError::UnknownVar(v, _)) if failed.contains(v)));
+    typ::State {
+        transitions: collect_errors(transitions, errors),
         name: state.name.value,
         weak: state.weak,
+        parent: state.parent,
         statements,
-    })
+        locations,
+    }
 }
 
-//type a statement, not much to say here.
+//type a statement, not much to say here. The position of the assigned variable is kept.
 fn type_statement(
     statement: untyp::Statement,
     var_types: &mut AHashMap<String, (usize, Pos)>,
     shared_types: &AHashMap<String, (usize, Pos)>,
     type_constraints: &mut AHashMap<String, (i32, Pos)>,
-) -> Result<(typ::Var, typ::Expr)> {
+) -> Result<(typ::Var, typ::Expr, Pos)> {
     match statement {
         untyp::Statement::Assign(mut var_assigns) => {
             assert_eq!(
@@ -282,12 +433,16 @@ fn type_statement(
                 "Should not happen : Var assign of size different from 1"
             );
             let untyp::VarAssign { var, expr } = var_assigns.drain(..).next().unwrap();
-            let sized_expr = type_expr(expr.value, var_types, shared_types, type_constraints)?;
+            let mut sized_expr = type_expr(expr.value, var_types, shared_types, type_constraints)?;
+            //roms are named after the variable they are assigned to, so their content can be loaded
+            if let typ::ExprType::Rom(name, _) = &mut sized_expr.value {
+                *name = source_name(&var.value);
+            }
             if let Some((_, loc)) = var_types.get(&var.value) {
                 Err(TypingError::DuplicateVar(var.value, var.loc, *loc))
             } else if let Some((size, loc)) = shared_types.get(&var.value) {
                 if *size == sized_expr.size {
-                    Ok((typ::Var::Shared(var.value), sized_expr))
+                    Ok((typ::Var::Shared(var.value), sized_expr, var.loc))
                 } else {
                     let token1 = Token {
                         loc: *loc,
@@ -306,7 +461,7 @@ fn type_statement(
                     .map_err(|_| TypingError::NegativeSizeBus(*loc, *size))?;
                 if size_u == sized_expr.size {
                     var_types.insert(var.value.clone(), (sized_expr.size, var.loc));
-                    Ok((typ::Var::Local(var.value), sized_expr))
+                    Ok((typ::Var::Local(var.value), sized_expr, var.loc))
                 } else {
                     let token1 = Token {
                         loc: *loc,
@@ -322,7 +477,7 @@ fn type_statement(
                 }
             } else {
                 var_types.insert(var.value.clone(), (sized_expr.size, var.loc));
-                Ok((typ::Var::Local(var.value), sized_expr))
+                Ok((typ::Var::Local(var.value), sized_expr, var.loc))
             }
         }
         _ => panic!(format!(
@@ -426,6 +581,59 @@ fn type_expr(
                 }
             }
         }
+        untyp::Expr::Arith(op, e1, e2) => {
+            let loc1 = e1.loc;
+            let loc2 = e2.loc;
+            let sized_e1 = type_expr_term(e1.value, var_types, shared_types, type_constraints)?;
+            let sized_e2 = type_expr_term(e2.value, var_types, shared_types, type_constraints)?;
+            //the shifts can be by a bus of any size
+            let shift = matches!(op, untyp::ArithOp::Shl | untyp::ArithOp::Shr);
+            if !shift && sized_e1.size != sized_e2.size {
+                let token1 = Token {
+                    loc: loc1,
+                    name: None,
+                    length: sized_e1.size,
+                };
+                let token2 = Token {
+                    loc: loc2,
+                    name: None,
+                    length: sized_e2.size,
+                };
+                Err(TypingError::MismatchedBusSize(token1, token2))
+            } else if op == untyp::ArithOp::Add && sized_e1.size == 1 {
+                Err(TypingError::SingleBitAdd((loc1.0, loc1.1, loc2.2)))
+            } else {
+                let size = match op {
+                    untyp::ArithOp::Eq | untyp::ArithOp::Neq | untyp::ArithOp::Lt => 1,
+                    _ => sized_e1.size,
+                };
+                Ok(typ::Sized {
+                    size,
+                    value: typ::ExprType::Arith(op, sized_e1, sized_e2),
+                })
+            }
+        }
+        untyp::Expr::Builtin(b, e) => {
+            let sized_e = type_expr_term(e.value, var_types, shared_types, type_constraints)?;
+            let (size, b) = match b {
+                untyp::Builtin::Repeat(c) => {
+                    if let untyp::Const::Value(i) = c.value {
+                        let n = usize::try_from(i)
+                            .map_err(|_| TypingError::NegativeSizeBus(c.loc, i))?;
+                        (n * sized_e.size, typ::Builtin::Repeat(n))
+                    } else {
+                        panic!("Should not happen : unknown const while typing")
+                    }
+                }
+                //the reduction of an empty bus is the neutral element of the operation
+                untyp::Builtin::Reduce(op) => (1, typ::Builtin::Reduce(op)),
+                untyp::Builtin::Reverse => (sized_e.size, typ::Builtin::Reverse),
+            };
+            Ok(typ::Sized {
+                size,
+                value: typ::ExprType::Builtin(b, sized_e),
+            })
+        }
         untyp::Expr::Mux(e1, e2, e3) => {
             let loc1 = e1.loc;
             let loc2 = e2.loc;
@@ -541,9 +749,10 @@ fn type_expr(
                 type_expr_term(read_addr.value, var_types, shared_types, type_constraints)?;
             if let untyp::Const::Value(i) = word_size {
                 let j = usize::try_from(i).map_err(|_| TypingError::NegativeSizeBus(loc, i))?;
+                //the name is filled in by type_statement, which knows the assigned variable
                 Ok(typ::Sized {
                     size: j,
-                    value: typ::ExprType::Rom(sized_expr),
+                    value: typ::ExprType::Rom(String::new(), sized_expr),
                 })
             } else {
                 panic!("Should not happen : unknown const while typing")
@@ -552,6 +761,35 @@ fn type_expr(
         untyp::Expr::FnCall(_) => panic!("Should not happen : fn call in typing"),
     }
 }
+//the bits of a literal with an explicit width. The value must fit in the width : the decimal values
+//of n bits go from -2^(n-1) to 2^n - 1, the negative ones being written in two's complement
+//(2'd(0-2) is 10, 2'd(0-3) is an error), as n'd(0-1) is the usual way to write n ones.
+fn sized_literal(
+    width: untyp::Loc<untyp::Const>,
+    literal: untyp::Loc<untyp::Literal>,
+) -> Result<Vec<bool>> {
+    let w = if let untyp::Const::Value(i) = width.value {
+        usize::try_from(i).map_err(|_| TypingError::NegativeSizeBus(width.loc, i))?
+    } else {
+        panic!("Should not happen : unknown const while typing")
+    };
+    match literal.value {
+        untyp::Literal::Bits(v) if v.iter().rev().skip(w).all(|b| !b) => {
+            let mut bits = vec![false; w.saturating_sub(v.len())];
+            bits.extend(v.iter().skip(v.len().saturating_sub(w)));
+            Ok(bits)
+        }
+        untyp::Literal::Num(untyp::Const::Value(i))
+            if w >= 32 || (i >= 0 && i >> w == 0) || (i < 0 && w > 0 && i >> (w - 1) == -1) =>
+        {
+            Ok((0..w).rev().map(|k| (i >> k.min(31)) & 1 == 1).collect())
+        }
+        untyp::Literal::Bits(_) | untyp::Literal::Num(untyp::Const::Value(_)) => {
+            Err(TypingError::LiteralOverflow(literal.loc, w))
+        }
+        untyp::Literal::Num(_) => panic!("Should not happen : unknown const while typing"),
+    }
+}
 //type an expression that the compiler assume will be constant or a variable
 fn type_expr_term(
     expr: untyp::Expr,
@@ -577,6 +815,13 @@ fn type_expr_term(
                     panic!("Should not happen : unknown const while typing")
                 }
             }
+            untyp::ConstExpr::Sized(width, literal) => {
+                let v = sized_literal(width, literal)?;
+                Ok(typ::Sized {
+                    size: v.len(),
+                    value: typ::ExprTermType::Const(v),
+                })
+            }
         },
         untyp::Expr::Var(v) => {
             if let Some((size, _loc)) = var_types.get(&v.value) {
@@ -626,6 +871,10 @@ fn type_expr_term_reg(
                     panic!("Should not happen : unknown const while typing")
                 }
             }
+            untyp::ConstExpr::Sized(width, literal) => {
+                let v = sized_literal(width, literal)?;
+                Ok((Some(v.len()), typ::ExprTermType::Const(v)))
+            }
         },
         untyp::Expr::Var(v) => {
             if let Some((size, _loc)) = var_types.get(&v.value) {
@@ -655,6 +904,13 @@ fn type_expr_term_reg(
 //from the variable name, it can retrace in which function with which arguments it was.
 //TODO : make it work the same, but without the ugly hack.
 fn format_var(var: String) -> String {
+    //the variables of functions inlined in a module are renamed again with the module
+    if let Some(inner) = var
+        .strip_prefix("inline_mod$")
+        .filter(|v| v.starts_with('$'))
+    {
+        return format_var(inner.to_string());
+    }
     if var.starts_with('$') {
         let vec: Vec<&str> = var.split('$').filter(|s| *s != "").collect();
         let _typ = vec[0];
@@ -672,6 +928,18 @@ fn format_var(var: String) -> String {
     }
 }
 
+//from a variable name, retrieves the name as it was written in the source,
+//without the renaming done by function and automaton inlining.
+pub fn source_name(var: &str) -> String {
+    let var = var.strip_prefix("inline_mod$").unwrap_or(var);
+    let name = if var.starts_with('$') {
+        var.split('$').nth(4).unwrap_or(var)
+    } else {
+        var.split('$').next().unwrap()
+    };
+    name.split('#').next().unwrap().to_string()
+}
+
 fn format_args(args: &str) -> String {
     args.split('|')
         .filter(|s| *s != "")
@@ -4,6 +4,7 @@ use std::{
 };
 
 use crate::ast::graph::*;
+use crate::util::{rom::RomImages, vcd::VcdWriter};
 
 pub struct InterpreterIterator<'a> {
     graph: &'a FlatProgramGraph,
@@ -12,6 +13,11 @@ pub struct InterpreterIterator<'a> {
     ram: Arc<Mutex<HashMap<Vec<bool>, Vec<bool>>>>,
     mem: HashMap<RCell<Node>, Vec<bool>>,
     inputs: Box<dyn FnMut() -> Vec<Vec<bool>>>,
+    roms: RomImages,
+    vcd: Option<VcdWriter>,
+    vcd_probes: bool,
+    cycle: usize,
+    double_writes: Vec<(usize, String)>,
 }
 
 impl<'a> Iterator for InterpreterIterator<'a> {
@@ -22,34 +28,104 @@ impl<'a> Iterator for InterpreterIterator<'a> {
         }
         let inputs = (self.inputs)();
         self.mem.clear();
-        Some(
-            self.graph
-                .outputs
-                .iter()
-                .map(|(s, i)| {
-                    (
-                        s,
-                        get_value(
-                            i,
-                            &self.reg_map,
-                            &mut self.next_reg_map,
-                            &mut self.mem,
-                            &inputs,
-                            self.ram.clone(),
-                        ),
-                    )
-                })
-                .collect(),
-        )
+        let outputs: Vec<(&String, Vec<bool>)> = self
+            .graph
+            .outputs
+            .iter()
+            .map(|(s, i)| {
+                (
+                    s,
+                    get_value(
+                        i,
+                        &self.reg_map,
+                        &mut self.next_reg_map,
+                        &mut self.mem,
+                        &inputs,
+                        self.ram.clone(),
+                        &self.roms,
+                    ),
+                )
+            })
+            .collect();
+        //the shared variables which may be assigned by two states at the same time
+        for (name, s1, s2) in &self.graph.conflicts {
+            let both_active = [s1, s2].iter().all(|node| {
+                get_value(
+                    node,
+                    &self.reg_map,
+                    &mut self.next_reg_map,
+                    &mut self.mem,
+                    &inputs,
+                    self.ram.clone(),
+                    &self.roms,
+                ) == vec![true]
+            });
+            if both_active {
+                self.double_writes.push((self.cycle, name.clone()));
+            }
+        }
+        self.cycle += 1;
+        if let Some(vcd) = self.vcd.as_mut() {
+            //the probes are computed after the outputs, so they see the same values
+            let mut values = inputs.clone();
+            values.extend(outputs.iter().map(|(_, v)| v.clone()));
+            if self.vcd_probes {
+                for (_, node) in &self.graph.probes {
+                    values.push(get_value(
+                        node,
+                        &self.reg_map,
+                        &mut self.next_reg_map,
+                        &mut self.mem,
+                        &inputs,
+                        self.ram.clone(),
+                        &self.roms,
+                    ));
+                }
+            }
+            vcd.step(&values).expect("Could not write the vcd file");
+        }
+        Some(outputs)
     }
 }
 
+impl<'a> InterpreterIterator<'a> {
+    //Records the inputs and outputs of every following cycle in the VCD format.
+    //If probes is true, the named shared variables and the states are recorded too.
+    pub fn record_vcd(&mut self, dest: Box<dyn std::io::Write>, probes: bool) -> std::io::Result<()> {
+        let mut signals: Vec<(String, usize)> = self.graph.inputs.clone();
+        let input_sizes = self.graph.input_sizes();
+        for (name, node) in &self.graph.outputs {
+            signals.push((name.clone(), node_size(node, &input_sizes)));
+        }
+        if probes {
+            for (name, node) in &self.graph.probes {
+                signals.push((name.clone(), node_size(node, &input_sizes)));
+            }
+        }
+        self.vcd = Some(VcdWriter::new(dest, &signals)?);
+        self.vcd_probes = probes;
+        Ok(())
+    }
+
+    pub fn finish_vcd(&mut self) -> std::io::Result<()> {
+        match &mut self.vcd {
+            Some(vcd) => vcd.finish(),
+            None => Ok(()),
+        }
+    }
+
+    //the cycles where a shared variable was assigned by two active states, and its name
+    pub fn double_writes(&self) -> &[(usize, String)] {
+        &self.double_writes
+    }
+}
+
+//inputs gives the inputs of each cycle
 pub fn interprete<'a>(
     graph: &'a FlatProgramGraph,
-    inputs_script_path: Option<String>,
+    inputs: Box<dyn FnMut() -> Vec<Vec<bool>>>,
+    roms: RomImages,
 ) -> InterpreterIterator {
-    let inputs =
-        crate::util::scripting::get_inputs_closure(inputs_script_path, graph.inputs.clone());
     let ram = Arc::new(Mutex::new(HashMap::new()));
     InterpreterIterator {
         graph,
@@ -58,6 +134,25 @@ pub fn interprete<'a>(
         ram,
         mem: HashMap::new(),
         inputs,
+        roms,
+        vcd: None,
+        vcd_probes: false,
+        cycle: 0,
+        double_writes: Vec::new(),
+    }
+}
+
+//the size of the value of a node. Cycles always go through a register, so this terminates.
+fn node_size(node: &RCell<Node>, input_sizes: &Vec<usize>) -> usize {
+    match &*node.borrow() {
+        Node::Input(i) => input_sizes[*i],
+        Node::Const(c) => c.len(),
+        Node::Not(e) => node_size(e, input_sizes),
+        Node::Slice(_, c1, c2) => c2 - c1,
+        Node::BiOp(BiOp::Concat, e1, e2) => node_size(e1, input_sizes) + node_size(e2, input_sizes),
+        Node::BiOp(_, e, _) | Node::Mux(_, e, _) => node_size(e, input_sizes),
+        Node::Reg(s, _) | Node::Ram(_, s, _, _, _, _) | Node::Rom(_, _, s, _) => *s,
+        Node::TmpValueHolder(_) => 0,
     }
 }
 
@@ -68,6 +163,7 @@ fn get_value(
     mem: &mut HashMap<RCell<Node>, Vec<bool>>,
     inputs: &Vec<Vec<bool>>,
     ram: Arc<Mutex<HashMap<Vec<bool>, Vec<bool>>>>,
+    roms: &RomImages,
 ) -> Vec<bool> {
     if let Some(v) = mem.get(state) {
         return v.clone();
@@ -76,26 +172,26 @@ fn get_value(
         Node::Input(i) => inputs[i].clone(),
         Node::Const(c) => c,
         Node::Not(n) => {
-            let mut v = get_value(&n, reg_map, next_reg_map, mem, inputs, ram);
+            let mut v = get_value(&n, reg_map, next_reg_map, mem, inputs, ram, roms);
             for b in &mut v {
                 *b = !*b
             }
             v
         }
         Node::Slice(n, c1, c2) => {
-            let v = get_value(&n, reg_map, next_reg_map, mem, inputs, ram);
+            let v = get_value(&n, reg_map, next_reg_map, mem, inputs, ram, roms);
             v[c1..c2].into()
         }
         Node::BiOp(op, n1, n2) => {
-            let mut v1 = get_value(&n1, reg_map, next_reg_map, mem, inputs, ram.clone());
-            let v2 = get_value(&n2, reg_map, next_reg_map, mem, inputs, ram);
+            let mut v1 = get_value(&n1, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
+            let v2 = get_value(&n2, reg_map, next_reg_map, mem, inputs, ram, roms);
             apply_op(op, &mut v1, v2);
             v1
         }
         Node::Mux(n1, n2, n3) => {
-            let v1 = get_value(&n1, reg_map, next_reg_map, mem, inputs, ram.clone());
-            let v2 = get_value(&n2, reg_map, next_reg_map, mem, inputs, ram.clone());
-            let v3 = get_value(&n3, reg_map, next_reg_map, mem, inputs, ram.clone());
+            let v1 = get_value(&n1, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
+            let v2 = get_value(&n2, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
+            let v3 = get_value(&n3, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
             if v1[0] {
                 v2
             } else {
@@ -105,15 +201,15 @@ fn get_value(
         Node::Reg(s, n) => {
             let prev_v = reg_map.get(&n).unwrap_or(&vec![false; s]).clone();
             mem.insert(state.clone(), prev_v.clone());
-            let v = get_value(&n, reg_map, next_reg_map, mem, inputs, ram);
+            let v = get_value(&n, reg_map, next_reg_map, mem, inputs, ram, roms);
             next_reg_map.insert(n, v);
             prev_v
         }
-        Node::Ram(n1, n2, n3, n4) => {
-            let read_addr = get_value(&n1, reg_map, next_reg_map, mem, inputs, ram.clone());
-            let write_enable = get_value(&n2, reg_map, next_reg_map, mem, inputs, ram.clone());
-            let write_addr = get_value(&n3, reg_map, next_reg_map, mem, inputs, ram.clone());
-            let write_data = get_value(&n4, reg_map, next_reg_map, mem, inputs, ram.clone());
+        Node::Ram(_, _, n1, n2, n3, n4) => {
+            let read_addr = get_value(&n1, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
+            let write_enable = get_value(&n2, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
+            let write_addr = get_value(&n3, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
+            let write_data = get_value(&n4, reg_map, next_reg_map, mem, inputs, ram.clone(), roms);
             let mut ram = ram.lock().unwrap();
             let v = ram
                 .get(&read_addr)
@@ -124,8 +220,9 @@ fn get_value(
             }
             v
         }
-        Node::Rom(_, _) => {
-            todo!()
+        Node::Rom(name, _, s, n) => {
+            let addr = get_value(&n, reg_map, next_reg_map, mem, inputs, ram, roms);
+            roms.read(&name, &addr, s)
         }
         Node::TmpValueHolder(_) => {
             panic!("Should not happen : tmp value in interpreter")
@@ -4,6 +4,8 @@ mod backends;
 mod frontend;
 mod interpreter;
 mod optimization;
+mod simulator;
+#[cfg(test)]
 mod test;
 mod util;
 //The standard hashmap is cryptographically secure.
@@ -27,23 +29,78 @@ struct Args {
     flag_s: Option<usize>,
     flag_netlist: bool,
     flag_o: usize,
+    flag_rom: Vec<String>,
+    flag_vcd: Option<String>,
+    flag_vcd_all: bool,
+    flag_interpreter: bool,
+    flag_netlist_path: Option<String>,
+    flag_no_netlist: bool,
+    flag_dot_path: Option<String>,
+    flag_out_dir: Option<String>,
+    flag_c_code: bool,
+    flag_c_path: Option<String>,
+    flag_verilog: bool,
+    flag_verilog_path: Option<String>,
+    flag_rec_depth: Option<u32>,
+    #[serde(rename = "flag_W")]
+    flag_warnings: String,
 }
 
-fn process_file(path: PathBuf) -> Result<FlatProgramGraph, util::errors::TinyjazzError> {
+//the program, and the warnings found while compiling it
+fn process_file(
+    path: PathBuf,
+    rec_depth: u32,
+) -> Result<(FlatProgramGraph, Vec<util::errors::TinyjazzWarning>), util::errors::TinyjazzError> {
     let (mut prog, files) = frontend::parser_wrapper::parse(path)?;
+    //the functions of all the files are known, so the calls which are built-ins can be found
+    frontend::functions::resolve_builtins(&mut prog).map_err(|e| (e, files.clone()))?;
     frontend::constants::compute_consts(&mut prog).map_err(|e| (e, files.clone()))?;
-    frontend::hierarchical_automata::collapse_automata(&mut prog)
+    //the transitions are checked and made explicit before their conditions are flattened
+    frontend::hierarchical_automata::check_strong_transitions(&prog)
         .map_err(|e| (e, files.clone()))?; //this is just error handling
+    frontend::hierarchical_automata::check_instant_transitions(&prog)
+        .map_err(|e| (e, files.clone()))?;
+    //the dead code is found before the program is transformed, so it is still close to what was written
+    let mut warnings: Vec<util::errors::TinyjazzWarning> =
+        frontend::dead_code::find_dead_code(&prog)
+            .into_iter()
+            .map(|w| (w, files.clone()).into())
+            .collect();
+    //+ changed meaning, so each one is reported where it was written
+    warnings.extend(
+        frontend::typing::find_bus_additions(&prog)
+            .into_iter()
+            .map(|w| (w, files.clone()).into()),
+    );
+    frontend::hierarchical_automata::make_transitions_explicit(&mut prog);
     frontend::nested_expr::flatten(&mut prog);
     //a map the keep the input and output types of function,
     //even when they are inlined
     let mut type_map = AHashMap::new();
-    frontend::functions::expand_functions(&mut prog, &mut type_map)
+    frontend::functions::expand_functions(&mut prog, &mut type_map, rec_depth)
+        .map_err(|e| (e, files.clone()))?;
+    frontend::conditionals::lower_dynamic_ifs(&mut prog).map_err(|e| (e, files.clone()))?;
+    //functions can use automata, so they are collapsed after the functions are inlined
+    frontend::hierarchical_automata::collapse_automata(&mut prog, &mut type_map)
         .map_err(|e| (e, files.clone()))?;
     let prog = frontend::typing::type_prog(prog, type_map).map_err(|e| (e, files.clone()))?;
-    let graph = frontend::make_graph_automaton::make_graph(&prog);
-    let graph = frontend::automaton::flatten_automata(&graph);
-    Ok(graph)
+    let mut graph = frontend::make_graph_automaton::make_graph(&prog);
+    warnings.extend(
+        frontend::concurrency::find_conflicts(&mut graph)
+            .into_iter()
+            .map(|w| (w, files.clone()).into()),
+    );
+    let mut flat_graph = frontend::automaton::flatten_automata(&graph);
+    //the backends write where the variables come from in comments
+    flat_graph.files = Some(files.clone());
+    //the schedule is only needed to find the combinational cycles here
+    util::schedule::schedule(&flat_graph).map_err(|e| (e, files.clone()))?;
+    warnings.extend(
+        frontend::dead_code::find_unused_inputs(&flat_graph, &graph.locations)
+            .into_iter()
+            .map(|w| (w, files.clone()).into()),
+    );
+    Ok((flat_graph, warnings))
 }
 fn main() {
     //gets the args from docopt
@@ -58,43 +115,178 @@ fn main() {
     //compute the intermediate representation from either the netlist,
     //or the .tj file, depending on the arguments
     let mut flat_prog = if args.flag_netlist {
-        frontend::from_netlist::from_netlist(&*args.arg_file)
-    } else {
-        process_file(args.arg_file.into()).unwrap_or_else(|err| {
+        frontend::from_netlist::from_netlist(&args.arg_file).unwrap_or_else(|err| {
             err.print().unwrap();
             exit(1)
         })
+    } else {
+        let (prog, warnings) = process_file(
+            args.arg_file.into(),
+            args.flag_rec_depth
+                .unwrap_or(frontend::functions::REC_DEPTH),
+        )
+        .unwrap_or_else(|err| {
+            err.print().unwrap();
+            exit(1)
+        });
+        //-W none hides the warnings, -W error makes them fatal
+        match &*args.flag_warnings {
+            "none" => (),
+            "all" | "error" => {
+                for warning in &warnings {
+                    warning.print().unwrap();
+                }
+                if args.flag_warnings == "error" && !warnings.is_empty() {
+                    eprintln!(
+                        "Error : {} warning(s) treated as errors (-W error)",
+                        warnings.len()
+                    );
+                    exit(1)
+                }
+            }
+            level => {
+                eprintln!(
+                    "Error : unknown warning level {}, expected all, none or error",
+                    level
+                );
+                exit(1)
+            }
+        }
+        prog
     };
     //optimises it if necessary
     if args.flag_o >= 1 {
         optimization::basic::optimize(&mut flat_prog);
     }
-    //write the output to "out.net"
-    let file = std::fs::File::create("out.net").unwrap();
-    backends::netlist::to_netlist(&flat_prog, file).unwrap();
+    //the files with a default name are written in the output directory
+    let out_dir = PathBuf::from(args.flag_out_dir.as_deref().unwrap_or("."));
+    //write the netlist to "out.net", the given path, or stdout ("-")
+    if !args.flag_no_netlist {
+        match args.flag_netlist_path.as_deref() {
+            Some("-") => backends::netlist::to_netlist(&flat_prog, std::io::stdout()).unwrap(),
+            path => {
+                let path = output_path(path, &out_dir, "out.net");
+                let file = create_file(&path);
+                backends::netlist::to_netlist(&flat_prog, std::io::BufWriter::new(file)).unwrap();
+            }
+        }
+    }
+    //the rom images are used by the simulation, the C and the Verilog code
+    let roms = util::rom::RomImages::from_specs(&args.flag_rom).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1)
+    });
+    //the images written one word per line must have the word size of the roms using them
+    for node in util::schedule::schedule(&flat_prog).unwrap_or_default() {
+        if let ast::graph::Node::Rom(name, _, word_size, _) = &*node.borrow() {
+            roms.check_word_size(name, *word_size).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1)
+            });
+        }
+    }
+    //write the C code if necessary
+    if args.flag_c_code || args.flag_c_path.is_some() {
+        let path = output_path(args.flag_c_path.as_deref(), &out_dir, "out.c");
+        let file = std::io::BufWriter::new(create_file(&path));
+        backends::c::to_c(&flat_prog, &roms, file).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1)
+        });
+    }
+    //write the Verilog code if necessary
+    if args.flag_verilog || args.flag_verilog_path.is_some() {
+        let path = output_path(args.flag_verilog_path.as_deref(), &out_dir, "out.v");
+        let file = std::io::BufWriter::new(create_file(&path));
+        backends::verilog::to_verilog(&flat_prog, &roms, file).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1)
+        });
+    }
     //print it if necessary
     if args.flag_print {
         println!("{:#?}", flat_prog)
     }
     //output the .dot visualisation if necessary
-    if args.flag_dot {
-        util::viz::render(&flat_prog);
+    if args.flag_dot || args.flag_dot_path.is_some() {
+        let path = output_path(args.flag_dot_path.as_deref(), &out_dir, "viz.dot");
+        util::viz::render_prog_to(&mut create_file(&path), &flat_prog);
     }
     //interprete the file for <steps> steps
     if let Some(steps) = args.flag_s {
-        for outputs in interpreter::interprete(&flat_prog, args.flag_i).take(steps) {
-            println!(
-                "{:?}",
-                outputs
-                    .into_iter()
-                    .map(|(s, v)| (
-                        s,
-                        v.into_iter()
-                            .map(|b| if b { 1 } else { 0 })
-                            .collect::<Vec<u32>>()
-                    ))
-                    .collect::<Vec<(&String, Vec<u32>)>>()
-            );
+        let vcd = args.flag_vcd.as_ref().map(|path| {
+            let file = create_file(path.as_ref());
+            Box::new(std::io::BufWriter::new(file)) as Box<dyn std::io::Write>
+        });
+        let inputs = util::scripting::get_inputs_closure(args.flag_i, flat_prog.input_sizes());
+        if args.flag_interpreter {
+            let mut sim = interpreter::interprete(&flat_prog, inputs, roms);
+            if let Some(vcd) = vcd {
+                sim.record_vcd(vcd, args.flag_vcd_all).unwrap();
+            }
+            for outputs in sim.by_ref().take(steps) {
+                print_outputs(outputs);
+            }
+            print_double_writes(sim.double_writes());
+            sim.finish_vcd().unwrap();
+        } else {
+            let mut sim = simulator::simulate(&flat_prog, inputs, roms).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1)
+            });
+            if let Some(vcd) = vcd {
+                sim.record_vcd(vcd, args.flag_vcd_all).unwrap();
+            }
+            for outputs in sim.by_ref().take(steps) {
+                print_outputs(outputs);
+            }
+            print_double_writes(sim.double_writes());
+            sim.finish_vcd().unwrap();
         }
     }
 }
+
+fn create_file(path: &std::path::Path) -> std::fs::File {
+    std::fs::File::create(path).unwrap_or_else(|err| {
+        eprintln!("Error : cannot create file {} : {}", path.display(), err);
+        exit(1)
+    })
+}
+
+//the given path, or the default name in the output directory, which is created only then
+fn output_path(path: Option<&str>, out_dir: &std::path::Path, default: &str) -> PathBuf {
+    match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            std::fs::create_dir_all(out_dir).unwrap_or_else(|err| {
+                eprintln!("Error : cannot create directory {} : {}", out_dir.display(), err);
+                exit(1)
+            });
+            out_dir.join(default)
+        }
+    }
+}
+
+fn print_double_writes(double_writes: &[(usize, String)]) {
+    for (cycle, name) in double_writes {
+        eprintln!(
+            "Warning : at cycle {}, {} is assigned by two active states, only one of the values is kept",
+            cycle, name
+        );
+    }
+}
+
+fn print_outputs(outputs: Vec<(&String, Vec<bool>)>) {
+    println!(
+        "{:?}",
+        outputs
+            .into_iter()
+            .map(|(s, v)| (
+                s,
+                v.into_iter()
+                    .map(|b| if b { 1 } else { 0 })
+                    .collect::<Vec<u32>>()
+            ))
+            .collect::<Vec<(&String, Vec<u32>)>>()
+    );
+}
@@ -225,8 +225,8 @@ fn try_compute(
                 None
             }
         }
-        Node::Ram(_, _, _, _) => None,
-        Node::Rom(_, _) => None,
+        Node::Ram(_, _, _, _, _, _) => None,
+        Node::Rom(_, _, _, _) => None,
         Node::TmpValueHolder(_) => None,
     };
     if let Some(v) = &ret {
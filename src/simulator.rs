@@ -0,0 +1,538 @@
+use ahash::AHashMap;
+use std::fmt::Display;
+
+use crate::ast::graph::*;
+use crate::util::{rom::RomImages, schedule::schedule, vcd::VcdWriter};
+/*
+A faster replacement for the interpreter.
+The graph is scheduled once (see util/schedule.rs) into a flat list of instructions, working on buses packed into u64 words.
+Each node gets a slot in a single array, bit i of a bus being at bit i%64 of its word i/64.
+Running a cycle is then just a loop over the instructions, without any recursion or allocation.
+
+Unlike the interpreter, every ram has its own memory, and is written at the end of the cycle
+(a read during the cycle of the write gives the previous value).
+*/
+#[derive(Debug)]
+pub enum SimulatorError {
+    CombinationalCycle,     //a cycle that does not go through a register
+    AddressTooLarge(usize), //ram or rom addresses are limited to 64 bits
+}
+
+impl Display for SimulatorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimulatorError::CombinationalCycle => write!(
+                f,
+                "Error : the program contains a combinational cycle, it cannot be simulated"
+            ),
+            SimulatorError::AddressTooLarge(n) => write!(
+                f,
+                "Error : addresses of {} bits are not supported by the simulator (at most 64)",
+                n
+            ),
+        }
+    }
+}
+
+//position and size (in bits) of a bus in the value array
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    offset: usize,
+    size: usize,
+}
+
+#[derive(Debug)]
+enum Instr {
+    Not(Slot, Slot),
+    BiOp(BiOp, Slot, Slot, Slot),
+    Concat(Slot, Slot, Slot),
+    Slice(Slot, Slot, usize),
+    Mux(Slot, Slot, Slot, Slot),
+    Ram(usize, Slot, Slot), //ram id, destination, read address
+    Rom(usize, Slot, Slot), //rom id, destination, read address
+}
+
+//rams with small addresses are stored as an array, the other ones in a map
+enum Memory {
+    Dense(Vec<u64>),
+    Sparse(AHashMap<u64, Vec<u64>>),
+}
+
+struct Ram {
+    memory: Memory,
+    word_size: usize,
+    //write enable, write address, write data
+    write: (Slot, Slot, Slot),
+}
+
+pub struct Simulator<'a> {
+    graph: &'a FlatProgramGraph,
+    values: Vec<u64>,
+    instrs: Vec<Instr>,
+    //(register, next value, input), the input is saved after the instructions,
+    //and the register is updated at the beginning of the next cycle
+    regs: Vec<(Slot, Slot, Slot)>,
+    rams: Vec<Ram>,
+    //the images of the roms, packed like the dense rams
+    roms: Vec<Vec<u64>>,
+    inputs: Vec<Slot>,
+    outputs: Vec<Slot>,
+    probes: Vec<Slot>,
+    //the shared variables which may be assigned by two states, with whether each state is active
+    conflicts: Vec<(&'a String, Slot, Slot)>,
+    get_inputs: Box<dyn FnMut() -> Vec<Vec<bool>>>,
+    vcd: Option<VcdWriter>,
+    vcd_probes: bool,
+    cycle: usize,
+    double_writes: Vec<(usize, String)>,
+}
+
+fn words(size: usize) -> usize {
+    size.div_ceil(64)
+}
+
+//the mask of the used bits of the last word of a bus
+fn last_mask(size: usize) -> u64 {
+    if size.is_multiple_of(64) {
+        u64::MAX
+    } else {
+        (1 << (size % 64)) - 1
+    }
+}
+
+//a ram while it is compiled : address size, word size, write enable, write address and write data
+type RamInputs = (usize, usize, RCell<Node>, RCell<Node>, RCell<Node>);
+
+struct Compiler {
+    slots: AHashMap<u32, Slot>,
+    size: usize,
+    inputs: Vec<Slot>,
+    instrs: Vec<Instr>,
+    consts: Vec<(Slot, Vec<bool>)>,
+    //registers and rams, with their inputs
+    regs: Vec<(Slot, RCell<Node>)>,
+    rams: Vec<RamInputs>,
+    roms: Vec<(String, usize)>,
+}
+
+impl Compiler {
+    fn alloc(&mut self, size: usize) -> Slot {
+        let slot = Slot {
+            offset: self.size,
+            size,
+        };
+        self.size += words(size);
+        slot
+    }
+
+    fn slot(&self, node: &RCell<Node>) -> Slot {
+        *self.slots.get(&node.id()).unwrap()
+    }
+
+    //all the dependencies of the node must already be compiled
+    fn compile_node(&mut self, node: &RCell<Node>) -> Result<Slot, SimulatorError> {
+        Ok(match node.borrow().clone() {
+            Node::Input(i) => self.inputs[i],
+            Node::Const(c) => {
+                let dst = self.alloc(c.len());
+                self.consts.push((dst, c));
+                dst
+            }
+            Node::Not(e) => {
+                let src = self.slot(&e);
+                let dst = self.alloc(src.size);
+                self.instrs.push(Instr::Not(dst, src));
+                dst
+            }
+            Node::Slice(e, c1, c2) => {
+                let src = self.slot(&e);
+                let dst = self.alloc(c2 - c1);
+                self.instrs.push(Instr::Slice(dst, src, c1));
+                dst
+            }
+            Node::BiOp(BiOp::Concat, e1, e2) => {
+                let (a, b) = (self.slot(&e1), self.slot(&e2));
+                let dst = self.alloc(a.size + b.size);
+                self.instrs.push(Instr::Concat(dst, a, b));
+                dst
+            }
+            Node::BiOp(op, e1, e2) => {
+                let (a, b) = (self.slot(&e1), self.slot(&e2));
+                let dst = self.alloc(a.size);
+                self.instrs.push(Instr::BiOp(op, dst, a, b));
+                dst
+            }
+            Node::Mux(e1, e2, e3) => {
+                let (c, a, b) = (self.slot(&e1), self.slot(&e2), self.slot(&e3));
+                let dst = self.alloc(a.size);
+                self.instrs.push(Instr::Mux(dst, c, a, b));
+                dst
+            }
+            Node::Reg(s, e) => {
+                let dst = self.alloc(s);
+                self.regs.push((dst, e));
+                dst
+            }
+            Node::Ram(addr_size, word_size, read_addr, write_enable, write_addr, write_data) => {
+                if addr_size > 64 {
+                    return Err(SimulatorError::AddressTooLarge(addr_size));
+                }
+                let addr = self.slot(&read_addr);
+                let dst = self.alloc(word_size);
+                let ram_id = self.rams.len();
+                self.instrs.push(Instr::Ram(ram_id, dst, addr));
+                self.rams
+                    .push((addr_size, word_size, write_enable, write_addr, write_data));
+                dst
+            }
+            Node::Rom(name, addr_size, word_size, read_addr) => {
+                if addr_size > 64 {
+                    return Err(SimulatorError::AddressTooLarge(addr_size));
+                }
+                let addr = self.slot(&read_addr);
+                let dst = self.alloc(word_size);
+                self.instrs.push(Instr::Rom(self.roms.len(), dst, addr));
+                self.roms.push((name, word_size));
+                dst
+            }
+            Node::TmpValueHolder(_) => {
+                panic!("Should not happen : tmp value in simulator")
+            }
+        })
+    }
+}
+
+//compiles the graph into a simulator. get_inputs gives the inputs of each cycle.
+pub fn simulate<'a>(
+    graph: &'a FlatProgramGraph,
+    get_inputs: Box<dyn FnMut() -> Vec<Vec<bool>>>,
+    rom_images: RomImages,
+) -> Result<Simulator<'a>, SimulatorError> {
+    let mut compiler = Compiler {
+        slots: AHashMap::new(),
+        size: 0,
+        inputs: Vec::new(),
+        instrs: Vec::new(),
+        consts: Vec::new(),
+        regs: Vec::new(),
+        rams: Vec::new(),
+        roms: Vec::new(),
+    };
+    for (_, s) in &graph.inputs {
+        let slot = compiler.alloc(*s);
+        compiler.inputs.push(slot);
+    }
+    for node in schedule(graph).map_err(|_| SimulatorError::CombinationalCycle)? {
+        let slot = compiler.compile_node(&node)?;
+        compiler.slots.insert(node.id(), slot);
+    }
+    let outputs = graph.outputs.iter().map(|(_, n)| compiler.slot(n)).collect();
+    let probes = graph.probes.iter().map(|(_, n)| compiler.slot(n)).collect();
+    let conflicts = graph
+        .conflicts
+        .iter()
+        .map(|(name, s1, s2)| (name, compiler.slot(s1), compiler.slot(s2)))
+        .collect();
+    let mut regs = Vec::new();
+    for (reg, e) in std::mem::take(&mut compiler.regs) {
+        let next = compiler.alloc(reg.size);
+        regs.push((reg, next, compiler.slot(&e)));
+    }
+    let mut rams = Vec::new();
+    for (addr_size, word_size, we, wa, wd) in std::mem::take(&mut compiler.rams) {
+        let memory = if addr_size <= 16 {
+            Memory::Dense(vec![0; words(word_size) << addr_size])
+        } else {
+            Memory::Sparse(AHashMap::new())
+        };
+        rams.push(Ram {
+            memory,
+            word_size,
+            write: (compiler.slot(&we), compiler.slot(&wa), compiler.slot(&wd)),
+        });
+    }
+    let roms = compiler
+        .roms
+        .iter()
+        .map(|(name, word_size)| match rom_images.image(name) {
+            Some(image) if *word_size > 0 => {
+                let n = words(*word_size);
+                let mut memory = vec![0; image.len().div_ceil(*word_size) * n];
+                for (k, word) in image.chunks(*word_size).enumerate() {
+                    let slot = Slot {
+                        offset: k * n,
+                        size: *word_size,
+                    };
+                    write_bools(&mut memory, slot, word);
+                }
+                memory
+            }
+            _ => Vec::new(),
+        })
+        .collect();
+    let mut values = vec![0; compiler.size];
+    for (slot, c) in &compiler.consts {
+        write_bools(&mut values, *slot, c);
+    }
+    Ok(Simulator {
+        graph,
+        values,
+        instrs: compiler.instrs,
+        regs,
+        rams,
+        roms,
+        inputs: compiler.inputs,
+        outputs,
+        probes,
+        conflicts,
+        get_inputs,
+        vcd: None,
+        vcd_probes: false,
+        cycle: 0,
+        double_writes: Vec::new(),
+    })
+}
+
+fn write_bools(values: &mut [u64], slot: Slot, bits: &[bool]) {
+    for w in 0..words(slot.size) {
+        values[slot.offset + w] = 0;
+    }
+    for (i, b) in bits.iter().enumerate().take(slot.size) {
+        values[slot.offset + i / 64] |= (*b as u64) << (i % 64);
+    }
+}
+
+fn read_bools(values: &[u64], slot: Slot) -> Vec<bool> {
+    (0..slot.size)
+        .map(|i| (values[slot.offset + i / 64] >> (i % 64)) & 1 == 1)
+        .collect()
+}
+
+//reads up to 64 bits of a bus, starting from any bit
+fn read_bits(values: &[u64], offset: usize, start: usize, len: usize) -> u64 {
+    let (w, b) = (offset + start / 64, start % 64);
+    let mut res = values[w] >> b;
+    if b != 0 && b + len > 64 {
+        res |= values[w + 1] << (64 - b);
+    }
+    if len == 64 {
+        res
+    } else {
+        res & ((1 << len) - 1)
+    }
+}
+
+//writes up to 64 bits in a bus, starting from any bit
+fn write_bits(values: &mut [u64], offset: usize, start: usize, len: usize, bits: u64) {
+    let (w, b) = (offset + start / 64, start % 64);
+    let mask = if len == 64 { u64::MAX } else { (1 << len) - 1 };
+    values[w] = (values[w] & !(mask << b)) | (bits << b);
+    if b != 0 && b + len > 64 {
+        values[w + 1] = (values[w + 1] & !(mask >> (64 - b))) | (bits >> (64 - b));
+    }
+}
+
+//copies len bits from src (starting at bit src_start) into dst (starting at bit dst_start)
+fn copy_bits(values: &mut [u64], dst: Slot, dst_start: usize, src: Slot, src_start: usize, len: usize) {
+    let mut i = 0;
+    while i < len {
+        let n = (len - i).min(64);
+        let bits = read_bits(values, src.offset, src_start + i, n);
+        write_bits(values, dst.offset, dst_start + i, n, bits);
+        i += n;
+    }
+}
+
+//the value of an address (the first bit is the most significant)
+fn address(values: &[u64], slot: Slot) -> u64 {
+    let mut addr = 0;
+    for i in 0..slot.size {
+        addr = (addr << 1) | ((values[slot.offset + i / 64] >> (i % 64)) & 1);
+    }
+    addr
+}
+
+impl<'a> Simulator<'a> {
+    //simulates one cycle
+    pub fn step(&mut self) {
+        //updates the registers with the values of the previous cycle
+        for (reg, next, _) in &self.regs {
+            for w in 0..words(reg.size) {
+                self.values[reg.offset + w] = self.values[next.offset + w];
+            }
+        }
+        let inputs = (self.get_inputs)();
+        for (slot, v) in self.inputs.iter().zip(inputs.iter()) {
+            write_bools(&mut self.values, *slot, v);
+        }
+        let values = &mut self.values;
+        for instr in &self.instrs {
+            match instr {
+                Instr::Not(dst, src) => {
+                    let n = words(dst.size);
+                    for w in 0..n {
+                        values[dst.offset + w] = !values[src.offset + w];
+                    }
+                    if n > 0 {
+                        values[dst.offset + n - 1] &= last_mask(dst.size);
+                    }
+                }
+                Instr::BiOp(op, dst, a, b) => {
+                    let n = words(dst.size);
+                    for w in 0..n {
+                        let (x, y) = (values[a.offset + w], values[b.offset + w]);
+                        values[dst.offset + w] = match op {
+                            BiOp::And => x & y,
+                            BiOp::Or => x | y,
+                            BiOp::Xor => x ^ y,
+                            BiOp::Nand => !(x & y),
+                            BiOp::Concat => unreachable!(),
+                        }
+                    }
+                    if n > 0 {
+                        values[dst.offset + n - 1] &= last_mask(dst.size);
+                    }
+                }
+                Instr::Concat(dst, a, b) => {
+                    if dst.size <= 64 {
+                        let x = if a.size == 0 { 0 } else { values[a.offset] };
+                        let y = if b.size == 0 { 0 } else { values[b.offset] };
+                        if dst.size > 0 {
+                            values[dst.offset] = x | y.checked_shl(a.size as u32).unwrap_or(0);
+                        }
+                    } else {
+                        copy_bits(values, *dst, 0, *a, 0, a.size);
+                        copy_bits(values, *dst, a.size, *b, 0, b.size);
+                    }
+                }
+                Instr::Slice(dst, src, start) => {
+                    if dst.size <= 64 {
+                        if dst.size > 0 {
+                            values[dst.offset] = read_bits(values, src.offset, *start, dst.size);
+                        }
+                    } else {
+                        copy_bits(values, *dst, 0, *src, *start, dst.size);
+                    }
+                }
+                Instr::Mux(dst, c, a, b) => {
+                    let src = if values[c.offset] & 1 == 1 { a } else { b };
+                    for w in 0..words(dst.size) {
+                        values[dst.offset + w] = values[src.offset + w];
+                    }
+                }
+                Instr::Ram(id, dst, addr) => {
+                    let ram = &self.rams[*id];
+                    let n = words(ram.word_size);
+                    let addr = address(values, *addr);
+                    match &ram.memory {
+                        Memory::Dense(memory) => match memory.get(addr as usize * n..(addr as usize + 1) * n) {
+                            Some(word) => values[dst.offset..dst.offset + n].copy_from_slice(word),
+                            None => values[dst.offset..dst.offset + n].fill(0),
+                        },
+                        Memory::Sparse(memory) => match memory.get(&addr) {
+                            Some(word) => values[dst.offset..dst.offset + n].copy_from_slice(word),
+                            None => values[dst.offset..dst.offset + n].fill(0),
+                        },
+                    }
+                }
+                Instr::Rom(id, dst, addr) => {
+                    let rom = &self.roms[*id];
+                    let n = words(dst.size);
+                    //the words after the end of the image (or of the address space) are 0
+                    let start = (address(values, *addr) as usize).checked_mul(n);
+                    match start.and_then(|s| rom.get(s..s.checked_add(n)?)) {
+                        Some(word) => values[dst.offset..dst.offset + n].copy_from_slice(word),
+                        None => values[dst.offset..dst.offset + n].fill(0),
+                    }
+                }
+            }
+        }
+        //the shared variables assigned by two active states
+        for (name, s1, s2) in &self.conflicts {
+            if values[s1.offset] & values[s2.offset] & 1 == 1 {
+                self.double_writes.push((self.cycle, name.to_string()));
+            }
+        }
+        self.cycle += 1;
+        //writes in the rams
+        for ram in &mut self.rams {
+            let (we, wa, wd) = ram.write;
+            if values[we.offset] & 1 == 0 {
+                continue;
+            }
+            let n = words(ram.word_size);
+            let addr = address(values, wa);
+            match &mut ram.memory {
+                Memory::Dense(memory) => {
+                    if let Some(word) = memory.get_mut(addr as usize * n..(addr as usize + 1) * n) {
+                        word.copy_from_slice(&values[wd.offset..wd.offset + n]);
+                    }
+                }
+                Memory::Sparse(memory) => {
+                    memory.insert(addr, values[wd.offset..wd.offset + n].to_vec());
+                }
+            }
+        }
+        //saves the inputs of the registers, they are updated at the beginning of the next cycle
+        for (_, next, src) in &self.regs {
+            for w in 0..words(next.size) {
+                values[next.offset + w] = values[src.offset + w];
+            }
+        }
+        if let Some(vcd) = &mut self.vcd {
+            let mut signals: Vec<Vec<bool>> = inputs;
+            signals.extend(self.outputs.iter().map(|s| read_bools(values, *s)));
+            if self.vcd_probes {
+                signals.extend(self.probes.iter().map(|s| read_bools(values, *s)));
+            }
+            vcd.step(&signals).expect("Could not write the vcd file");
+        }
+    }
+
+    //the values of the outputs, as computed by the last cycle
+    pub fn outputs(&self) -> Vec<(&'a String, Vec<bool>)> {
+        self.graph
+            .outputs
+            .iter()
+            .zip(self.outputs.iter())
+            .map(|((name, _), slot)| (name, read_bools(&self.values, *slot)))
+            .collect()
+    }
+
+    //Records the inputs and outputs of every following cycle in the VCD format.
+    //If probes is true, the named shared variables and the states are recorded too.
+    pub fn record_vcd(&mut self, dest: Box<dyn std::io::Write>, probes: bool) -> std::io::Result<()> {
+        let mut signals: Vec<(String, usize)> = self.graph.inputs.clone();
+        for ((name, _), slot) in self.graph.outputs.iter().zip(self.outputs.iter()) {
+            signals.push((name.clone(), slot.size));
+        }
+        if probes {
+            for ((name, _), slot) in self.graph.probes.iter().zip(self.probes.iter()) {
+                signals.push((name.clone(), slot.size));
+            }
+        }
+        self.vcd = Some(VcdWriter::new(dest, &signals)?);
+        self.vcd_probes = probes;
+        Ok(())
+    }
+
+    pub fn finish_vcd(&mut self) -> std::io::Result<()> {
+        match &mut self.vcd {
+            Some(vcd) => vcd.finish(),
+            None => Ok(()),
+        }
+    }
+
+    //the cycles where a shared variable was assigned by two active states, and its name
+    pub fn double_writes(&self) -> &[(usize, String)] {
+        &self.double_writes
+    }
+}
+
+impl<'a> Iterator for Simulator<'a> {
+    type Item = Vec<(&'a String, Vec<bool>)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step();
+        Some(self.outputs())
+    }
+}
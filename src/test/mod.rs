@@ -1,3 +1,504 @@
 /*
-One day this file will contain unit tests
+The test suite, run with cargo test. It goes through the corpus of programs in src/tests :
+-parser/ : the files used by the tests of the parser (in frontend/parser_wrapper)
+-pass/<name>.tj : programs which must compile. Next to them :
+    -<name>.net is the expected netlist (optimised). The ids of the nodes do not matter.
+    -<name>.trace is the expected simulation : one line per cycle, "<inputs> -> <outputs>",
+     each bus being written with 0s and 1s, or "-" if it is empty.
+     It is checked with the simulator and the interpreter, with and without optimisations,
+     with the expected netlist, and with the C code when gcc is available.
+     A line can end with "! <names>", the shared variables assigned by two active states during
+     the cycle, which the simulator and the interpreter must report.
+    -<name>.rom is the image of the roms, if there are any.
+-fail/<code>_<name>.tj (or .net, for netlists) : programs which must be rejected with the error <code>.
+    When the name starts with a number (<code>_<n>_<name>.tj), they must be rejected with n errors,
+    all with this code : the parser and the typing report every error they find.
+-warn/<code>_<name>.tj : programs which must compile with the warning <code>.
+
+When the behaviour of the compiler changes on purpose, run the tests with TINYJAZZ_BLESS=1 to
+rewrite the expected netlists and the outputs of the traces (the inputs are kept).
+A new trace can be written with only the inputs ("<inputs> ->"), and then blessed.
 */
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{read_dir, read_to_string, write},
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use regex::{Captures, Regex};
+
+use crate::ast::graph::FlatProgramGraph;
+use crate::util::rom::RomImages;
+use crate::{backends, frontend, interpreter, optimization, simulator};
+
+const CORPUS: &str = "src/tests";
+
+fn bless() -> bool {
+    std::env::var_os("TINYJAZZ_BLESS").is_some()
+}
+
+//the files of a directory of the corpus with the given extension, sorted
+fn corpus(dir: &str, ext: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = read_dir(Path::new(CORPUS).join(dir))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == ext))
+        .collect();
+    files.sort();
+    files
+}
+
+//each failing test is reported, and they make the test fail at the end
+fn check_all(files: Vec<PathBuf>, test: impl Fn(&Path) -> Result<(), String>) {
+    let failures: Vec<String> = files
+        .iter()
+        .filter_map(|path| {
+            test(path)
+                .err()
+                .map(|e| format!("{} : {}", path.display(), e))
+        })
+        .collect();
+    if !failures.is_empty() {
+        panic!("\n{}\n", failures.join("\n"))
+    }
+}
+
+fn compile(path: &Path, optimize: bool) -> Result<FlatProgramGraph, String> {
+    let (mut prog, _) = crate::process_file(path.to_path_buf(), frontend::functions::REC_DEPTH)
+        .map_err(|err| {
+            err.print().unwrap();
+            format!("compilation failed with {:?}", err.code())
+        })?;
+    if optimize {
+        optimization::basic::optimize(&mut prog);
+    }
+    Ok(prog)
+}
+
+//the ids of the nodes depend on everything compiled before, so they are renamed in order of appearance
+fn canonical_netlist(netlist: &str) -> String {
+    let mut names = HashMap::new();
+    Regex::new(r"\bv_[0-9]+\b")
+        .unwrap()
+        .replace_all(netlist, |caps: &Captures| {
+            let n = names.len();
+            format!("v_{}", names.entry(caps[0].to_string()).or_insert(n))
+        })
+        .into_owned()
+}
+
+fn parse_bus(s: &str) -> Result<Vec<bool>, String> {
+    if s == "-" {
+        return Ok(Vec::new());
+    }
+    s.chars()
+        .map(|c| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(format!("unexpected character {:?} in the trace", c)),
+        })
+        .collect()
+}
+
+fn format_buses(buses: &[Vec<bool>]) -> String {
+    buses
+        .iter()
+        .map(|v| {
+            if v.is_empty() {
+                "-".to_string()
+            } else {
+                v.iter().map(|b| if *b { '1' } else { '0' }).collect()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+//the inputs, expected outputs and expected double writes of each cycle
+type Trace = Vec<(Vec<Vec<bool>>, Vec<Vec<bool>>, Vec<String>)>;
+
+fn parse_trace(trace: &str) -> Result<Trace, String> {
+    trace
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (inputs, outputs) = line
+                .split_once("->")
+                .ok_or(format!("missing \"->\" in line {:?}", line))?;
+            let (outputs, double_writes) = outputs.split_once('!').unwrap_or((outputs, ""));
+            let parse = |s: &str| {
+                s.split_whitespace()
+                    .map(parse_bus)
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            Ok((
+                parse(inputs)?,
+                parse(outputs)?,
+                double_writes.split_whitespace().map(String::from).collect(),
+            ))
+        })
+        .collect()
+}
+
+//the names of the variables assigned by two active states at each cycle, without duplicates
+fn double_writes_by_cycle(double_writes: &[(usize, String)], cycles: usize) -> Vec<Vec<String>> {
+    let mut names = vec![Vec::new(); cycles];
+    for (cycle, name) in double_writes {
+        if !names[*cycle].contains(name) {
+            names[*cycle].push(name.clone());
+        }
+    }
+    for cycle in &mut names {
+        cycle.sort();
+    }
+    names
+}
+
+enum Engine {
+    Simulator,
+    Interpreter,
+}
+
+//the outputs and the double writes of each cycle
+type Simulation = (Vec<Vec<Vec<bool>>>, Vec<Vec<String>>);
+
+fn simulate(
+    prog: &FlatProgramGraph,
+    engine: Engine,
+    inputs: Vec<Vec<Vec<bool>>>,
+    roms: RomImages,
+) -> Result<Simulation, String> {
+    let steps = inputs.len();
+    for cycle in &inputs {
+        let sizes: Vec<usize> = cycle.iter().map(|v| v.len()).collect();
+        if sizes != prog.input_sizes() {
+            return Err(format!(
+                "the inputs of the trace have sizes {:?} instead of {:?}",
+                sizes,
+                prog.input_sizes()
+            ));
+        }
+    }
+    let mut inputs = inputs.into_iter();
+    let get_inputs = Box::new(move || inputs.next().unwrap());
+    let values = |outputs: Vec<(&String, Vec<bool>)>| outputs.into_iter().map(|(_, v)| v).collect();
+    Ok(match engine {
+        Engine::Simulator => {
+            let mut sim = simulator::simulate(prog, get_inputs, roms).map_err(|e| e.to_string())?;
+            let outputs = sim.by_ref().take(steps).map(values).collect();
+            (outputs, double_writes_by_cycle(sim.double_writes(), steps))
+        }
+        Engine::Interpreter => {
+            let mut sim = interpreter::interprete(prog, get_inputs, roms);
+            let outputs = sim.by_ref().take(steps).map(values).collect();
+            (outputs, double_writes_by_cycle(sim.double_writes(), steps))
+        }
+    })
+}
+
+//the outputs of each cycle, computed by the C code compiled with gcc (None if gcc is not available)
+fn run_c(
+    prog: &FlatProgramGraph,
+    name: &str,
+    inputs: &[Vec<Vec<bool>>],
+    roms: &RomImages,
+) -> Result<Option<Vec<Vec<Vec<bool>>>>, String> {
+    let dir = std::env::temp_dir().join(format!("tinyjazz_test_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (c_path, exe_path) = (dir.join(format!("{}.c", name)), dir.join(name));
+    let mut code = Vec::new();
+    backends::c::to_c(prog, roms, &mut code).map_err(|e| e.to_string())?;
+    write(&c_path, code).unwrap();
+    let status = match Command::new("gcc")
+        .arg("-o")
+        .arg(&exe_path)
+        .arg(&c_path)
+        .status()
+    {
+        Ok(status) => status,
+        Err(_) => return Ok(None),
+    };
+    if !status.success() {
+        return Err("the C code does not compile".to_string());
+    }
+    let mut child = Command::new(&exe_path)
+        .arg(inputs.len().to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdin: String = inputs
+        .iter()
+        .map(|cycle| format!("{}\n", format_buses(cycle).replace('-', "")))
+        .collect();
+    //a program without inputs can exit before they are written, its outputs are checked anyway
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).ok();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    //each line is "name=bits name=bits ..."
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            line.split_whitespace()
+                .map(|out| parse_bus(out.split_once('=').map_or("", |(_, bits)| bits)))
+                .collect()
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn check_trace(expected: &Trace, outputs: &[Vec<Vec<bool>>], what: &str) -> Result<(), String> {
+    for (i, ((inputs, expected, _), got)) in expected.iter().zip(outputs).enumerate() {
+        if expected != got {
+            return Err(format!(
+                "{} : at cycle {} (inputs {}), expected {}, got {}",
+                what,
+                i,
+                format_buses(inputs),
+                format_buses(expected),
+                format_buses(got)
+            ));
+        }
+    }
+    Ok(())
+}
+
+//the netlists do not know the states, so only the engines compiled from the program report the double writes
+fn check_double_writes(expected: &Trace, got: &[Vec<String>], what: &str) -> Result<(), String> {
+    for (i, ((inputs, _, expected), got)) in expected.iter().zip(got).enumerate() {
+        let mut expected = expected.clone();
+        expected.sort();
+        if &expected != got {
+            return Err(format!(
+                "{} : at cycle {} (inputs {}), expected the double writes {:?}, got {:?}",
+                what,
+                i,
+                format_buses(inputs),
+                expected,
+                got
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_program(path: &Path) -> Result<(), String> {
+    let prog = compile(path, true)?;
+    //the netlist
+    let net_path = path.with_extension("net");
+    let mut netlist = Vec::new();
+    backends::netlist::to_netlist(&prog, &mut netlist).unwrap();
+    let netlist = String::from_utf8(netlist).unwrap();
+    if bless() {
+        write(&net_path, &netlist).unwrap();
+    } else if let Ok(expected) = read_to_string(&net_path) {
+        if canonical_netlist(&expected) != canonical_netlist(&netlist) {
+            return Err(format!(
+                "the netlist changed, expected :\n{}\ngot :\n{}",
+                expected, netlist
+            ));
+        }
+    }
+    //the simulation
+    let trace_path = path.with_extension("trace");
+    let trace = match read_to_string(&trace_path) {
+        Ok(trace) => parse_trace(&trace)?,
+        Err(_) => return Ok(()),
+    };
+    let roms = match path.with_extension("rom") {
+        rom_path if rom_path.exists() => {
+            RomImages::from_specs(&[rom_path.to_string_lossy().to_string()])
+                .map_err(|e| e.to_string())?
+        }
+        _ => RomImages::default(),
+    };
+    let inputs: Vec<Vec<Vec<bool>>> = trace.iter().map(|(i, _, _)| i.clone()).collect();
+    let (outputs, double_writes) =
+        simulate(&prog, Engine::Simulator, inputs.clone(), roms.clone())?;
+    if bless() {
+        let lines: Vec<String> = inputs
+            .iter()
+            .zip(&outputs)
+            .zip(&double_writes)
+            .map(|((i, o), names)| {
+                let names = if names.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ! {}", names.join(" "))
+                };
+                format!("{} -> {}{}\n", format_buses(i), format_buses(o), names)
+            })
+            .map(|line| line.trim_start().to_string())
+            .collect();
+        write(&trace_path, lines.concat()).unwrap();
+        return Ok(());
+    }
+    check_trace(&trace, &outputs, "simulator")?;
+    check_double_writes(&trace, &double_writes, "simulator")?;
+    let unoptimized = compile(path, false)?;
+    let (outputs, double_writes) = simulate(
+        &unoptimized,
+        Engine::Simulator,
+        inputs.clone(),
+        roms.clone(),
+    )?;
+    check_trace(&trace, &outputs, "simulator without optimisations")?;
+    check_double_writes(&trace, &double_writes, "simulator without optimisations")?;
+    let (outputs, double_writes) =
+        simulate(&prog, Engine::Interpreter, inputs.clone(), roms.clone())?;
+    check_trace(&trace, &outputs, "interpreter")?;
+    check_double_writes(&trace, &double_writes, "interpreter")?;
+    let name = path.file_stem().unwrap().to_string_lossy();
+    if let Some(outputs) = run_c(&prog, &name, &inputs, &roms)? {
+        check_trace(&trace, &outputs, "C code")?;
+    }
+    if net_path.exists() {
+        let from_netlist = frontend::from_netlist::from_netlist(&net_path.to_string_lossy())
+            .map_err(|err| {
+                err.print().unwrap();
+                "the expected netlist cannot be read".to_string()
+            })?;
+        let (outputs, _) = simulate(&from_netlist, Engine::Simulator, inputs, roms)?;
+        check_trace(&trace, &outputs, "expected netlist")?;
+    }
+    Ok(())
+}
+
+fn check_failure(path: &Path) -> Result<(), String> {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let mut parts = name.split('_');
+    let expected = parts.next().unwrap();
+    let count = parts.next().and_then(|n| n.parse::<usize>().ok());
+    let result = if path.extension().unwrap() == "net" {
+        frontend::from_netlist::from_netlist(&path.to_string_lossy()).map(|_| ())
+    } else {
+        crate::process_file(path.to_path_buf(), frontend::functions::REC_DEPTH).map(|_| ())
+    };
+    match result {
+        Ok(()) => Err(format!("expected error {}, but it compiled", expected)),
+        Err(err) => {
+            let codes = err.codes();
+            let all_expected = codes.iter().all(|c| c.as_deref() == Some(expected));
+            match count {
+                None if err.code().as_deref() == Some(expected) => Ok(()),
+                Some(n) if codes.len() == n && all_expected => Ok(()),
+                _ => {
+                    err.print().unwrap();
+                    Err(format!(
+                        "expected {} error(s) {}, got {:?}",
+                        count.unwrap_or(1),
+                        expected,
+                        codes
+                    ))
+                }
+            }
+        }
+    }
+}
+
+fn check_warning(path: &Path) -> Result<(), String> {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let expected = name.split('_').next().unwrap();
+    let (_, warnings) = crate::process_file(path.to_path_buf(), frontend::functions::REC_DEPTH)
+        .map_err(|err| {
+            err.print().unwrap();
+            format!("compilation failed with {:?}", err.code())
+        })?;
+    if warnings
+        .iter()
+        .any(|w| w.code().as_deref() == Some(expected))
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected warning {}, got {:?}",
+            expected,
+            warnings.iter().map(|w| w.code()).collect::<Vec<_>>()
+        ))
+    }
+}
+
+#[test]
+fn test_programs() {
+    check_all(corpus("pass", "tj"), check_program);
+}
+
+#[test]
+fn test_failures() {
+    let mut files = corpus("fail", "tj");
+    files.append(&mut corpus("fail", "net"));
+    check_all(files, check_failure);
+}
+
+#[test]
+fn test_warnings() {
+    check_all(corpus("warn", "tj"), check_warning);
+}
+
+//the inputs and outputs must not be renamed into the variables of the Verilog code,
+//and the code must be accepted by iverilog or verilator when one of them is available
+#[test]
+fn test_verilog_names() {
+    let declaration =
+        Regex::new(r"^\s*(input|output|wire|reg|integer)\s+(\[[^\]]*\]\s+)?([A-Za-z_0-9]+)")
+            .unwrap();
+    check_all(corpus("pass", "tj"), |path| {
+        let mut verilog = Vec::new();
+        backends::verilog::to_verilog(&compile(path, true)?, &RomImages::default(), &mut verilog)
+            .map_err(|e| e.to_string())?;
+        let mut declared = HashSet::new();
+        for line in String::from_utf8(verilog.clone()).unwrap().lines() {
+            if let Some(caps) = declaration.captures(line) {
+                if !declared.insert(caps[3].to_string()) {
+                    return Err(format!("{} is declared twice", &caps[3]));
+                }
+            }
+        }
+        let name = path.file_stem().unwrap().to_string_lossy();
+        lint_verilog(&name, &verilog)
+    });
+}
+
+//checks the syntax of the Verilog code with iverilog, or with verilator if it is not available (nothing if neither is)
+fn lint_verilog(name: &str, verilog: &[u8]) -> Result<(), String> {
+    let dir = std::env::temp_dir().join(format!("tinyjazz_lint_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let v_path = dir.join(format!("{}.v", name));
+    write(&v_path, verilog).unwrap();
+    let linters: [(&str, &[&str]); 2] = [
+        ("iverilog", &["-o", "/dev/null"]),
+        ("verilator", &["--lint-only", "-Wno-fatal"]),
+    ];
+    let status = linters.iter().find_map(|(linter, args)| {
+        Command::new(linter)
+            .args(*args)
+            .arg(&v_path)
+            .stdout(Stdio::null())
+            .status()
+            .ok()
+    });
+    std::fs::remove_dir_all(&dir).ok();
+    match status {
+        Some(status) if !status.success() => Err("the Verilog code is rejected".to_string()),
+        _ => Ok(()),
+    }
+}
+
+//the compiler must give the same netlist every time, or the expected netlists are useless
+#[test]
+fn test_deterministic_netlist() {
+    check_all(corpus("pass", "tj"), |path| {
+        let mut netlists = Vec::new();
+        for _ in 0..2 {
+            let mut netlist = Vec::new();
+            backends::netlist::to_netlist(&compile(path, true)?, &mut netlist).unwrap();
+            netlists.push(canonical_netlist(&String::from_utf8(netlist).unwrap()));
+        }
+        if netlists[0] != netlists[1] {
+            return Err("two compilations gave different netlists".to_string());
+        }
+        Ok(())
+    });
+}
@@ -4,12 +4,17 @@ use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use std::rc::Rc;
 
 use crate::frontend::{
+    concurrency::ConcurrencyWarning,
+    dead_code::DeadCodeWarning,
+    conditionals::DynamicIfError,
     constants::ComputeConstError,
-    functions::{ExpandFnError, REC_DEPTH},
+    from_netlist::NetlistError,
+    functions::ExpandFnError,
     hierarchical_automata::CollapseAutomataError,
     parser_wrapper::{ParseErrorType, ParserError},
-    typing::TypingError,
+    typing::{TypingError, TypingWarning},
 };
+use crate::util::schedule::CombinationalCycle;
 
 /*
 This file is dedicated to the handling of all errors, to pretty print them using codespan_diagnostic.
@@ -21,6 +26,9 @@ pub enum ErrorType {
     ExpandFn(ExpandFnError),
     Typing(TypingError),
     ColAutomata(CollapseAutomataError),
+    DynamicIf(DynamicIfError),
+    Cycle(CombinationalCycle),
+    Netlist(NetlistError),
 }
 
 fn get_diagnostic(
@@ -57,6 +65,10 @@ fn get_diagnostic(
                 ParseErrorType::Other(s) => Diagnostic::error()
                     .with_message(format!("Error : {}", s))
                     .with_code("E0004"),
+                ParseErrorType::Invalid(l, r, s) => Diagnostic::error()
+                    .with_message("Error : invalid token")
+                    .with_code("E0004")
+                    .with_labels(vec![Label::primary(*file_id, *l..*r).with_message(s.clone())]),
             },
         },
         ErrorType::ComputeConst(const_error) => match const_error {
@@ -78,14 +90,52 @@ fn get_diagnostic(
                 .with_code("E0004"),
         },
         ErrorType::ExpandFn(fn_error) => match fn_error {
-            ExpandFnError::StackOverflow(name) => Diagnostic::error()
-                .with_message("Error : stack overflow")
-                .with_code("E0007")
-                .with_labels(vec![Label::primary(name.loc.0, name.loc.1..name.loc.2)])
-                .with_message(format!(
-                    "Function {} was expanded recursively more than {} times without finishing.",
-                    name.value, REC_DEPTH
-                )),
+            ExpandFnError::StackOverflow(calls) => {
+                //each call site is shown once, with the first call made there
+                let sites: Vec<_> = calls
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, call)| calls[..*i].iter().all(|c| c.loc != call.loc))
+                    .map(|(_, call)| call)
+                    .collect();
+                //the middle of a long chain is elided
+                let names: Vec<&str> = calls.iter().map(|call| call.value.as_str()).collect();
+                let chain = if names.len() > 8 {
+                    format!(
+                        "{} -> ... -> {}",
+                        names[..4].join(" -> "),
+                        names[names.len() - 3..].join(" -> ")
+                    )
+                } else {
+                    names.join(" -> ")
+                };
+                Diagnostic::error()
+                    .with_message("Error : stack overflow")
+                    .with_code("E0007")
+                    .with_labels(
+                        sites
+                            .iter()
+                            .enumerate()
+                            .map(|(i, call)| {
+                                if i == 0 {
+                                    Label::primary(call.loc.0, call.loc.1..call.loc.2)
+                                } else {
+                                    Label::secondary(call.loc.0, call.loc.1..call.loc.2)
+                                }
+                                .with_message(format!("{} is called here", call.value))
+                            })
+                            .collect(),
+                    )
+                    .with_notes(vec![
+                        format!(
+                            "Calls were expanded {} levels deep without finishing : {}",
+                            calls.len(),
+                            chain
+                        ),
+                        "Check the base case of the recursion, or raise the limit with --rec-depth"
+                            .to_string(),
+                    ])
+            }
             ExpandFnError::WrongNumber(typ, (file_id, l, r), _name, expected, got) => {
                 Diagnostic::error()
                     .with_message(format!("Error : wrong number of {}", typ))
@@ -169,11 +219,6 @@ fn get_diagnostic(
                     "Index out of range : index is {} for a bus of length {}",
                     got, len
                 )),
-            TypingError::LocalVarInUnless(loc, name) => Diagnostic::error()
-                .with_message("Error : local var in strong transition")
-                .with_code("E0021")
-                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
-                .with_message(format!("Local var {} in strong transition", name)),
             TypingError::ConflictingStateShared(loc1, name, loc2) => Diagnostic::error()
                 .with_message("Error : Conflicting node name and shared variable name")
                 .with_code("E0025")
@@ -185,6 +230,19 @@ fn get_diagnostic(
                     "A node and a shared variable cannot have the same name {}",
                     name
                 )),
+            TypingError::LiteralOverflow(loc, width) => Diagnostic::error()
+                .with_message("Error : literal too wide")
+                .with_code("E0024")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
+                .with_message(format!("This literal does not fit in {} bits", width)),
+            TypingError::SingleBitAdd(loc) => Diagnostic::error()
+                .with_message("Error : addition of single bits")
+                .with_code("E0032")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)
+                    .with_message("+ is the addition, it is not the or anymore")])
+                .with_message(
+                    "+ on single bits is the addition modulo 2 : write or for the disjunction, or ^ for the sum",
+                ),
             TypingError::NonSharedInLast(loc, name) => Diagnostic::error()
                 .with_message("Error : Non-shared var in last")
                 .with_code("E0026")
@@ -192,10 +250,31 @@ fn get_diagnostic(
                 .with_message(format!("Non shared var {} in last", name)),
         },
         ErrorType::ColAutomata(err) => match err {
-            CollapseAutomataError::CyclicAutomatonCall(s) => Diagnostic::error()
+            CollapseAutomataError::CyclicAutomatonCall(uses) => Diagnostic::error()
                 .with_message("Error : cyclic module calls")
                 .with_code("E0019")
-                .with_message(format!("Module {} called itself", s)),
+                .with_labels(
+                    uses.iter()
+                        .enumerate()
+                        .map(|(i, (loc, caller, called))| {
+                            if i == 0 {
+                                Label::primary(loc.0, loc.1..loc.2)
+                            } else {
+                                Label::secondary(loc.0, loc.1..loc.2)
+                            }
+                            .with_message(format!("{} uses {}", caller, called))
+                        })
+                        .collect(),
+                )
+                .with_notes(vec![format!(
+                    "Module {} uses itself : {} -> {}",
+                    uses[0].1,
+                    uses.iter()
+                        .map(|(_, caller, _)| caller.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" -> "),
+                    uses[0].1
+                )]),
             CollapseAutomataError::NoMainAutomaton => Diagnostic::error()
                 .with_message("Error : no main module")
                 .with_code("E0020")
@@ -218,20 +297,317 @@ fn get_diagnostic(
                     "Wrong number of {}:expected {}, got {}",
                     typ, expected, got
                 )),
+            CollapseAutomataError::InstantLoop(transitions) => Diagnostic::error()
+                .with_message("Error : loop of instantaneous transitions")
+                .with_code("E0030")
+                .with_labels(
+                    transitions
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (loc, from, to))| {
+                            if i == 0 {
+                                Label::primary(loc.0, loc.1..loc.2)
+                            } else {
+                                Label::secondary(loc.0, loc.1..loc.2)
+                            }
+                            .with_message(format!("{} goes to {} in the same cycle", from, to))
+                        })
+                        .collect(),
+                )
+                .with_notes(vec![format!(
+                    "State {} would be entered again in the same cycle : {} -> {}",
+                    transitions[0].1,
+                    transitions
+                        .iter()
+                        .map(|(_, from, _)| from.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" -> "),
+                    transitions[0].1
+                )]),
+            CollapseAutomataError::LocalVarInUnless(loc, name) => Diagnostic::error()
+                .with_message("Error : local var in strong transition")
+                .with_code("E0021")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
+                .with_message(format!(
+                    "Local var {} in strong transition. The condition is computed before the state",
+                    name
+                )),
+        },
+        ErrorType::DynamicIf(err) => match err {
+            DynamicIfError::AssignedInOneBlock(loc, name) => Diagnostic::error()
+                .with_message("Error : variable assigned in only one block of an if")
+                .with_code("E0022")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2).with_message(
+                    format!("{} has no value when the other block is selected", name),
+                )])
+                .with_notes(vec![format!(
+                    "Assign {} in both blocks, or make it a shared variable to keep its previous value",
+                    name
+                )]),
+            DynamicIfError::UseInIf(loc, name) => Diagnostic::error()
+                .with_message("Error : automaton used in an if")
+                .with_code("E0023")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
+                .with_message(format!(
+                    "Automaton {} is used in an if whose condition is a signal. Use it in a state instead",
+                    name
+                )),
+        },
+        ErrorType::Cycle(CombinationalCycle(vars)) => {
+            let mut names: Vec<&str> = Vec::new();
+            for (name, _) in vars {
+                if !names.contains(&name.as_str()) {
+                    names.push(name)
+                }
+            }
+            let mut notes = Vec::new();
+            if let Some(first) = names.first() {
+                notes.push(format!(
+                    "The cycle goes through {}: {} cannot be computed before itself",
+                    names.join(", "),
+                    first
+                ));
+            }
+            notes.push(
+                "Break the cycle with reg, or read the previous value of a shared variable with last"
+                    .to_string(),
+            );
+            Diagnostic::error()
+                .with_message("Error : combinational cycle")
+                .with_code("E0005")
+                .with_labels(
+                    vars.iter()
+                        .filter_map(|(name, loc)| {
+                            loc.map(|loc| {
+                                Label::primary(loc.0, loc.1..loc.2)
+                                    .with_message(format!("{} is part of the cycle", name))
+                            })
+                        })
+                        .collect(),
+                )
+                .with_notes(notes)
+        }
+        ErrorType::Netlist(err) => match err {
+            NetlistError::UndeclaredVar(loc, name) => Diagnostic::error()
+                .with_message("Error : undeclared variable")
+                .with_code("E0027")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
+                .with_message(format!("Variable {} is not declared in the VAR section", name)),
+            NetlistError::UndefinedVar(loc, name) => Diagnostic::error()
+                .with_message("Error : undefined variable")
+                .with_code("E0028")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
+                .with_message(format!(
+                    "Variable {} is never assigned and is not an input",
+                    name
+                )),
+            NetlistError::MultipleDeclaration(loc1, name, loc2) => Diagnostic::error()
+                .with_message("Error : duplicate variable")
+                .with_code("E0013")
+                .with_labels(vec![
+                    Label::primary(loc1.0, loc1.1..loc1.2),
+                    Label::secondary(loc2.0, loc2.1..loc2.2)
+                        .with_message("first declared here"),
+                ])
+                .with_message(format!("Duplicate variable {}", name)),
+            NetlistError::MultipleAssign(loc1, name, loc2) => Diagnostic::error()
+                .with_message("Error : multiple assignment")
+                .with_code("E0029")
+                .with_labels(vec![
+                    Label::primary(loc1.0, loc1.1..loc1.2),
+                    Label::secondary(loc2.0, loc2.1..loc2.2)
+                        .with_message("first assigned here"),
+                ])
+                .with_message(format!("Variable {} is assigned more than once", name)),
+            NetlistError::WrongSize(loc1, name, expected, got, loc2) => Diagnostic::error()
+                .with_message("Error : msimatched bus lengths")
+                .with_code("E0011")
+                .with_labels(vec![
+                    Label::primary(loc1.0, loc1.1..loc1.2)
+                        .with_message(format!("expected length {}", expected)),
+                    Label::secondary(loc2.0, loc2.1..loc2.2)
+                        .with_message(format!("declared with length {}", got)),
+                ])
+                .with_message(format!(
+                    "The variable {} has length {}, but length {} was expected",
+                    name, got, expected
+                )),
+            NetlistError::IndexOutOfRange(loc, got, len) => Diagnostic::error()
+                .with_message("Error : index out of range")
+                .with_code("E0018")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
+                .with_message(format!(
+                    "Index out of range : index is {} for a bus of length {}",
+                    got, len
+                )),
+            NetlistError::ReversedSlice(loc, first, last) => Diagnostic::error()
+                .with_message("Error : reversed slice")
+                .with_code("E0031")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)])
+                .with_message(format!(
+                    "The slice from index {} to index {} is reversed",
+                    first, last
+                )),
+        },
+    }
+}
+
+//the warnings are printed, and the compilation goes on
+pub enum WarningType {
+    Concurrency(ConcurrencyWarning),
+    DeadCode(DeadCodeWarning),
+    Typing(TypingWarning),
+}
+
+fn get_warning_diagnostic(warning_type: &WarningType) -> Diagnostic<usize> {
+    match warning_type {
+        WarningType::Concurrency(ConcurrencyWarning::MultipleAssign(name, loc1, loc2)) => {
+            Diagnostic::warning()
+                .with_message("Warning : shared variable assigned by two states")
+                .with_code("W0001")
+                .with_labels(vec![
+                    Label::primary(loc2.0, loc2.1..loc2.2)
+                        .with_message(format!("{} is assigned here", name)),
+                    Label::secondary(loc1.0, loc1.1..loc1.2)
+                        .with_message("and here, by a state which can be active at the same time"),
+                ])
+                .with_notes(vec![format!(
+                    "When both states are active, {} only takes one of the values. The simulation reports the cycles where it happens",
+                    name
+                )])
+        }
+        WarningType::DeadCode(warning) => match warning {
+            DeadCodeWarning::UnusedShared(name, loc) => Diagnostic::warning()
+                .with_message("Warning : unused shared variable")
+                .with_code("W0002")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)
+                    .with_message(format!("{} is never read", name))])
+                .with_notes(vec![format!(
+                    "The value of {} is lost, the variable can be removed",
+                    name
+                )]),
+            DeadCodeWarning::UnreachableState(name, loc) => Diagnostic::warning()
+                .with_message("Warning : unreachable state")
+                .with_code("W0003")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)
+                    .with_message(format!("{} is never active", name))])
+                .with_notes(vec![
+                    "The state is not an init state, and no transition leads to it from one"
+                        .to_string(),
+                ]),
+            DeadCodeWarning::UnusedFunction(name, loc) => Diagnostic::warning()
+                .with_message("Warning : unused function")
+                .with_code("W0004")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)
+                    .with_message(format!("{} is never called", name))]),
+            DeadCodeWarning::DuplicateDefault(loc, default_loc) => Diagnostic::warning()
+                .with_message("Warning : several default transitions")
+                .with_code("W0005")
+                .with_labels(vec![
+                    Label::primary(loc.0, loc.1..loc.2)
+                        .with_message("This default transition is taken too"),
+                    Label::secondary(default_loc.0, default_loc.1..default_loc.2)
+                        .with_message("when this one is taken"),
+                ])
+                .with_notes(vec![
+                    "When none of the conditions is true, all the default transitions are taken, and all their states become active"
+                        .to_string(),
+                ]),
+            DeadCodeWarning::UnusedInput(name, loc) => Diagnostic::warning()
+                .with_message("Warning : unused input")
+                .with_code("W0006")
+                .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)
+                    .with_message(format!("{} does not influence any output", name))]),
         },
+        WarningType::Typing(TypingWarning::BusAdd(loc)) => Diagnostic::warning()
+            .with_message("Warning : + on buses")
+            .with_code("W0007")
+            .with_labels(vec![Label::primary(loc.0, loc.1..loc.2)
+                .with_message("+ is the addition of the buses read as numbers")])
+            .with_notes(vec![
+                "+ used to be the or : the designs written for it must use or instead".to_string(),
+            ]),
+    }
+}
+
+pub struct TinyjazzWarning {
+    warning: WarningType,
+    files: Rc<SimpleFiles<String, String>>,
+}
+impl TinyjazzWarning {
+    //the code of the warning, like "W0001", which the tests check
+    #[cfg(test)]
+    pub fn code(&self) -> Option<String> {
+        get_warning_diagnostic(&self.warning).code
+    }
+
+    pub fn print(&self) -> std::fmt::Result {
+        let diagnostic = get_warning_diagnostic(&self.warning);
+        let config = codespan_reporting::term::Config::default();
+        let mut writer = StandardStream::stderr(ColorChoice::Always);
+        codespan_reporting::term::emit(&mut writer, &config, &*self.files, &diagnostic).unwrap();
+        Ok(())
+    }
+}
+impl From<(ConcurrencyWarning, Rc<SimpleFiles<String, String>>)> for TinyjazzWarning {
+    fn from(warning: (ConcurrencyWarning, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (concurrency_warning, files) = warning;
+        TinyjazzWarning {
+            warning: WarningType::Concurrency(concurrency_warning),
+            files,
+        }
+    }
+}
+impl From<(DeadCodeWarning, Rc<SimpleFiles<String, String>>)> for TinyjazzWarning {
+    fn from(warning: (DeadCodeWarning, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (dead_code_warning, files) = warning;
+        TinyjazzWarning {
+            warning: WarningType::DeadCode(dead_code_warning),
+            files,
+        }
+    }
+}
+
+impl From<(TypingWarning, Rc<SimpleFiles<String, String>>)> for TinyjazzWarning {
+    fn from(warning: (TypingWarning, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (typing_warning, files) = warning;
+        TinyjazzWarning {
+            warning: WarningType::Typing(typing_warning),
+            files,
+        }
     }
 }
 
+//the errors found by a pass. Most passes stop at the first one,
+//but the parser and the typing report every independent error they find.
 pub struct TinyjazzError {
-    error: ErrorType,
+    errors: Vec<ErrorType>,
     files: Rc<SimpleFiles<String, String>>,
 }
 impl TinyjazzError {
+    //the code of the first error, like "E0011", which the tests check
+    #[cfg(test)]
+    pub fn code(&self) -> Option<String> {
+        self.codes().into_iter().next().flatten()
+    }
+
+    //the codes of all the errors, in the order they are printed
+    #[cfg(test)]
+    pub fn codes(&self) -> Vec<Option<String>> {
+        self.errors
+            .iter()
+            .map(|error| get_diagnostic(error, self.files.clone()).code)
+            .collect()
+    }
+
     pub fn print(&self) -> std::fmt::Result {
-        let diagnostic = get_diagnostic(&self.error, self.files.clone());
         let config = codespan_reporting::term::Config::default();
         let mut writer = StandardStream::stderr(ColorChoice::Always);
-        codespan_reporting::term::emit(&mut writer, &config, &*self.files, &diagnostic).unwrap();
+        for error in &self.errors {
+            let diagnostic = get_diagnostic(error, self.files.clone());
+            codespan_reporting::term::emit(&mut writer, &config, &*self.files, &diagnostic)
+                .unwrap();
+        }
         Ok(())
     }
 }
@@ -240,7 +616,16 @@ impl From<(ParserError, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
     fn from(err: (ParserError, Rc<SimpleFiles<String, String>>)) -> Self {
         let (parse_error, files) = err;
         TinyjazzError {
-            error: ErrorType::Parser(parse_error),
+            errors: vec![ErrorType::Parser(parse_error)],
+            files,
+        }
+    }
+}
+impl From<(Vec<ParserError>, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
+    fn from(err: (Vec<ParserError>, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (parse_errors, files) = err;
+        TinyjazzError {
+            errors: parse_errors.into_iter().map(ErrorType::Parser).collect(),
             files,
         }
     }
@@ -249,7 +634,7 @@ impl From<(ComputeConstError, Rc<SimpleFiles<String, String>>)> for TinyjazzErro
     fn from(err: (ComputeConstError, Rc<SimpleFiles<String, String>>)) -> Self {
         let (const_error, files) = err;
         TinyjazzError {
-            error: ErrorType::ComputeConst(const_error),
+            errors: vec![ErrorType::ComputeConst(const_error)],
             files,
         }
     }
@@ -259,7 +644,7 @@ impl From<(ExpandFnError, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
     fn from(err: (ExpandFnError, Rc<SimpleFiles<String, String>>)) -> Self {
         let (fn_error, files) = err;
         TinyjazzError {
-            error: ErrorType::ExpandFn(fn_error),
+            errors: vec![ErrorType::ExpandFn(fn_error)],
             files,
         }
     }
@@ -269,7 +654,17 @@ impl From<(TypingError, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
     fn from(err: (TypingError, Rc<SimpleFiles<String, String>>)) -> Self {
         let (typ_error, files) = err;
         TinyjazzError {
-            error: ErrorType::Typing(typ_error),
+            errors: vec![ErrorType::Typing(typ_error)],
+            files,
+        }
+    }
+}
+
+impl From<(Vec<TypingError>, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
+    fn from(err: (Vec<TypingError>, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (typ_errors, files) = err;
+        TinyjazzError {
+            errors: typ_errors.into_iter().map(ErrorType::Typing).collect(),
             files,
         }
     }
@@ -279,7 +674,35 @@ impl From<(CollapseAutomataError, Rc<SimpleFiles<String, String>>)> for Tinyjazz
     fn from(err: (CollapseAutomataError, Rc<SimpleFiles<String, String>>)) -> Self {
         let (typ_error, files) = err;
         TinyjazzError {
-            error: ErrorType::ColAutomata(typ_error),
+            errors: vec![ErrorType::ColAutomata(typ_error)],
+            files,
+        }
+    }
+}
+
+impl From<(DynamicIfError, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
+    fn from(err: (DynamicIfError, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (if_error, files) = err;
+        TinyjazzError {
+            errors: vec![ErrorType::DynamicIf(if_error)],
+            files,
+        }
+    }
+}
+impl From<(CombinationalCycle, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
+    fn from(err: (CombinationalCycle, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (cycle, files) = err;
+        TinyjazzError {
+            errors: vec![ErrorType::Cycle(cycle)],
+            files,
+        }
+    }
+}
+impl From<(NetlistError, Rc<SimpleFiles<String, String>>)> for TinyjazzError {
+    fn from(err: (NetlistError, Rc<SimpleFiles<String, String>>)) -> Self {
+        let (netlist_error, files) = err;
+        TinyjazzError {
+            errors: vec![ErrorType::Netlist(netlist_error)],
             files,
         }
     }
@@ -1,3 +1,6 @@
 pub(crate) mod errors;
+pub(crate) mod rom;
+pub(crate) mod schedule;
 pub(crate) mod scripting;
+pub(crate) mod vcd;
 pub(crate) mod viz;
@@ -0,0 +1,170 @@
+use ahash::AHashMap;
+use std::{fmt::Display, fs::read, path::Path};
+/*
+This file loads the content of the roms, to be used by the interpreter.
+An image is read from a file, in a format which depends on the extension of the file :
+-.bin : raw binary, each byte gives 8 bits, most significant bit first
+-.hex : hexadecimal text, each digit gives 4 bits, most significant bit first
+-anything else : one word per line, written with 0s and 1s
+(whitespace and "_" are ignored in text formats)
+In the last format, all the lines which are not empty must have the same width, which must be the
+word size of the roms using the image.
+
+The bits are simply concatenated, so word i is made of the bits [i*word_size, (i+1)*word_size[.
+The first bit of an address is the most significant one.
+Reading outside of the image always gives 0s.
+*/
+#[derive(Debug)]
+pub enum RomError {
+    File(String, std::io::Error),
+    Format(String, usize, char), //file, line, unexpected character
+    LineWidth(String, usize, usize, usize), //file, line, width of the first line, width of this one
+    WordSize(String, usize, String, usize), //file, width of its lines, rom, word size of the rom
+}
+
+impl Display for RomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RomError::File(path, err) => write!(f, "Error : cannot read rom file {} : {}", path, err),
+            RomError::Format(path, line, c) => write!(
+                f,
+                "Error : unexpected character {:?} in rom file {}, line {}",
+                c, path, line
+            ),
+            RomError::LineWidth(path, line, expected, width) => write!(
+                f,
+                "Error : line {} of rom file {} has {} bits, but the first word has {}",
+                line, path, width, expected
+            ),
+            RomError::WordSize(path, width, name, word_size) => write!(
+                f,
+                "Error : the words of rom file {} have {} bits, but the rom {} reads words of {} bits",
+                path, width, name, word_size
+            ),
+        }
+    }
+}
+
+//The images of all the roms of the program.
+//A rom uses the image given for its name if there is one, else the default image.
+#[derive(Debug, Clone, Default)]
+pub struct RomImages {
+    default: Option<Vec<bool>>,
+    named: AHashMap<String, Vec<bool>>,
+    //the file and the width of the words of the images written one word per line (None for the default one)
+    widths: AHashMap<Option<String>, (String, usize)>,
+}
+
+impl RomImages {
+    //Each spec is either "<file>", to set the default image, or "<name>=<file>".
+    pub fn from_specs(specs: &[String]) -> Result<Self, RomError> {
+        let mut images = RomImages::default();
+        for spec in specs {
+            let (name, path) = match spec.split_once('=') {
+                Some((name, path)) => (Some(name.to_string()), path),
+                None => (None, &**spec),
+            };
+            let (image, width) = load_image(path)?;
+            if let Some(width) = width {
+                images
+                    .widths
+                    .insert(name.clone(), (path.to_string(), width));
+            }
+            match name {
+                Some(name) => images.named.insert(name, image),
+                None => images.default.replace(image),
+            };
+        }
+        Ok(images)
+    }
+
+    //the image used by the rom called name
+    pub fn image(&self, name: &str) -> Option<&Vec<bool>> {
+        self.named.get(name).or(self.default.as_ref())
+    }
+
+    //the image of the rom called name must be made of words of its word size, when they are written one per line
+    pub fn check_word_size(&self, name: &str, word_size: usize) -> Result<(), RomError> {
+        let key = Some(name.to_string()).filter(|n| self.named.contains_key(n));
+        match self.widths.get(&key) {
+            Some((path, width)) if *width != word_size => Err(RomError::WordSize(
+                path.clone(),
+                *width,
+                name.to_string(),
+                word_size,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    //read the word at the given address in the rom called name.
+    pub fn read(&self, name: &str, addr: &[bool], word_size: usize) -> Vec<bool> {
+        let image = match self.image(name) {
+            Some(image) => image,
+            None => return vec![false; word_size],
+        };
+        let mut index: usize = 0;
+        for b in addr {
+            index = match index.checked_mul(2) {
+                Some(i) => i + *b as usize,
+                None => return vec![false; word_size],
+            }
+        }
+        let start = index.saturating_mul(word_size);
+        (start..start.saturating_add(word_size))
+            .map(|i| image.get(i).cloned().unwrap_or(false))
+            .collect()
+    }
+}
+
+//the bits of the image, and the width of its lines when it is written one word per line
+pub fn load_image(path: &str) -> Result<(Vec<bool>, Option<usize>), RomError> {
+    let content = read(path).map_err(|e| RomError::File(path.to_string(), e))?;
+    let extension = Path::new(path).extension().and_then(|e| e.to_str());
+    if extension == Some("bin") {
+        let bits = content
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+        return Ok((bits, None));
+    }
+    let text = String::from_utf8_lossy(&content);
+    let mut bits = Vec::new();
+    let mut width = None;
+    for (line_nb, line) in text.lines().enumerate() {
+        let line_start = bits.len();
+        for c in line.chars() {
+            if c.is_whitespace() || c == '_' {
+                continue;
+            }
+            let err = || RomError::Format(path.to_string(), line_nb + 1, c);
+            if extension == Some("hex") {
+                let digit = c.to_digit(16).ok_or_else(err)?;
+                bits.extend((0..4).rev().map(|i| (digit >> i) & 1 == 1));
+            } else {
+                match c {
+                    '0' => bits.push(false),
+                    '1' => bits.push(true),
+                    _ => return Err(err()),
+                }
+            }
+        }
+        //a word of the wrong width would shift all the following ones
+        let line_width = bits.len() - line_start;
+        if extension != Some("hex") && line_width != 0 {
+            match width {
+                None => width = Some(line_width),
+                Some(w) if w != line_width => {
+                    return Err(RomError::LineWidth(
+                        path.to_string(),
+                        line_nb + 1,
+                        w,
+                        line_width,
+                    ))
+                }
+                _ => (),
+            }
+        }
+    }
+    Ok((bits, width))
+}
@@ -0,0 +1,119 @@
+use ahash::AHashSet;
+
+use crate::ast::graph::*;
+/*
+Orders the nodes of a program so that every node comes after the nodes it depends on
+during a cycle. Registers and rams break the cycles : their value is known at the beginning
+of the cycle, and their inputs are only used at the end of it (a ram only needs its read address).
+This is used by the simulator and the backends generating code, and to reject the programs
+with a combinational cycle when they are compiled.
+*/
+//the variables named by the user on the cycle, each one computed from the next one,
+//with where they are assigned and declared when the program was compiled from a .tj file
+#[derive(Debug)]
+pub struct CombinationalCycle(pub Vec<(String, Option<Pos>)>);
+
+//the nodes needed to compute a node during the same cycle
+pub fn dependencies(node: &Node) -> Vec<RCell<Node>> {
+    match node {
+        Node::Input(_) | Node::Const(_) | Node::Reg(_, _) | Node::TmpValueHolder(_) => Vec::new(),
+        Node::Not(e) | Node::Slice(e, _, _) | Node::Rom(_, _, _, e) => vec![e.clone()],
+        Node::Ram(_, _, e, _, _, _) => vec![e.clone()],
+        Node::BiOp(_, e1, e2) => vec![e1.clone(), e2.clone()],
+        Node::Mux(e1, e2, e3) => vec![e1.clone(), e2.clone(), e3.clone()],
+    }
+}
+
+//the nodes used at the end of the cycle
+pub fn late_dependencies(node: &Node) -> Vec<RCell<Node>> {
+    match node {
+        Node::Reg(_, e) => vec![e.clone()],
+        Node::Ram(_, _, _, e1, e2, e3) => vec![e1.clone(), e2.clone(), e3.clone()],
+        _ => Vec::new(),
+    }
+}
+
+//all the nodes needed to compute the outputs, the probes and the states of the conflicts, in scheduling order
+pub fn schedule(prog: &FlatProgramGraph) -> Result<Vec<RCell<Node>>, CombinationalCycle> {
+    let mut order = Vec::new();
+    let mut done = AHashSet::new();
+    let mut roots: Vec<RCell<Node>> = prog
+        .outputs
+        .iter()
+        .chain(prog.probes.iter())
+        .map(|(_, node)| node.clone())
+        .chain(
+            prog.conflicts
+                .iter()
+                .flat_map(|(_, s1, s2)| vec![s1.clone(), s2.clone()]),
+        )
+        .collect();
+    //the late dependencies are scheduled after everything else, as new roots
+    let mut next = 0;
+    while next < order.len() || !roots.is_empty() {
+        for root in std::mem::take(&mut roots) {
+            schedule_node(root, &mut order, &mut done)
+                .map_err(|cycle| CombinationalCycle(cycle_vars(prog, cycle)))?;
+        }
+        while next < order.len() {
+            roots.extend(late_dependencies(&order[next].borrow()));
+            next += 1;
+        }
+    }
+    Ok(order)
+}
+
+//iterative depth first search, as a recursive one overflows the stack on long combinational chains
+fn schedule_node(
+    root: RCell<Node>,
+    order: &mut Vec<RCell<Node>>,
+    done: &mut AHashSet<u32>,
+) -> Result<(), Vec<RCell<Node>>> {
+    let mut in_progress = AHashSet::new();
+    let mut stack = vec![(root, 0)];
+    while let Some((node, i)) = stack.pop() {
+        if done.contains(&node.id()) {
+            continue;
+        }
+        if i == 0 && !in_progress.insert(node.id()) {
+            //the nodes in progress are on the stack, from the root to the last one visited
+            let start = stack.iter().position(|(n, _)| *n == node).unwrap();
+            return Err(stack.drain(start..).map(|(n, _)| n).collect());
+        }
+        let deps = dependencies(&node.borrow());
+        if i < deps.len() {
+            let dep = deps[i].clone();
+            stack.push((node, i + 1));
+            if !done.contains(&dep.id()) {
+                stack.push((dep, 0));
+            }
+            continue;
+        }
+        in_progress.remove(&node.id());
+        done.insert(node.id());
+        order.push(node);
+    }
+    Ok(())
+}
+
+//names the nodes of a cycle with the variables computed by them
+fn cycle_vars(prog: &FlatProgramGraph, cycle: Vec<RCell<Node>>) -> Vec<(String, Option<Pos>)> {
+    let mut vars: Vec<(String, Option<Pos>)> = Vec::new();
+    for node in cycle {
+        let names = prog
+            .locations
+            .iter()
+            .map(|(name, loc, n)| (name, Some(*loc), n))
+            .chain(prog.probes.iter().map(|(name, n)| (name, None, n)));
+        for (name, loc, _) in names.filter(|(_, _, n)| **n == node) {
+            //the probes only name the variables which are not located
+            if !vars
+                .iter()
+                .any(|(v, l)| v == name && (*l == loc || loc.is_none()))
+            {
+                vars.push((name.clone(), loc));
+            }
+        }
+    }
+    vars
+}
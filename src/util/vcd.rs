@@ -0,0 +1,84 @@
+use std::io::Write;
+/*
+This file writes the traces of the interpreter in the VCD (Value Change Dump) format,
+so they can be opened with a waveform viewer such as GTKWave.
+Each cycle of the simulation is one time unit, and a value is only written when it changes.
+*/
+pub struct VcdWriter {
+    dest: Box<dyn Write>,
+    ids: Vec<String>,
+    sizes: Vec<usize>,
+    prev: Vec<Option<Vec<bool>>>,
+    time: usize,
+}
+
+impl VcdWriter {
+    //writes the header declaring every signal (name, size)
+    pub fn new(mut dest: Box<dyn Write>, signals: &[(String, usize)]) -> std::io::Result<Self> {
+        writeln!(dest, "$version tinyjazz 0.0.1 $end")?;
+        writeln!(dest, "$timescale 1 ns $end")?;
+        writeln!(dest, "$scope module main $end")?;
+        let ids: Vec<String> = (0..signals.len()).map(identifier).collect();
+        for ((name, size), id) in signals.iter().zip(ids.iter()) {
+            //references can't contain whitespace
+            let name: String = name
+                .chars()
+                .map(|c| if c.is_whitespace() { '_' } else { c })
+                .collect();
+            writeln!(dest, "$var wire {} {} {} $end", size, id, name)?;
+        }
+        writeln!(dest, "$upscope $end")?;
+        writeln!(dest, "$enddefinitions $end")?;
+        Ok(VcdWriter {
+            dest,
+            ids,
+            sizes: signals.iter().map(|(_, s)| *s).collect(),
+            prev: vec![None; signals.len()],
+            time: 0,
+        })
+    }
+
+    //writes the values of a cycle, in the order of the declaration
+    pub fn step(&mut self, values: &[Vec<bool>]) -> std::io::Result<()> {
+        writeln!(self.dest, "#{}", self.time)?;
+        if self.time == 0 {
+            writeln!(self.dest, "$dumpvars")?;
+        }
+        for (i, v) in values.iter().enumerate() {
+            if self.prev[i].as_ref() == Some(v) {
+                continue;
+            }
+            let bits: String = v.iter().map(|b| if *b { '1' } else { '0' }).collect();
+            if self.sizes[i] == 1 {
+                writeln!(self.dest, "{}{}", bits, self.ids[i])?;
+            } else {
+                writeln!(self.dest, "b{} {}", bits, self.ids[i])?;
+            }
+            self.prev[i] = Some(v.clone());
+        }
+        if self.time == 0 {
+            writeln!(self.dest, "$end")?;
+        }
+        self.time += 1;
+        Ok(())
+    }
+
+    //writes the final timestamp, so the last cycle is displayed
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        writeln!(self.dest, "#{}", self.time)?;
+        self.dest.flush()
+    }
+}
+
+//short identifier of the i-th signal, using the printable ascii characters
+fn identifier(mut i: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (i % 94) as u8) as char);
+        i /= 94;
+        if i == 0 {
+            return id;
+        }
+        i -= 1;
+    }
+}
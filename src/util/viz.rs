@@ -13,17 +13,20 @@ struct Graph {
     nodes: Vec<String>,
     edges: Vec<(usize, usize, String)>,
 }
-
-pub fn render(prog: &FlatProgramGraph) {
-    use std::fs::File;
-    let mut f = File::create("viz.dot").unwrap();
-    render_prog_to(&mut f, prog);
+//the names of the inputs, and of the nodes named by the user
+struct Names<'a> {
+    inputs: Vec<&'a String>,
+    nodes: HashMap<u32, &'a String>,
 }
 
 pub fn render_prog_to<W: Write>(output: &mut W, node: &FlatProgramGraph) {
     let mut nodes_mem = HashMap::new();
     let mut nodes = Vec::new();
     let mut edges = Vec::new();
+    let names = Names {
+        inputs: node.inputs.iter().map(|(name, _)| name).collect(),
+        nodes: node.node_names(),
+    };
     for (s, e) in node.outputs.iter() {
         nodes.push(format!("Output {}", s));
         edges.push((
@@ -31,7 +34,7 @@ pub fn render_prog_to<W: Write>(output: &mut W, node: &FlatProgramGraph) {
             mem(nodes.len(), e.clone(), &mut nodes_mem),
             String::new(),
         ));
-        render_rec(e.clone(), &mut nodes, &mut edges, &mut nodes_mem);
+        render_rec(e.clone(), &mut nodes, &mut edges, &mut nodes_mem, &names);
     }
 
     let graph = Graph { nodes, edges };
@@ -44,13 +47,15 @@ fn render_rec(
     nodes: &mut Vec<String>,
     edges: &mut Vec<(usize, usize, String)>,
     nodes_mem: &mut HashMap<RCell<Node>, usize>,
+    names: &Names,
 ) {
     if nodes_mem.contains_key(&e) {
         return;
     }
-    nodes_mem.insert(e.clone(), nodes.len());
+    let id = nodes.len();
+    nodes_mem.insert(e.clone(), id);
     match &*e.borrow() {
-        Node::Input(i) => nodes.push(format!("Input : {}", i)),
+        Node::Input(i) => nodes.push(format!("Input : {}", names.inputs[*i])),
         Node::Const(c) => nodes.push(format!(
             "{}",
             c.iter()
@@ -64,7 +69,7 @@ fn render_rec(
                 mem(nodes.len(), e.clone(), nodes_mem),
                 String::new(),
             ));
-            render_rec(e.clone(), nodes, edges, nodes_mem)
+            render_rec(e.clone(), nodes, edges, nodes_mem, names)
         }
         Node::Slice(e, c1, c2) => {
             nodes.push(format!("Slice {} {}", c1, c2));
@@ -73,7 +78,7 @@ fn render_rec(
                 mem(nodes.len(), e.clone(), nodes_mem),
                 String::new(),
             ));
-            render_rec(e.clone(), nodes, edges, nodes_mem)
+            render_rec(e.clone(), nodes, edges, nodes_mem, names)
         }
         Node::BiOp(op, e1, e2) => {
             nodes.push(format!("{:?}", op));
@@ -83,9 +88,9 @@ fn render_rec(
                 mem(nodes.len(), e1.clone(), nodes_mem),
                 String::new(),
             ));
-            render_rec(e1.clone(), nodes, edges, nodes_mem);
+            render_rec(e1.clone(), nodes, edges, nodes_mem, names);
             edges.push((id, mem(nodes.len(), e2.clone(), nodes_mem), String::new()));
-            render_rec(e2.clone(), nodes, edges, nodes_mem);
+            render_rec(e2.clone(), nodes, edges, nodes_mem, names);
         }
         Node::Mux(e1, e2, e3) => {
             nodes.push(format!("Mux"));
@@ -95,11 +100,11 @@ fn render_rec(
                 mem(nodes.len(), e1.clone(), nodes_mem),
                 "c".to_string(),
             ));
-            render_rec(e1.clone(), nodes, edges, nodes_mem);
+            render_rec(e1.clone(), nodes, edges, nodes_mem, names);
             edges.push((id, mem(nodes.len(), e2.clone(), nodes_mem), "v".to_string()));
-            render_rec(e2.clone(), nodes, edges, nodes_mem);
+            render_rec(e2.clone(), nodes, edges, nodes_mem, names);
             edges.push((id, mem(nodes.len(), e3.clone(), nodes_mem), "f".to_string()));
-            render_rec(e3.clone(), nodes, edges, nodes_mem);
+            render_rec(e3.clone(), nodes, edges, nodes_mem, names);
         }
         Node::Reg(_, e) => {
             nodes.push(format!("Reg"));
@@ -108,9 +113,9 @@ fn render_rec(
                 mem(nodes.len(), e.clone(), nodes_mem),
                 String::new(),
             ));
-            render_rec(e.clone(), nodes, edges, nodes_mem);
+            render_rec(e.clone(), nodes, edges, nodes_mem, names);
         }
-        Node::Ram(e1, e2, e3, e4) => {
+        Node::Ram(_, _, e1, e2, e3, e4) => {
             nodes.push(format!("Ram"));
             let id = nodes.len() - 1;
             edges.push((
@@ -118,22 +123,22 @@ fn render_rec(
                 mem(nodes.len(), e1.clone(), nodes_mem),
                 String::new(),
             ));
-            render_rec(e1.clone(), nodes, edges, nodes_mem);
+            render_rec(e1.clone(), nodes, edges, nodes_mem, names);
             edges.push((id, mem(nodes.len(), e2.clone(), nodes_mem), String::new()));
-            render_rec(e2.clone(), nodes, edges, nodes_mem);
+            render_rec(e2.clone(), nodes, edges, nodes_mem, names);
             edges.push((id, mem(nodes.len(), e3.clone(), nodes_mem), String::new()));
-            render_rec(e3.clone(), nodes, edges, nodes_mem);
+            render_rec(e3.clone(), nodes, edges, nodes_mem, names);
             edges.push((id, mem(nodes.len(), e4.clone(), nodes_mem), String::new()));
-            render_rec(e4.clone(), nodes, edges, nodes_mem);
+            render_rec(e4.clone(), nodes, edges, nodes_mem, names);
         }
-        Node::Rom(_, e) => {
-            nodes.push(format!("Rom"));
+        Node::Rom(name, _, _, e) => {
+            nodes.push(format!("Rom {}", name));
             edges.push((
                 nodes.len() - 1,
                 mem(nodes.len(), e.clone(), nodes_mem),
                 String::new(),
             ));
-            render_rec(e.clone(), nodes, edges, nodes_mem)
+            render_rec(e.clone(), nodes, edges, nodes_mem, names)
         }
         Node::TmpValueHolder(i) => {
             //panic!("Should not happen : temp value in viz"),
@@ -143,9 +148,12 @@ fn render_rec(
                 mem(nodes.len(), e.clone(), nodes_mem),
                 String::new(),
             ));
-            render_rec(e.clone(), nodes, edges, nodes_mem)
+            render_rec(e.clone(), nodes, edges, nodes_mem, names)
         }
     }
+    if let Some(name) = names.nodes.get(&e.id()) {
+        nodes[id] = format!("{} ({})", nodes[id], name);
+    }
 }
 
 fn mem(i: usize, e: RCell<Node>, nodes_mem: &mut HashMap<RCell<Node>, usize>) -> usize {